@@ -0,0 +1,839 @@
+//! Command-line interface to a Tally database, for scripting imports, reports
+//! and exports without the desktop app, such as from cron.
+//!
+//! Built on `tally-core` like the app, so it reads and writes the database
+//! exactly the way the app does. An encrypted database is opened with the
+//! passphrase in `TALLY_PASSPHRASE`.
+//!
+//! Run `tally-cli help` for the list of commands.
+
+use chrono::{Datelike, Local, Months, NaiveDate};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tally_core::{backup, database, encryption, export, ledger, reports};
+
+const USAGE: &str = "\
+Usage: tally-cli [--database PATH] [--json] <command> [arguments]
+
+Commands:
+  accounts [--archived]
+      List accounts
+  categories
+      List categories with their paths
+  transactions <account> [--limit N]
+      List an account's most recent transactions
+  add <account> <date> <amount> [--description TEXT] [--payee TEXT] [--memo TEXT]
+      [--category CATEGORY] [--cleared] [--pending]
+      Add a transaction; money going out is negative
+  import <file> [--format json|ledger|beancount]
+      Import a Tally JSON export into an empty database, or a journal
+  report spending [--from DATE] [--to DATE] [--account ACCOUNT]
+      [--by category|tag] [--include-archived]
+      Total spending by category or tag
+  report split [--from DATE] [--to DATE] [--account ACCOUNT] [--include-archived]
+      Fixed and discretionary spending per month
+  export <file> [--format json|csv_zip|ledger|beancount]
+      Export everything to a file
+
+Accounts and categories are given by ID or name, and categories also by
+path, such as \"Food > Groceries\". Dates are YYYY-MM-DD; reports cover the
+current month by default. File formats default to the file's extension.
+The database is backed up when it's opened, before it's migrated, and again
+before an import.
+
+Options:
+  --database PATH   Database file (default ./tally.db)
+  --json            Print results as JSON instead of tables
+  --keep-backups N  Number of most recent backups to keep (default 10)
+
+Environment:
+  TALLY_PASSPHRASE        Passphrase of an encrypted database
+  TALLY_BACKUP_RETENTION  Number of most recent backups to keep
+";
+
+/// Options that don't take a value.
+const FLAGS: [&str; 6] = [
+    "archived",
+    "cleared",
+    "help",
+    "include-archived",
+    "json",
+    "pending",
+];
+
+/// Number of transactions `transactions` lists by default.
+const DEFAULT_LIMIT: i32 = 20;
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let result = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => run(args).await,
+        Err(e) => Err(e.into()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Command-line arguments, taken one by one as each command reads them.
+struct Args {
+    positional: VecDeque<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut positional = VecDeque::new();
+        let mut options = HashMap::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                positional.push_back(arg);
+                continue;
+            };
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None if FLAGS.contains(&name) => (name.to_string(), String::new()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("--{name} needs a value"))?;
+                    (name.to_string(), value)
+                }
+            };
+            options.insert(name, value);
+        }
+
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    fn next(&mut self, name: &str) -> Result<String, String> {
+        self.positional
+            .pop_front()
+            .ok_or_else(|| format!("missing <{name}>, see `tally-cli help`"))
+    }
+
+    fn option(&mut self, name: &str) -> Option<String> {
+        self.options.remove(name)
+    }
+
+    fn flag(&mut self, name: &str) -> bool {
+        self.options.remove(name).is_some()
+    }
+
+    /// Fails on anything the command didn't read, so typos aren't ignored.
+    fn finish(self) -> Result<(), String> {
+        if let Some(argument) = self.positional.front() {
+            return Err(format!("unexpected argument `{argument}`"));
+        }
+        if let Some(name) = self.options.keys().next() {
+            return Err(format!("unknown option --{name}"));
+        }
+        Ok(())
+    }
+}
+
+async fn run(mut args: Args) -> CliResult {
+    let json = args.flag("json");
+    let database = PathBuf::from(
+        args.option("database")
+            .unwrap_or_else(|| database::DATABASE_PATH.to_string()),
+    );
+    let command = args.positional.pop_front();
+    if args.flag("help") || matches!(command.as_deref(), None | Some("help")) {
+        print!("{USAGE}");
+        return Ok(());
+    }
+
+    // Opening a missing file would quietly create an empty database
+    if !database.exists() {
+        return Err(format!("{} doesn't exist", database.display()).into());
+    }
+    let passphrase = std::env::var("TALLY_PASSPHRASE").ok();
+    if passphrase.is_none() && encryption::is_encrypted(&database)? {
+        return Err("the database is encrypted, set TALLY_PASSPHRASE".into());
+    }
+    // Backups go next to the database, like the app's
+    let mut backups = backup::BackupConfig::from_env(database.with_file_name("backups"))?;
+    if let Some(value) = args.option("keep-backups") {
+        backups.retention = backup::parse_retention(&value)
+            .ok_or_else(|| format!("--keep-backups must be a number of backups, not `{value}`"))?;
+    }
+    let pool = encryption::unlock(&database, passphrase.as_deref()).await?;
+
+    // Back up before migrating, like the app does at startup
+    backup::backup_on_startup(&pool, &backups).await?;
+    database::create_schema(&pool).await?;
+
+    let result = match command.as_deref().unwrap_or_default() {
+        "accounts" => list_accounts(&pool, args, json).await,
+        "categories" => list_categories(&pool, args, json).await,
+        "transactions" => list_transactions(&pool, args, json).await,
+        "add" => add_transaction(&pool, args, json).await,
+        "import" => import(&pool, &backups, args, json).await,
+        "report" => report(&pool, args, json).await,
+        "export" => export(&pool, args, json).await,
+        other => Err(format!("unknown command `{other}`, see `tally-cli help`").into()),
+    };
+    pool.close().await;
+    result
+}
+
+async fn list_accounts(pool: &SqlitePool, mut args: Args, json: bool) -> CliResult {
+    let archived = args.flag("archived");
+    args.finish()?;
+
+    let accounts = if archived {
+        database::get_archived_accounts(pool).await?
+    } else {
+        database::get_accounts(pool).await?
+    };
+    if json {
+        return print_json(&accounts);
+    }
+
+    print_table(
+        ["ID", "Name", "Type", "Institution", "Balance"],
+        accounts.iter().map(|account| {
+            [
+                account.id.to_string(),
+                account.name.clone(),
+                account.account_type.clone(),
+                account.institution.clone().unwrap_or_default(),
+                account.current_balance.map(money).unwrap_or_default(),
+            ]
+        }),
+    );
+    Ok(())
+}
+
+async fn list_categories(pool: &SqlitePool, args: Args, json: bool) -> CliResult {
+    args.finish()?;
+
+    let tree = database::get_category_tree(pool).await?;
+    if json {
+        return print_json(&tree);
+    }
+
+    print_table(
+        ["ID", "Category", "Uses"],
+        tree.iter().map(|node| {
+            [
+                node.category.id.to_string(),
+                node.path.clone(),
+                node.category.usage_count.to_string(),
+            ]
+        }),
+    );
+    Ok(())
+}
+
+async fn list_transactions(pool: &SqlitePool, mut args: Args, json: bool) -> CliResult {
+    let account = find_account(pool, &args.next("account")?).await?;
+    let limit = match args.option("limit") {
+        Some(limit) => limit
+            .parse()
+            .map_err(|_| format!("--limit must be a number, not `{limit}`"))?,
+        None => DEFAULT_LIMIT,
+    };
+    args.finish()?;
+
+    let page = database::get_transactions(pool, account.id, limit, None).await?;
+    if json {
+        return print_json(&page.transactions);
+    }
+
+    let paths: HashMap<i64, String> = database::get_category_tree(pool)
+        .await?
+        .into_iter()
+        .map(|node| (node.category.id, node.path))
+        .collect();
+    print_table(
+        ["ID", "Date", "Payee", "Description", "Category", "Amount"],
+        page.transactions.iter().map(|transaction| {
+            [
+                transaction.id.to_string(),
+                transaction.date.to_string(),
+                transaction.payee.clone().unwrap_or_default(),
+                transaction.description.clone().unwrap_or_default(),
+                transaction
+                    .category_id
+                    .and_then(|id| paths.get(&id).cloned())
+                    .unwrap_or_default(),
+                money(transaction.amount),
+            ]
+        }),
+    );
+    Ok(())
+}
+
+async fn add_transaction(pool: &SqlitePool, mut args: Args, json: bool) -> CliResult {
+    let account = find_account(pool, &args.next("account")?).await?;
+    let date = parse_date(&args.next("date")?)?;
+    let amount = args.next("amount")?;
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| format!("`{amount}` isn't an amount"))?;
+    let category_id = match args.option("category") {
+        Some(category) => Some(find_category(pool, &category).await?),
+        None => None,
+    };
+
+    let request = database::CreateTransactionRequest {
+        account_id: account.id,
+        date,
+        amount,
+        description: args.option("description"),
+        payee: args.option("payee"),
+        memo: args.option("memo"),
+        category_id,
+        pending: args.flag("pending"),
+        cleared: args.flag("cleared"),
+        tag_ids: None,
+        spending_kind: None,
+    };
+    args.finish()?;
+
+    let id = database::insert_transaction(pool, &request).await?;
+    if json {
+        return print_json(&serde_json::json!({ "id": id }));
+    }
+    println!("Added transaction {id} to {}", account.name);
+    Ok(())
+}
+
+async fn import(
+    pool: &SqlitePool,
+    backups: &backup::BackupConfig,
+    mut args: Args,
+    json: bool,
+) -> CliResult {
+    let path = PathBuf::from(args.next("file")?);
+    let format = FileFormat::of(&path, args.option("format"))?;
+    args.finish()?;
+
+    if format == FileFormat::CsvZip {
+        return Err("CSV exports can't be imported".into());
+    }
+    if !path.exists() {
+        return Err(format!("{} doesn't exist", path.display()).into());
+    }
+
+    backup::create_backup(pool, backups, backup::BackupReason::Import).await?;
+
+    match format {
+        FileFormat::Json | FileFormat::CsvZip => {
+            let counts = export::import_from_file(pool, &path).await?;
+            if json {
+                return print_json(&counts);
+            }
+            println!(
+                "Imported {} accounts, {} categories, {} tags, {} transactions and {} transfers",
+                counts.accounts,
+                counts.categories,
+                counts.tags,
+                counts.transactions,
+                counts.transfers
+            );
+        }
+        FileFormat::Journal(format) => {
+            let import = ledger::import_ledger(pool, &path, format).await?;
+            if json {
+                return print_json(&import);
+            }
+            println!(
+                "Imported {} transactions and {} transfers, creating {} accounts, {} categories and {} tags",
+                import.transactions,
+                import.transfers,
+                import.accounts,
+                import.categories,
+                import.tags
+            );
+            for skipped in &import.skipped {
+                println!("Skipped line {}: {}", skipped.line, skipped.reason);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn report(pool: &SqlitePool, mut args: Args, json: bool) -> CliResult {
+    let kind = args.next("report")?;
+    let this_month = Local::now().date_naive().with_day(1).unwrap_or_default();
+    let start_date = match args.option("from") {
+        Some(date) => parse_date(&date)?,
+        None => this_month,
+    };
+    let end_date = match args.option("to") {
+        Some(date) => parse_date(&date)?,
+        None => this_month + Months::new(1) - chrono::Days::new(1),
+    };
+    let account_id = match args.option("account") {
+        Some(account) => Some(find_account(pool, &account).await?.id),
+        None => None,
+    };
+    let include_archived = args.flag("include-archived");
+
+    match kind.as_str() {
+        "spending" => {
+            let group_by = match args.option("by").as_deref() {
+                None | Some("category") => reports::ReportGrouping::Category,
+                Some("tag") => reports::ReportGrouping::Tag,
+                Some(other) => {
+                    return Err(format!("--by must be category or tag, not `{other}`").into());
+                }
+            };
+            args.finish()?;
+
+            let request = reports::SpendingReportRequest {
+                start_date,
+                end_date,
+                account_id,
+                group_by,
+                include_archived,
+            };
+            let rows = reports::get_spending_report(pool, &request).await?;
+            if json {
+                return print_json(&rows);
+            }
+            let group = match group_by {
+                reports::ReportGrouping::Category => "Category",
+                reports::ReportGrouping::Tag => "Tag",
+            };
+            print_table(
+                [group, "Transactions", "Total"],
+                rows.iter().map(|row| {
+                    [
+                        row.group_name.clone(),
+                        row.transaction_count.to_string(),
+                        money(row.total),
+                    ]
+                }),
+            );
+        }
+        "split" => {
+            args.finish()?;
+
+            let request = reports::SpendingSplitRequest {
+                start_date,
+                end_date,
+                account_id,
+                include_archived,
+            };
+            let rows = reports::get_spending_split_report(pool, &request).await?;
+            if json {
+                return print_json(&rows);
+            }
+            print_table(
+                ["Month", "Fixed", "Discretionary", "Unclassified", "Total"],
+                rows.iter().map(|row| {
+                    [
+                        row.month.format("%Y-%m").to_string(),
+                        money(row.fixed),
+                        money(row.discretionary),
+                        money(row.unclassified),
+                        money(row.total),
+                    ]
+                }),
+            );
+        }
+        other => {
+            return Err(format!("unknown report `{other}`, use spending or split").into());
+        }
+    }
+    Ok(())
+}
+
+async fn export(pool: &SqlitePool, mut args: Args, json: bool) -> CliResult {
+    let path = PathBuf::from(args.next("file")?);
+    let format = FileFormat::of(&path, args.option("format"))?;
+    args.finish()?;
+
+    match format {
+        FileFormat::Json | FileFormat::CsvZip => {
+            let format = if format == FileFormat::Json {
+                export::ExportFormat::Json
+            } else {
+                export::ExportFormat::CsvZip
+            };
+            let counts = export::export_to_file(pool, &path, format).await?;
+            if json {
+                return print_json(&counts);
+            }
+            println!(
+                "Exported {} accounts, {} categories, {} tags, {} transactions and {} transfers to {}",
+                counts.accounts,
+                counts.categories,
+                counts.tags,
+                counts.transactions,
+                counts.transfers,
+                path.display()
+            );
+        }
+        FileFormat::Journal(format) => {
+            let entries = ledger::export_ledger(pool, &path, format).await?;
+            if json {
+                return print_json(&serde_json::json!({ "entries": entries }));
+            }
+            println!("Exported {entries} entries to {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Kinds of files that can be imported or exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileFormat {
+    Json,
+    CsvZip,
+    Journal(ledger::LedgerFormat),
+}
+
+impl FileFormat {
+    /// Reads `--format`, or guesses from the file's extension without it.
+    fn of(path: &Path, format: Option<String>) -> Result<Self, String> {
+        let name = format.unwrap_or_else(|| {
+            path.extension()
+                .map(|extension| extension.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+        });
+
+        match name.as_str() {
+            "json" => Ok(FileFormat::Json),
+            "zip" | "csv_zip" => Ok(FileFormat::CsvZip),
+            "ledger" | "journal" | "hledger" | "dat" => {
+                Ok(FileFormat::Journal(ledger::LedgerFormat::Ledger))
+            }
+            "beancount" | "bean" => Ok(FileFormat::Journal(ledger::LedgerFormat::Beancount)),
+            _ => Err(format!(
+                "can't tell the format of {}, pass --format",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// Finds an active or archived account by ID or, ignoring case, by name.
+async fn find_account(pool: &SqlitePool, key: &str) -> Result<database::Account, Box<dyn Error>> {
+    let mut accounts = database::get_accounts(pool).await?;
+    accounts.extend(database::get_archived_accounts(pool).await?);
+
+    let by_id = accounts
+        .iter()
+        .position(|account| account.id.to_string() == key);
+    let by_name = || {
+        accounts
+            .iter()
+            .position(|account| account.name.eq_ignore_ascii_case(key))
+    };
+    match by_id.or_else(by_name) {
+        Some(index) => Ok(accounts.swap_remove(index)),
+        None => Err(format!("no account `{key}`, see `tally-cli accounts`").into()),
+    }
+}
+
+/// Finds an active category by ID, by path or by name, ignoring case.
+async fn find_category(pool: &SqlitePool, key: &str) -> Result<i64, Box<dyn Error>> {
+    let tree = database::get_category_tree(pool).await?;
+
+    if let Some(node) = tree
+        .iter()
+        .find(|node| node.category.id.to_string() == key || node.path.eq_ignore_ascii_case(key))
+    {
+        return Ok(node.category.id);
+    }
+    let named: Vec<_> = tree
+        .iter()
+        .filter(|node| node.category.name.eq_ignore_ascii_case(key))
+        .collect();
+    match named[..] {
+        [node] => Ok(node.category.id),
+        [] => Err(format!("no category `{key}`, see `tally-cli categories`").into()),
+        _ => Err(format!("several categories are called `{key}`, give its path").into()),
+    }
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .map_err(|_| format!("`{text}` isn't a date, use YYYY-MM-DD"))
+}
+
+fn money(amount: f64) -> String {
+    format!("{amount:.2}")
+}
+
+fn print_json(value: &impl Serialize) -> CliResult {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_table<const N: usize>(headers: [&str; N], rows: impl Iterator<Item = [String; N]>) {
+    print!("{}", format_table(headers, rows));
+}
+
+/// Lays rows out under a header, right-aligning columns that only hold numbers.
+fn format_table<const N: usize>(
+    headers: [&str; N],
+    rows: impl Iterator<Item = [String; N]>,
+) -> String {
+    let rows: Vec<[String; N]> = rows.collect();
+
+    let mut widths = headers.map(|header| header.chars().count());
+    let mut numeric = [true; N];
+    for row in &rows {
+        for (column, cell) in row.iter().enumerate() {
+            widths[column] = widths[column].max(cell.chars().count());
+            numeric[column] &= cell.is_empty() || cell.parse::<f64>().is_ok();
+        }
+    }
+
+    let line = |table: &mut String, cells: [&str; N]| {
+        let cells: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                let width = widths[column];
+                if numeric[column] {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    };
+
+    let mut table = String::new();
+    line(&mut table, headers);
+    for row in &rows {
+        line(&mut table, row.each_ref().map(String::as_str));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    async fn create_test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        database::create_schema(&pool).await.unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let mut parsed = args(&[
+            "add",
+            "--json",
+            "Checking",
+            "--payee",
+            "Corner Store",
+            "2026-10-01",
+            "-12.50",
+            "--memo=a=b",
+            "--cleared",
+        ])
+        .unwrap();
+
+        assert_eq!(parsed.next("command").unwrap(), "add");
+        assert_eq!(parsed.next("account").unwrap(), "Checking");
+        assert_eq!(parsed.next("date").unwrap(), "2026-10-01");
+        // A negative amount is a value, not an option
+        assert_eq!(parsed.next("amount").unwrap(), "-12.50");
+        assert_eq!(parsed.option("payee").as_deref(), Some("Corner Store"));
+        assert_eq!(parsed.option("memo").as_deref(), Some("a=b"));
+        assert!(parsed.flag("json"));
+        assert!(parsed.flag("cleared"));
+        assert!(!parsed.flag("pending"));
+        assert!(parsed.finish().is_ok());
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert_eq!(
+            args(&["transactions", "Checking", "--limit"])
+                .err()
+                .as_deref(),
+            Some("--limit needs a value")
+        );
+
+        let mut parsed = args(&["accounts", "--archived", "extra"]).unwrap();
+        assert_eq!(parsed.next("command").unwrap(), "accounts");
+        assert!(parsed.flag("archived"));
+        assert_eq!(
+            parsed.finish().err().as_deref(),
+            Some("unexpected argument `extra`")
+        );
+
+        let mut parsed = args(&["accounts", "--limt", "5"]).unwrap();
+        parsed.next("command").unwrap();
+        assert_eq!(
+            parsed.finish().err().as_deref(),
+            Some("unknown option --limt")
+        );
+        assert_eq!(
+            args(&[]).unwrap().next("command").err().as_deref(),
+            Some("missing <command>, see `tally-cli help`")
+        );
+    }
+
+    #[test]
+    fn test_file_format() {
+        let of = |path: &str, format: Option<&str>| {
+            FileFormat::of(Path::new(path), format.map(String::from))
+        };
+
+        assert_eq!(of("export.json", None), Ok(FileFormat::Json));
+        assert_eq!(of("export.ZIP", None), Ok(FileFormat::CsvZip));
+        assert_eq!(
+            of("2026.journal", None),
+            Ok(FileFormat::Journal(ledger::LedgerFormat::Ledger))
+        );
+        assert_eq!(
+            of("books.bean", None),
+            Ok(FileFormat::Journal(ledger::LedgerFormat::Beancount))
+        );
+        // --format wins over the extension
+        assert_eq!(
+            of("books.txt", Some("beancount")),
+            Ok(FileFormat::Journal(ledger::LedgerFormat::Beancount))
+        );
+        assert_eq!(
+            of("books.txt", None),
+            Err("can't tell the format of books.txt, pass --format".to_string())
+        );
+        assert!(of("export", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_account() {
+        let pool = create_test_pool().await;
+        let account = |name: &str| database::CreateAccountRequest {
+            name: name.to_string(),
+            account_type: "checking".to_string(),
+            institution: None,
+            current_balance: None,
+            display_order: None,
+            include_in_net_worth: None,
+            account_number_last4: None,
+        };
+        let checking = database::insert_account(&pool, &account("Checking"))
+            .await
+            .unwrap();
+        // An account named like another's ID doesn't shadow it
+        database::insert_account(&pool, &account(&checking.to_string()))
+            .await
+            .unwrap();
+        let old = database::insert_account(&pool, &account("Old"))
+            .await
+            .unwrap();
+        database::archive_account(&pool, old).await.unwrap();
+
+        assert_eq!(find_account(&pool, "checking").await.unwrap().id, checking);
+        assert_eq!(
+            find_account(&pool, &checking.to_string()).await.unwrap().id,
+            checking
+        );
+        assert_eq!(find_account(&pool, "old").await.unwrap().id, old);
+        assert_eq!(
+            find_account(&pool, "savings")
+                .await
+                .unwrap_err()
+                .to_string(),
+            "no account `savings`, see `tally-cli accounts`"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_category() {
+        let pool = create_test_pool().await;
+        let category = |name: &str, parent_category_id| database::CreateCategoryRequest {
+            name: name.to_string(),
+            display_order: None,
+            parent_category_id,
+            default_discretionary: None,
+            default_fixed: None,
+        };
+        let food = database::insert_category(&pool, &category("Food", None))
+            .await
+            .unwrap();
+        let travel = database::insert_category(&pool, &category("Travel", None))
+            .await
+            .unwrap();
+        let snacks = database::insert_category(&pool, &category("Snacks", Some(food)))
+            .await
+            .unwrap();
+        let travel_snacks = database::insert_category(&pool, &category("Snacks", Some(travel)))
+            .await
+            .unwrap();
+
+        assert_eq!(find_category(&pool, "food").await.unwrap(), food);
+        assert_eq!(
+            find_category(&pool, &snacks.to_string()).await.unwrap(),
+            snacks
+        );
+        // A name two categories share needs the path
+        assert_eq!(
+            find_category(&pool, "snacks")
+                .await
+                .unwrap_err()
+                .to_string(),
+            "several categories are called `snacks`, give its path"
+        );
+        assert_eq!(find_category(&pool, "Food > Snacks").await.unwrap(), snacks);
+        assert_eq!(
+            find_category(&pool, "travel > snacks").await.unwrap(),
+            travel_snacks
+        );
+        assert!(find_category(&pool, "Rent").await.is_err());
+    }
+
+    #[test]
+    fn test_format_table() {
+        let rows = [
+            [
+                "1".to_string(),
+                "Checking".to_string(),
+                "1500.00".to_string(),
+            ],
+            ["12".to_string(), "Visa".to_string(), "-42.10".to_string()],
+            [
+                "3".to_string(),
+                "Savings account".to_string(),
+                String::new(),
+            ],
+        ];
+        let table = format_table(["ID", "Name", "Balance"], rows.into_iter());
+
+        assert_eq!(
+            table,
+            "\
+ID  Name             Balance
+ 1  Checking         1500.00
+12  Visa              -42.10
+ 3  Savings account
+"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Compares offset and keyset paging over a single large account.
+//!
+//! Seeds an in-memory database with 500k transactions for one account, then
+//! times fetching a 50-row page at increasing depths with `LIMIT/OFFSET` and
+//! with the `(date, id)` cursor used by `database::get_transactions`.
+//!
+//! Run with `cargo bench -p tally-core --bench transaction_paging`.
+
+use chrono::NaiveDate;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use std::time::{Duration, Instant};
+use tally_core::database::{self, TransactionCursor};
+
+const ROWS: i64 = 500_000;
+const PAGE_SIZE: i32 = 50;
+const DEPTHS: [i64; 5] = [0, 10_000, 100_000, 250_000, 499_000];
+const ITERATIONS: u32 = 20;
+
+async fn seed_pool() -> SqlitePool {
+    // A single connection keeps every query on the same in-memory database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect(":memory:")
+        .await
+        .unwrap();
+
+    database::create_accounts_table(&pool).await.unwrap();
+    database::create_categories_table(&pool).await.unwrap();
+    database::create_transactions_table(&pool).await.unwrap();
+    database::create_tags_table(&pool).await.unwrap();
+
+    sqlx::query("INSERT INTO accounts (id, name, type) VALUES (1, 'Benchmark Checking', 'checking')")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Spread rows over ~8 years with several transactions per day
+    sqlx::query(
+        r#"
+        WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < ?)
+        INSERT INTO transactions (account_id, date, amount, description, transaction_type, source)
+        SELECT 1, date('2018-01-01', '+' || (n / 170) || ' days'), n % 10000, 'Transaction ' || n, 'expense', 'manual'
+        FROM seq
+        "#,
+    )
+    .bind(ROWS)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    pool
+}
+
+async fn offset_page(pool: &SqlitePool, offset: i64) -> usize {
+    sqlx::query(
+        r#"
+            SELECT
+                id, account_id, date, amount, description, category_id, pending, transaction_type, created_at, reconciled, import_id, source, payee, original_description, memo, spending_kind
+            FROM transactions
+            WHERE account_id = 1
+            ORDER BY date DESC, id DESC
+            LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(PAGE_SIZE)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+    .len()
+}
+
+/// Finds the cursor a client would hold after scrolling `depth` rows.
+async fn cursor_at(pool: &SqlitePool, depth: i64) -> Option<TransactionCursor> {
+    if depth == 0 {
+        return None;
+    }
+
+    let (date, id) = sqlx::query_as::<_, (NaiveDate, i64)>(
+        "SELECT date, id FROM transactions WHERE account_id = 1 ORDER BY date DESC, id DESC LIMIT 1 OFFSET ?",
+    )
+    .bind(depth - 1)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+
+    Some(TransactionCursor { date, id })
+}
+
+fn average(total: Duration) -> f64 {
+    total.as_secs_f64() * 1000.0 / f64::from(ITERATIONS)
+}
+
+#[tokio::main]
+async fn main() {
+    let started = Instant::now();
+    let pool = seed_pool().await;
+    println!("Seeded {ROWS} transactions in {:.1?}", started.elapsed());
+    println!();
+    println!("{:>10}  {:>12}  {:>12}", "depth", "offset (ms)", "keyset (ms)");
+
+    for depth in DEPTHS {
+        let cursor = cursor_at(&pool, depth).await;
+
+        let mut offset_total = Duration::ZERO;
+        let mut keyset_total = Duration::ZERO;
+
+        for _ in 0..ITERATIONS {
+            let started = Instant::now();
+            let rows = offset_page(&pool, depth).await;
+            offset_total += started.elapsed();
+            assert_eq!(rows, PAGE_SIZE as usize);
+
+            let started = Instant::now();
+            let page = database::get_transactions(&pool, 1, PAGE_SIZE, cursor.as_ref())
+                .await
+                .unwrap();
+            keyset_total += started.elapsed();
+            assert!(!page.transactions.is_empty());
+        }
+
+        println!(
+            "{:>10}  {:>12.3}  {:>12.3}",
+            depth,
+            average(offset_total),
+            average(keyset_total)
+        );
+    }
+}
@@ -0,0 +1,452 @@
+use crate::database;
+use crate::encryption;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, Pool, Sqlite};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory automatic and manual backups are written to, next to the database.
+pub const BACKUP_DIR: &str = "./backups";
+
+/// Number of most recent backups kept unless `TALLY_BACKUP_RETENTION` is set.
+pub const DEFAULT_BACKUP_RETENTION: usize = 10;
+
+/// Environment variable with the number of most recent backups to keep.
+pub const BACKUP_RETENTION_VAR: &str = "TALLY_BACKUP_RETENTION";
+
+/// Tables a file must have to be accepted as a Tally backup.
+const REQUIRED_TABLES: [&str; 3] = ["accounts", "categories", "transactions"];
+
+/// Timestamp format used in backup file names; sorts in creation order.
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%6fZ";
+
+/// Errors from creating, listing or restoring backups.
+#[derive(Debug)]
+pub enum BackupError {
+    /// The database couldn't be read or written
+    Sqlx(sqlx::Error),
+    /// The backups directory or a backup file couldn't be accessed
+    Io(io::Error),
+    /// The file isn't a usable backup, with an explanation
+    Invalid(String),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupError::Sqlx(e) => write!(f, "{e}"),
+            BackupError::Io(e) => write!(f, "{e}"),
+            BackupError::Invalid(message) => write!(f, "invalid backup: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BackupError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BackupError::Sqlx(e) => Some(e),
+            BackupError::Io(e) => Some(e),
+            BackupError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for BackupError {
+    fn from(e: sqlx::Error) -> Self {
+        BackupError::Sqlx(e)
+    }
+}
+
+impl From<io::Error> for BackupError {
+    fn from(e: io::Error) -> Self {
+        BackupError::Io(e)
+    }
+}
+
+/// Where backups are written and how many of them are kept.
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub dir: PathBuf,
+    /// Number of most recent backups to keep; older ones are deleted
+    pub retention: usize,
+}
+
+impl BackupConfig {
+    /// Backups in `dir`, keeping as many as `TALLY_BACKUP_RETENTION` says, or
+    /// `DEFAULT_BACKUP_RETENTION` when it isn't set.
+    pub fn from_env(dir: impl Into<PathBuf>) -> Result<Self, String> {
+        let retention = match std::env::var(BACKUP_RETENTION_VAR) {
+            Ok(value) => parse_retention(&value).ok_or_else(|| {
+                format!("{BACKUP_RETENTION_VAR} must be a number of backups, not `{value}`")
+            })?,
+            Err(_) => DEFAULT_BACKUP_RETENTION,
+        };
+        Ok(BackupConfig {
+            dir: dir.into(),
+            retention,
+        })
+    }
+}
+
+/// Parses a number of backups to keep. It has to be at least one, so the
+/// backup just taken is never deleted.
+pub fn parse_retention(value: &str) -> Option<usize> {
+    value.trim().parse().ok().filter(|&retention| retention > 0)
+}
+
+/// Why a backup was taken, recorded in its file name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    /// Taken when the app starts, before the schema is migrated
+    Startup,
+    /// Requested by the user
+    Manual,
+    Import,
+    BulkDelete,
+    PurgeTrash,
+    /// The database as it was before another backup was restored over it
+    Restore,
+}
+
+impl BackupReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BackupReason::Startup => "startup",
+            BackupReason::Manual => "manual",
+            BackupReason::Import => "import",
+            BackupReason::BulkDelete => "bulk_delete",
+            BackupReason::PurgeTrash => "purge_trash",
+            BackupReason::Restore => "restore",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "startup" => Some(BackupReason::Startup),
+            "manual" => Some(BackupReason::Manual),
+            "import" => Some(BackupReason::Import),
+            "bulk_delete" => Some(BackupReason::BulkDelete),
+            "purge_trash" => Some(BackupReason::PurgeTrash),
+            "restore" => Some(BackupReason::Restore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backup {
+    pub file_name: String,
+    pub reason: BackupReason,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+    /// Whether the backup needs a passphrase to be restored
+    pub encrypted: bool,
+}
+
+impl Backup {
+    /// Reads a backup's details from its file name, `tally-<timestamp>-<reason>.db`.
+    fn from_file_name(file_name: &str, size_bytes: u64) -> Option<Self> {
+        let (timestamp, reason) = file_name
+            .strip_prefix("tally-")?
+            .strip_suffix(".db")?
+            .split_once('-')?;
+        let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+
+        Some(Backup {
+            file_name: file_name.to_string(),
+            reason: BackupReason::parse(reason)?,
+            created_at: created_at.and_utc(),
+            size_bytes,
+            encrypted: false,
+        })
+    }
+}
+
+/// Writes a consistent copy of the database to the backups directory.
+///
+/// Uses `VACUUM INTO`, which copies a snapshot of the live database without
+/// blocking other connections for long and compacts the copy on the way. Once
+/// the backup is written, backups beyond `config.retention` are deleted, oldest
+/// first. A backup of an encrypted database is encrypted with the same
+/// passphrase.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `config` - Backups directory, created if missing, and retention
+/// * `reason` - Why the backup is taken, kept in its file name
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Backup)` - The backup that was written
+/// - `Err(BackupError)` - The copy or the backups directory failed
+///
+/// # Examples
+///
+/// ```rust
+/// let backup = create_backup(&pool, &config, BackupReason::Manual).await?;
+/// println!("Saved {} ({} bytes)", backup.file_name, backup.size_bytes);
+/// ```
+pub async fn create_backup(
+    pool: &Pool<Sqlite>,
+    config: &BackupConfig,
+    reason: BackupReason,
+) -> Result<Backup, BackupError> {
+    fs::create_dir_all(&config.dir)?;
+
+    let file_name = format!(
+        "tally-{}-{}.db",
+        Utc::now().format(TIMESTAMP_FORMAT),
+        reason.as_str()
+    );
+    let path = config.dir.join(&file_name);
+    let target = path
+        .to_str()
+        .ok_or_else(|| BackupError::Invalid(format!("unsupported path {}", path.display())))?;
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(target)
+        .execute(pool)
+        .await?;
+
+    let mut backup = Backup::from_file_name(&file_name, fs::metadata(&path)?.len())
+        .ok_or_else(|| BackupError::Invalid(format!("unexpected file name {file_name}")))?;
+    backup.encrypted = encryption::is_encrypted(&path)?;
+
+    prune_backups(config)?;
+
+    Ok(backup)
+}
+
+/// Backs up an existing database when the app starts.
+///
+/// Runs before the schema is migrated, so the backup also protects against a
+/// failed migration. A new, empty database is not backed up.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Some(Backup))` - The backup that was written
+/// - `Ok(None)` - The database has no tables yet
+/// - `Err(BackupError)` - The backup failed
+pub async fn backup_on_startup(
+    pool: &Pool<Sqlite>,
+    config: &BackupConfig,
+) -> Result<Option<Backup>, BackupError> {
+    let has_tables: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table')")
+            .fetch_one(pool)
+            .await?;
+
+    if !has_tables {
+        return Ok(None);
+    }
+    create_backup(pool, config, BackupReason::Startup)
+        .await
+        .map(Some)
+}
+
+/// Lists the backups in the backups directory, newest first.
+///
+/// Files that don't follow the backup naming scheme are ignored. A missing
+/// directory means no backups have been taken yet.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Backup>)` - Every backup found
+/// - `Err(BackupError)` - The directory couldn't be read
+pub fn list_backups(config: &BackupConfig) -> Result<Vec<Backup>, BackupError> {
+    let entries = match fs::read_dir(&config.dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Some(file_name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if let Some(mut backup) = Backup::from_file_name(&file_name, entry.metadata()?.len()) {
+            backup.encrypted = encryption::is_encrypted(&entry.path())?;
+            backups.push(backup);
+        }
+    }
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+
+    Ok(backups)
+}
+
+/// Deletes the oldest backups so that at most `config.retention` remain.
+fn prune_backups(config: &BackupConfig) -> Result<(), BackupError> {
+    for backup in list_backups(config)?.iter().skip(config.retention) {
+        fs::remove_file(config.dir.join(&backup.file_name))?;
+    }
+    Ok(())
+}
+
+/// Checks that a file is an intact Tally database before it is restored.
+///
+/// Runs SQLite's integrity and foreign key checks, then makes sure the core
+/// tables are present. The file is opened for writing because checking the
+/// full-text index needs it, so [`restore_backup`] validates a copy rather than
+/// the backup itself.
+///
+/// # Arguments
+///
+/// * `path` - The file to check
+/// * `passphrase` - Passphrase the file is encrypted with, if it is
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - The backup can be restored
+/// - `Err(BackupError::Invalid)` - The file is corrupt or not a Tally database
+/// - `Err(BackupError)` - The file couldn't be opened
+pub async fn validate_backup(path: &Path, passphrase: Option<&str>) -> Result<(), BackupError> {
+    if !path.is_file() {
+        return Err(BackupError::Invalid(format!(
+            "{} not found",
+            path.display()
+        )));
+    }
+
+    let mut options = SqliteConnectOptions::new().filename(path);
+    if let Some(passphrase) = passphrase {
+        options = options.pragma("key", database::quote_literal(passphrase));
+    }
+    let mut conn = options
+        .connect()
+        .await
+        .map_err(|e| BackupError::Invalid(format!("can't be opened ({e})")))?;
+
+    let result = check_backup(&mut conn).await;
+    conn.close().await?;
+    result
+}
+
+async fn check_backup(conn: &mut sqlx::SqliteConnection) -> Result<(), BackupError> {
+    let integrity: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| BackupError::Invalid(format!("not a database ({e})")))?;
+    if integrity != ["ok"] {
+        return Err(BackupError::Invalid(format!(
+            "integrity check failed: {}",
+            integrity.join("; ")
+        )));
+    }
+
+    let broken_references = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(&mut *conn)
+        .await?
+        .len();
+    if broken_references > 0 {
+        return Err(BackupError::Invalid(format!(
+            "{broken_references} rows reference records that don't exist"
+        )));
+    }
+
+    for table in REQUIRED_TABLES {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+        )
+        .bind(table)
+        .fetch_one(&mut *conn)
+        .await?;
+        if !exists {
+            return Err(BackupError::Invalid(format!("missing the {table} table")));
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the database file with a backup.
+///
+/// The backup is validated first and the current database is backed up, so a
+/// restore can itself be undone by restoring that backup. The pool is then
+/// closed and the backup copied over the database file, so the app must open a
+/// new pool, typically by restarting, before it can use the database again.
+///
+/// The backup is copied next to the database, validated there and renamed over
+/// the database, so an interrupted restore never leaves a half-written
+/// database behind.
+///
+/// # Arguments
+///
+/// * `pool` - Pool of the database being replaced; closed on success
+/// * `database` - Path of the database file
+/// * `config` - Backups directory and retention
+/// * `file_name` - Name of the backup in the backups directory, from [`list_backups`]
+/// * `passphrase` - Passphrase the backup is encrypted with, if it is; the
+///   restored database is unlocked with it from then on
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Backup)` - The backup taken of the database before it was replaced
+/// - `Err(BackupError::Invalid)` - The backup doesn't exist or failed validation;
+///   nothing was changed
+/// - `Err(BackupError)` - Backing up or replacing the database failed
+pub async fn restore_backup(
+    pool: &Pool<Sqlite>,
+    database: &Path,
+    config: &BackupConfig,
+    file_name: &str,
+    passphrase: Option<&str>,
+) -> Result<Backup, BackupError> {
+    // Only files from the backups directory can be restored
+    if Backup::from_file_name(file_name, 0).is_none() {
+        return Err(BackupError::Invalid(format!("{file_name} is not a backup")));
+    }
+    let source = config.dir.join(file_name);
+    if !source.is_file() {
+        return Err(BackupError::Invalid(format!("{file_name} not found")));
+    }
+
+    let staging = database.with_extension("db-restore");
+    fs::copy(&source, &staging)?;
+    let previous = async {
+        validate_backup(&staging, passphrase).await?;
+        create_backup(pool, config, BackupReason::Restore).await
+    }
+    .await
+    .inspect_err(|_| {
+        let _ = fs::remove_file(&staging);
+    })?;
+    replace_database(pool, database, &staging).await?;
+
+    Ok(previous)
+}
+
+/// Closes the pool and moves a complete database file over the database.
+///
+/// Any journal left by the closed pool is removed first so SQLite doesn't
+/// replay it over the new file. The rename is atomic, so the database is
+/// either the old file or the new one.
+pub async fn replace_database(
+    pool: &Pool<Sqlite>,
+    database: &Path,
+    replacement: &Path,
+) -> io::Result<()> {
+    pool.close().await;
+
+    for suffix in ["-wal", "-shm"] {
+        let mut journal = database.as_os_str().to_owned();
+        journal.push(suffix);
+        match fs::remove_file(PathBuf::from(journal)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    fs::rename(replacement, database)
+}
@@ -0,0 +1,4855 @@
+use crate::events::{self, ChangedRecord};
+use crate::validation::{self, FieldError};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{
+    Pool, Row, Sqlite, SqliteConnection,
+    error::ErrorKind,
+    sqlite::{SqliteConnectOptions, SqlitePool, SqliteRow},
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// Errors from operations that change data.
+///
+/// Constraint failures reported by SQLite are sorted into their own variants so
+/// callers can tell a bad request apart from a broken database.
+#[derive(Debug)]
+pub enum DatabaseError {
+    /// The underlying database operation failed
+    Sqlx(sqlx::Error),
+    /// A referenced record doesn't exist or is archived
+    NotFound { entity: &'static str, id: i64 },
+    /// The request is well-formed but not allowed, with an explanation
+    Invalid(String),
+    /// One or more fields of the request are invalid
+    Validation(Vec<FieldError>),
+    /// A foreign key points at a record that doesn't exist
+    ForeignKeyViolation(String),
+    /// A value must be unique but is already taken
+    UniqueViolation(String),
+    /// A CHECK or NOT NULL constraint rejected a value
+    CheckViolation(String),
+    /// A trigger guarding a domain rule rejected the change, such as adding a
+    /// transaction to an archived account
+    InvariantViolation(String),
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseError::Sqlx(e) => write!(f, "{e}"),
+            DatabaseError::NotFound { entity, id } => write!(f, "{entity} {id} not found"),
+            DatabaseError::Invalid(message) | DatabaseError::InvariantViolation(message) => {
+                write!(f, "{message}")
+            }
+            DatabaseError::Validation(errors) => {
+                let fields: Vec<String> = errors
+                    .iter()
+                    .map(|error| format!("{} {}", error.field, error.message))
+                    .collect();
+                write!(f, "invalid request: {}", fields.join("; "))
+            }
+            DatabaseError::ForeignKeyViolation(message) => {
+                write!(f, "referenced record doesn't exist ({message})")
+            }
+            DatabaseError::UniqueViolation(message) => {
+                write!(f, "value already exists ({message})")
+            }
+            DatabaseError::CheckViolation(message) => write!(f, "invalid value ({message})"),
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatabaseError::Sqlx(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Extended result code SQLite reports for `RAISE(ABORT, ...)` in a trigger.
+const SQLITE_CONSTRAINT_TRIGGER: &str = "1811";
+
+/// Start of the message SQLite reports when a CHECK constraint fails.
+const CHECK_FAILED: &str = "CHECK constraint failed";
+
+impl From<sqlx::Error> for DatabaseError {
+    fn from(e: sqlx::Error) -> Self {
+        let Some(db_error) = e.as_database_error() else {
+            return DatabaseError::Sqlx(e);
+        };
+        let message = db_error.message().to_string();
+
+        match db_error.kind() {
+            ErrorKind::ForeignKeyViolation => DatabaseError::ForeignKeyViolation(message),
+            ErrorKind::UniqueViolation => DatabaseError::UniqueViolation(message),
+            ErrorKind::CheckViolation | ErrorKind::NotNullViolation => {
+                DatabaseError::CheckViolation(message)
+            }
+            // Triggers standing in for CHECK constraints on older tables
+            // repeat SQLite's message, so they are reported the same way
+            _ if db_error.code().as_deref() == Some(SQLITE_CONSTRAINT_TRIGGER)
+                && message.starts_with(CHECK_FAILED) =>
+            {
+                DatabaseError::CheckViolation(message)
+            }
+            _ if db_error.code().as_deref() == Some(SQLITE_CONSTRAINT_TRIGGER) => {
+                DatabaseError::InvariantViolation(message)
+            }
+            _ => DatabaseError::Sqlx(e),
+        }
+    }
+}
+
+/// Whether spending is committed or could be cut back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpendingKind {
+    Fixed,
+    Discretionary,
+}
+
+impl SpendingKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SpendingKind::Fixed => "fixed",
+            SpendingKind::Discretionary => "discretionary",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fixed" => Some(SpendingKind::Fixed),
+            "discretionary" => Some(SpendingKind::Discretionary),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: i64,
+    pub account_id: i64,
+    pub date: NaiveDate,
+    pub amount: f64,
+    pub description: Option<String>,
+    pub category_id: Option<i64>,
+    pub pending: bool,
+    pub transaction_type: String,
+    pub created_at: DateTime<Utc>,
+    pub reconciled: bool,
+    pub import_id: Option<String>,
+    pub source: Option<String>,
+    pub payee: Option<String>,
+    pub original_description: Option<String>,
+    pub memo: Option<String>,
+    /// Overrides the category's fixed or discretionary default; `None` inherits it
+    pub spending_kind: Option<SpendingKind>,
+    pub tag_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub id: i64,
+    pub name: String,
+    pub account_type: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub current_balance: Option<f64>,
+    pub institution: Option<String>,
+    pub display_order: Option<i32>,
+    pub archived: bool,
+    pub include_in_net_worth: bool,
+    pub account_number_last4: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Category {
+    pub id: i64,
+    pub name: String,
+    pub archived: bool,
+    pub created_at: DateTime<Utc>,
+    pub display_order: Option<i32>,
+    pub parent_category_id: Option<i64>,
+    pub default_discretionary: Option<bool>,
+    pub default_fixed: Option<bool>,
+    /// Date of the latest transaction assigned to the category
+    pub last_used_date: Option<NaiveDate>,
+    /// How many times a transaction has been assigned to the category
+    pub usage_count: i64,
+    pub is_system_category: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTreeNode {
+    pub category: Category,
+    pub depth: u32,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySuggestion {
+    pub category: Category,
+    /// How many of the payee's transactions are in this category
+    pub payee_uses: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCategoryRequest {
+    pub name: String,
+    pub display_order: Option<i32>,
+    pub parent_category_id: Option<i64>,
+    pub default_discretionary: Option<bool>,
+    pub default_fixed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTransactionRequest {
+    pub account_id: i64,
+    #[serde(deserialize_with = "validation::deserialize_date")]
+    pub date: NaiveDate,
+    pub amount: f64,
+    pub description: Option<String>,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub category_id: Option<i64>,
+    pub pending: bool,
+    pub cleared: bool,
+    pub tag_ids: Option<Vec<i64>>,
+    /// Overrides the category's fixed or discretionary default; `None` inherits it
+    #[serde(default)]
+    pub spending_kind: Option<SpendingKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateTransactionRequest {
+    #[serde(deserialize_with = "validation::deserialize_date")]
+    pub date: NaiveDate,
+    pub amount: f64,
+    pub description: Option<String>,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub category_id: Option<i64>,
+    pub pending: bool,
+    pub cleared: bool,
+    pub tag_ids: Option<Vec<i64>>,
+    /// Overrides the category's fixed or discretionary default; `None` inherits it
+    #[serde(default)]
+    pub spending_kind: Option<SpendingKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAccountRequest {
+    pub name: String,
+    pub account_type: String,
+    pub institution: Option<String>,
+    pub current_balance: Option<f64>,
+    pub display_order: Option<i32>,
+    pub include_in_net_worth: Option<bool>,
+    pub account_number_last4: Option<String>,
+}
+
+/// Moves money between two accounts as a withdrawal, a deposit and a transfer
+/// linking them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTransferRequest {
+    pub from_account_id: i64,
+    pub to_account_id: i64,
+    #[serde(deserialize_with = "validation::deserialize_date")]
+    pub date: NaiveDate,
+    /// Amount moved, in dollars; always positive
+    pub amount: f64,
+    pub description: Option<String>,
+    pub memo: Option<String>,
+    pub cleared: bool,
+}
+
+/// A link between the two sides of a transfer between accounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transfer {
+    pub id: i64,
+    pub from_transaction_id: i64,
+    pub to_transaction_id: i64,
+    pub transfer_type: Option<String>,
+    pub auto_created: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Balance of an account worked out from its transactions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountBalance {
+    pub account_id: i64,
+    /// Sum of every transaction outside the trash
+    pub balance: f64,
+    /// Part of the balance that is still pending
+    pub pending: f64,
+    /// Sum of the reconciled transactions, to compare with a statement
+    pub reconciled: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTagRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: i64,
+    pub transaction_id: i64,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAttachmentRequest {
+    pub transaction_id: i64,
+    pub file_name: String,
+    pub mime_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// Kind of record tracked by the change log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    Account,
+    Category,
+    Transaction,
+    Tag,
+    Transfer,
+}
+
+impl EntityType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntityType::Account => "account",
+            EntityType::Category => "category",
+            EntityType::Transaction => "transaction",
+            EntityType::Tag => "tag",
+            EntityType::Transfer => "transfer",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "account" => Some(EntityType::Account),
+            "category" => Some(EntityType::Category),
+            "transaction" => Some(EntityType::Transaction),
+            "tag" => Some(EntityType::Tag),
+            "transfer" => Some(EntityType::Transfer),
+            _ => None,
+        }
+    }
+
+    fn table(self) -> &'static str {
+        match self {
+            EntityType::Account => "accounts",
+            EntityType::Category => "categories",
+            EntityType::Transaction => "transactions",
+            EntityType::Tag => "tags",
+            EntityType::Transfer => "transfers",
+        }
+    }
+
+    /// Columns maintained by triggers rather than by edits. They are left out
+    /// of snapshots so that undoing an edit doesn't roll them back.
+    fn derived_columns(self) -> &'static [&'static str] {
+        match self {
+            EntityType::Category => &["last_used_date", "usage_count"],
+            EntityType::Account
+            | EntityType::Transaction
+            | EntityType::Tag
+            | EntityType::Transfer => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Archive,
+    Delete,
+    Restore,
+    Purge,
+    Undo,
+    Redo,
+}
+
+impl ChangeAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeAction::Create => "create",
+            ChangeAction::Update => "update",
+            ChangeAction::Archive => "archive",
+            ChangeAction::Delete => "delete",
+            ChangeAction::Restore => "restore",
+            ChangeAction::Purge => "purge",
+            ChangeAction::Undo => "undo",
+            ChangeAction::Redo => "redo",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "create" => Some(ChangeAction::Create),
+            "update" => Some(ChangeAction::Update),
+            "archive" => Some(ChangeAction::Archive),
+            "delete" => Some(ChangeAction::Delete),
+            "restore" => Some(ChangeAction::Restore),
+            "purge" => Some(ChangeAction::Purge),
+            "undo" => Some(ChangeAction::Undo),
+            "redo" => Some(ChangeAction::Redo),
+            _ => None,
+        }
+    }
+}
+
+/// Who or what made a change: the user directly, a file import, an
+/// auto-categorization rule or a scheduled job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSource {
+    Manual,
+    Import,
+    Rule,
+    Schedule,
+}
+
+impl ChangeSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeSource::Manual => "manual",
+            ChangeSource::Import => "import",
+            ChangeSource::Rule => "rule",
+            ChangeSource::Schedule => "schedule",
+        }
+    }
+}
+
+/// A command's worth of changes that is undone and redone as a unit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub id: i64,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoStack {
+    /// Operations that can be undone, most recent first
+    pub undo: Vec<Operation>,
+    /// Undone operations that can be redone, most recently undone first
+    pub redo: Vec<Operation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeLogEntry {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub action: String,
+    pub source: String,
+    pub changed_at: DateTime<Utc>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionSearchRequest {
+    pub query: String,
+    pub account_id: Option<i64>,
+    pub tag_ids: Option<Vec<i64>>,
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionSearchResult {
+    pub transaction: Transaction,
+    pub rank: f64,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionPage {
+    pub transactions: Vec<Transaction>,
+    pub next_cursor: Option<String>,
+}
+
+/// The transactions a bulk command applies to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkTarget {
+    /// An explicit list of transaction IDs
+    Ids(Vec<i64>),
+    /// Every transaction matching a search, ignoring its result limit unless set
+    Filter(TransactionSearchRequest),
+}
+
+/// One change applied to every transaction of a bulk command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkChange {
+    SetCategory { category_id: Option<i64> },
+    SetPayee { payee: Option<String> },
+    SetCleared { cleared: bool },
+    AddTag { tag_id: i64 },
+    RemoveTag { tag_id: i64 },
+    MoveToAccount { account_id: i64 },
+    Delete,
+}
+
+impl BulkChange {
+    fn label(&self) -> &'static str {
+        match self {
+            BulkChange::SetCategory { .. } => "Recategorize transactions",
+            BulkChange::SetPayee { .. } => "Set payee",
+            BulkChange::SetCleared { .. } => "Set cleared",
+            BulkChange::AddTag { .. } => "Tag transactions",
+            BulkChange::RemoveTag { .. } => "Untag transactions",
+            BulkChange::MoveToAccount { .. } => "Move transactions",
+            BulkChange::Delete => "Delete transactions",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedTransaction {
+    pub transaction: Transaction,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Position of the last transaction on a page, in `ORDER BY date DESC, id DESC` order.
+///
+/// Handed to the frontend as an opaque hex string so callers can't depend on
+/// its contents; use [`TransactionCursor::encode`] and [`TransactionCursor::decode`]
+/// to convert between the two.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionCursor {
+    pub date: NaiveDate,
+    pub id: i64,
+}
+
+impl TransactionCursor {
+    pub fn encode(&self) -> String {
+        format!("{}|{}", self.date, self.id)
+            .bytes()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    /// Returns `None` if the string was not produced by [`TransactionCursor::encode`].
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(cursor.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        let decoded = String::from_utf8(bytes).ok()?;
+        let (date, id) = decoded.rsplit_once('|')?;
+
+        Some(TransactionCursor {
+            date: date.parse().ok()?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// Location of the database file, relative to the working directory.
+pub const DATABASE_PATH: &str = "./tally.db";
+
+/// Opens a connection pool to the database file, creating it if missing.
+///
+/// An encrypted database is opened with `passphrase`, which SQLCipher uses as
+/// the key for every connection in the pool. A wrong or missing passphrase
+/// fails with SQLite's "file is not a database" error, code 26, rather than
+/// returning a pool that can't read anything.
+pub async fn create_connection(
+    database: &Path,
+    passphrase: Option<&str>,
+) -> Result<Pool<Sqlite>, sqlx::Error> {
+    // SQLite only enforces REFERENCES clauses with foreign keys switched on,
+    // per connection, so set it explicitly for every connection in the pool
+    let mut options = SqliteConnectOptions::new()
+        .filename(database)
+        .create_if_missing(true)
+        .foreign_keys(true);
+    if let Some(passphrase) = passphrase {
+        options = options.pragma("key", quote_literal(passphrase));
+    }
+
+    let pool = SqlitePool::connect_with(options).await?;
+
+    // The key is only checked once a page is read, so read one now
+    sqlx::query("SELECT count(*) FROM sqlite_master")
+        .execute(&pool)
+        .await?;
+
+    Ok(pool)
+}
+
+/// Quotes a value as an SQL string literal, for pragmas that can't be bound.
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Creates the accounts table if it doesn't already exist.
+///
+/// Initializes the accounts table schema with all necessary columns including
+/// account metadata, balance tracking, and soft delete support.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Table created or already exists
+/// - `Err(sqlx::Error)` - Database schema creation error
+///
+/// # Database Schema
+///
+/// Creates table with columns: id, name, type, created_at, updated_at,
+/// current_balance (in cents), institution, display_order, archived,
+/// include_in_net_worth, account_number_last4
+pub async fn create_accounts_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS accounts (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL CHECK (length(trim(name)) > 0),
+            type TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            current_balance INTEGER,
+            institution TEXT,
+            display_order INTEGER,
+            archived BOOLEAN DEFAULT FALSE,
+            include_in_net_worth BOOLEAN DEFAULT TRUE,
+            account_number_last4 TEXT
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    add_checks_if_missing(pool, "accounts", &[NAME_NOT_BLANK]).await?;
+
+    Ok(())
+}
+
+/// Names of accounts, categories and tags can't be empty or only spaces.
+const NAME_NOT_BLANK: Check = Check {
+    name: "name",
+    constraint: "length(trim(name)) > 0",
+    columns: "name",
+    condition: "length(trim(NEW.name)) > 0",
+};
+
+pub async fn create_transactions_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS transactions (
+            id INTEGER PRIMARY KEY,
+            account_id INTEGER NOT NULL REFERENCES accounts(id),
+            date DATE NOT NULL CHECK (date IS date(date)),
+            amount INTEGER NOT NULL CHECK (typeof(amount) = 'integer'),
+            description TEXT,
+            category_id INTEGER REFERENCES categories(id),
+            pending BOOLEAN DEFAULT FALSE,
+            transaction_type TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            cleared BOOLEAN DEFAULT FALSE,
+            reconciled BOOLEAN DEFAULT FALSE,
+            import_id TEXT,
+            source TEXT,
+            payee TEXT,
+            original_description TEXT,
+            memo TEXT,
+            deleted_at DATETIME,
+            spending_kind TEXT CHECK (spending_kind IN ('fixed', 'discretionary'))
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    add_column_if_missing(pool, "transactions", "deleted_at", "DATETIME").await?;
+    add_column_if_missing(
+        pool,
+        "transactions",
+        "spending_kind",
+        "TEXT CHECK (spending_kind IN ('fixed', 'discretionary'))",
+    )
+    .await?;
+    let older_table = add_checks_if_missing(
+        pool,
+        "transactions",
+        &[
+            Check {
+                name: "date",
+                constraint: "date IS date(date)",
+                columns: "date",
+                condition: "NEW.date IS date(NEW.date)",
+            },
+            Check {
+                name: "amount",
+                constraint: "typeof(amount) = 'integer'",
+                columns: "amount",
+                condition: "typeof(NEW.amount) = 'integer'",
+            },
+        ],
+    )
+    .await?;
+
+    // Dates were stored as free text before they were typed. Rewrite the ones
+    // SQLite can read, such as a date with a time, as YYYY-MM-DD; any others
+    // fail to load with an error instead of a date.
+    if older_table {
+        sqlx::query(
+            "UPDATE transactions SET date = date(date) WHERE date IS NOT date(date) AND date(date) IS NOT NULL",
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    // Serves the keyset-paginated account listing in both directions
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_transactions_account_date ON transactions (account_id, date, id)",
+    )
+    .execute(pool)
+    .await?;
+
+    // Serves the last used date of a category when its usage changes
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_transactions_category_date ON transactions (category_id, date)",
+    )
+    .execute(pool)
+    .await?;
+
+    // Earlier versions only ever added to a category's usage, so replace their
+    // triggers and recount what they left behind
+    let counted_only_up: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'trigger' AND name = 'transactions_category_usage_update')",
+    )
+    .fetch_one(pool)
+    .await?;
+    if counted_only_up {
+        sqlx::query("DROP TRIGGER IF EXISTS transactions_category_usage_insert")
+            .execute(pool)
+            .await?;
+        sqlx::query("DROP TRIGGER IF EXISTS transactions_category_usage_update")
+            .execute(pool)
+            .await?;
+        sqlx::query(
+            r#"
+            UPDATE categories SET
+                usage_count = (SELECT COUNT(*) FROM transactions WHERE category_id = categories.id AND deleted_at IS NULL),
+                last_used_date = (SELECT MAX(date) FROM transactions WHERE category_id = categories.id AND deleted_at IS NULL)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    // Transactions can't be put on an archived account or an archived
+    // category. Updates only check a column that actually changes, so editing
+    // an existing transaction on an account archived later still works.
+    let triggers = [
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_account_active_insert BEFORE INSERT ON transactions
+        WHEN (SELECT archived FROM accounts WHERE id = NEW.account_id)
+        BEGIN
+            SELECT RAISE(ABORT, 'transactions can''t be added to an archived account');
+        END
+        "#,
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_account_active_update BEFORE UPDATE OF account_id ON transactions
+        WHEN NEW.account_id IS NOT OLD.account_id
+            AND (SELECT archived FROM accounts WHERE id = NEW.account_id)
+        BEGIN
+            SELECT RAISE(ABORT, 'transactions can''t be moved to an archived account');
+        END
+        "#,
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_category_active_insert BEFORE INSERT ON transactions
+        WHEN (SELECT archived FROM categories WHERE id = NEW.category_id)
+        BEGIN
+            SELECT RAISE(ABORT, 'transactions can''t be assigned to an archived category');
+        END
+        "#,
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_category_active_update BEFORE UPDATE OF category_id ON transactions
+        WHEN NEW.category_id IS NOT OLD.category_id
+            AND (SELECT archived FROM categories WHERE id = NEW.category_id)
+        BEGIN
+            SELECT RAISE(ABORT, 'transactions can''t be assigned to an archived category');
+        END
+        "#,
+        // Track category usage for suggestions from the transactions that are
+        // not in the trash, however they change: entry, edits, bulk changes,
+        // merges, trashing, purging or undo. An update takes the row off its
+        // old category and puts it on the new one, recomputing the last date
+        // of both.
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_category_use_insert AFTER INSERT ON transactions
+        WHEN NEW.category_id IS NOT NULL AND NEW.deleted_at IS NULL
+        BEGIN
+            UPDATE categories
+            SET usage_count = usage_count + 1,
+                last_used_date = max(coalesce(last_used_date, NEW.date), NEW.date)
+            WHERE id = NEW.category_id;
+        END
+        "#,
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_category_use_update AFTER UPDATE OF category_id, deleted_at, date ON transactions
+        WHEN NEW.category_id IS NOT OLD.category_id
+            OR NEW.deleted_at IS NOT OLD.deleted_at
+            OR NEW.date IS NOT OLD.date
+        BEGIN
+            UPDATE categories
+            SET usage_count = usage_count - 1,
+                last_used_date = (SELECT MAX(date) FROM transactions WHERE category_id = categories.id AND deleted_at IS NULL)
+            WHERE id = OLD.category_id AND OLD.deleted_at IS NULL;
+            UPDATE categories
+            SET usage_count = usage_count + 1,
+                last_used_date = (SELECT MAX(date) FROM transactions WHERE category_id = categories.id AND deleted_at IS NULL)
+            WHERE id = NEW.category_id AND NEW.deleted_at IS NULL;
+        END
+        "#,
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_category_use_delete AFTER DELETE ON transactions
+        WHEN OLD.category_id IS NOT NULL AND OLD.deleted_at IS NULL
+        BEGIN
+            UPDATE categories
+            SET usage_count = usage_count - 1,
+                last_used_date = (SELECT MAX(date) FROM transactions WHERE category_id = categories.id AND deleted_at IS NULL)
+            WHERE id = OLD.category_id;
+        END
+        "#,
+    ];
+    for trigger in triggers {
+        sqlx::query(trigger).execute(pool).await?;
+    }
+
+    Ok(())
+}
+
+/// Adds a column to an existing table unless it is already there.
+///
+/// `CREATE TABLE IF NOT EXISTS` leaves tables from older versions of the app
+/// untouched, so columns introduced later are added here when the database is
+/// opened. Returns whether the column was added.
+async fn add_column_if_missing(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<bool, sqlx::Error> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pragma_table_info(?) WHERE name = ?)")
+            .bind(table)
+            .bind(column)
+            .fetch_one(pool)
+            .await?;
+
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(!exists)
+}
+
+/// A CHECK constraint that tables created by older versions of the app lack.
+struct Check {
+    /// Used in the names of the triggers that stand in for it
+    name: &'static str,
+    /// The constraint as written in `CREATE TABLE`
+    constraint: &'static str,
+    /// Comma-separated columns the constraint reads
+    columns: &'static str,
+    /// The constraint written against the `NEW` row
+    condition: &'static str,
+}
+
+/// Enforces CHECK constraints on a table created before they were added.
+///
+/// SQLite can't add a constraint to an existing table, so when the table's
+/// definition lacks one, insert and update triggers enforce it instead. They
+/// fail with SQLite's own CHECK message, so the error is a
+/// [`DatabaseError::CheckViolation`] either way. Rows written before the
+/// triggers existed are left as they are. Returns whether any triggers were
+/// added, which happens once for each older table.
+async fn add_checks_if_missing(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    checks: &[Check],
+) -> Result<bool, sqlx::Error> {
+    let definition: String =
+        sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(table)
+            .fetch_one(pool)
+            .await?;
+
+    let mut added = false;
+    for check in checks {
+        let trigger = format!("{table}_check_{}_insert", check.name);
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'trigger' AND name = ?)",
+        )
+        .bind(&trigger)
+        .fetch_one(pool)
+        .await?;
+        if exists || definition.contains(check.constraint) {
+            continue;
+        }
+        added = true;
+
+        let message = quote_literal(&format!("{CHECK_FAILED}: {}", check.constraint));
+        for (event, when) in [
+            ("INSERT", "INSERT".to_string()),
+            ("UPDATE", format!("UPDATE OF {}", check.columns)),
+        ] {
+            sqlx::query(&format!(
+                r#"
+                CREATE TRIGGER IF NOT EXISTS {table}_check_{name}_{event} BEFORE {when} ON {table}
+                WHEN NOT ({condition})
+                BEGIN
+                    SELECT RAISE(ABORT, {message});
+                END
+                "#,
+                name = check.name,
+                event = event.to_lowercase(),
+                condition = check.condition,
+            ))
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    Ok(added)
+}
+
+pub async fn create_categories_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS categories (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            archived BOOLEAN DEFAULT FALSE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            display_order INTEGER,
+            parent_category_id INTEGER REFERENCES categories(id),
+            default_discretionary BOOLEAN,
+            default_fixed BOOLEAN,
+            last_used_date DATE,
+            usage_count INTEGER NOT NULL DEFAULT 0,
+            is_system_category BOOLEAN DEFAULT FALSE,
+            CHECK (length(trim(name)) > 0),
+            CHECK (parent_category_id IS NULL OR parent_category_id != id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    add_checks_if_missing(
+        pool,
+        "categories",
+        &[
+            NAME_NOT_BLANK,
+            Check {
+                name: "parent",
+                constraint: "parent_category_id IS NULL OR parent_category_id != id",
+                columns: "id, parent_category_id",
+                condition: "NEW.parent_category_id IS NULL OR NEW.parent_category_id != NEW.id",
+            },
+        ],
+    )
+    .await?;
+
+    // Older databases never wrote usage, so count it from their transactions
+    if add_column_if_missing(
+        pool,
+        "categories",
+        "usage_count",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+    .await?
+    {
+        sqlx::query(
+            r#"
+            UPDATE categories SET
+                usage_count = (SELECT COUNT(*) FROM transactions WHERE category_id = categories.id AND deleted_at IS NULL),
+                last_used_date = (SELECT MAX(date) FROM transactions WHERE category_id = categories.id AND deleted_at IS NULL)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Seeds the default "Uncategorized" system category if it doesn't exist.
+///
+/// Creates the system category that serves as a fallback for transactions
+/// without explicit categorization. This category cannot be deleted or archived
+/// by users, ensuring data integrity.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - System category seeded or already exists
+/// - `Err(sqlx::Error)` - Database operation error
+pub async fn seed_default_categories(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    // Check if Uncategorized category already exists
+    let existing = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM categories WHERE name = 'Uncategorized' AND is_system_category = TRUE"
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if existing == 0 {
+        sqlx::query(
+            r#"INSERT INTO categories (name, is_system_category, display_order, archived) 
+               VALUES ('Uncategorized', TRUE, 0, FALSE)"#,
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Adds the "Uncategorized" system category unless it already exists, and
+/// returns its ID when it was added.
+pub(crate) async fn insert_system_category(
+    conn: &mut SqliteConnection,
+) -> Result<Option<i64>, sqlx::Error> {
+    // Check if Uncategorized category already exists
+    let existing = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM categories WHERE name = 'Uncategorized' AND is_system_category = TRUE"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    if existing > 0 {
+        return Ok(None);
+    }
+
+    let result = sqlx::query(
+        r#"INSERT INTO categories (name, is_system_category, display_order, archived) 
+           VALUES ('Uncategorized', TRUE, 0, FALSE)"#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(Some(result.last_insert_rowid()))
+}
+
+pub async fn create_transfers_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS transfers (
+            id INTEGER PRIMARY KEY,
+            from_transaction_id INTEGER NOT NULL REFERENCES transactions(id),
+            to_transaction_id INTEGER NOT NULL REFERENCES transactions(id),
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            transfer_type TEXT,
+            auto_created BOOLEAN DEFAULT FALSE,
+            CHECK (from_transaction_id != to_transaction_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    add_checks_if_missing(
+        pool,
+        "transfers",
+        &[Check {
+            name: "sides",
+            constraint: "from_transaction_id != to_transaction_id",
+            columns: "from_transaction_id, to_transaction_id",
+            condition: "NEW.from_transaction_id != NEW.to_transaction_id",
+        }],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Creates the full-text search index over transaction text fields.
+///
+/// Builds an FTS5 external-content table backed by `transactions` and the
+/// triggers that keep it in sync on insert, update and delete. When the index
+/// is created for the first time it is rebuilt from any existing transactions,
+/// so upgrading an existing database makes its full history searchable.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Index and triggers created or already exist
+/// - `Err(sqlx::Error)` - Database schema creation error
+///
+/// # Database Schema
+///
+/// Creates virtual table `transactions_fts` indexing description, memo, payee
+/// and original_description, keyed by the transaction `id` as its rowid.
+pub async fn create_transactions_fts_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    let exists = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'transactions_fts'",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS transactions_fts USING fts5(
+            description,
+            memo,
+            payee,
+            original_description,
+            content='transactions',
+            content_rowid='id',
+            tokenize='unicode61 remove_diacritics 2'
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_fts_insert AFTER INSERT ON transactions BEGIN
+            INSERT INTO transactions_fts (rowid, description, memo, payee, original_description)
+            VALUES (new.id, new.description, new.memo, new.payee, new.original_description);
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_fts_delete AFTER DELETE ON transactions BEGIN
+            INSERT INTO transactions_fts (transactions_fts, rowid, description, memo, payee, original_description)
+            VALUES ('delete', old.id, old.description, old.memo, old.payee, old.original_description);
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS transactions_fts_update
+        AFTER UPDATE OF description, memo, payee, original_description ON transactions BEGIN
+            INSERT INTO transactions_fts (transactions_fts, rowid, description, memo, payee, original_description)
+            VALUES ('delete', old.id, old.description, old.memo, old.payee, old.original_description);
+            INSERT INTO transactions_fts (rowid, description, memo, payee, original_description)
+            VALUES (new.id, new.description, new.memo, new.payee, new.original_description);
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Index rows that were written before the search table existed
+    if exists == 0 {
+        sqlx::query("INSERT INTO transactions_fts (transactions_fts) VALUES ('rebuild')")
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Creates the tags and transaction_tags tables if they don't already exist.
+///
+/// Tags are free-form labels such as "vacation-2026" or "tax-deductible" that cut
+/// across the category hierarchy. A transaction can carry any number of tags and
+/// a tag can be applied to any number of transactions through the join table.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Tables created or already exist
+/// - `Err(sqlx::Error)` - Database schema creation error
+///
+/// # Database Schema
+///
+/// - `tags` - id, name (unique, case-insensitive), created_at
+/// - `transaction_tags` - (transaction_id, tag_id) pairs; rows are removed
+///   automatically when either the transaction or the tag is deleted
+pub async fn create_tags_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE CHECK (length(trim(name)) > 0),
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    add_checks_if_missing(pool, "tags", &[NAME_NOT_BLANK]).await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS transaction_tags (
+            transaction_id INTEGER NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (transaction_id, tag_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_transaction_tags_tag ON transaction_tags (tag_id)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Creates the attachments table if it doesn't already exist.
+///
+/// Receipts and other documents are stored as BLOBs inside `tally.db` rather than
+/// as loose files, so they are backed up, moved and deleted together with the
+/// transactions they belong to and can never drift out of sync with the database.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Table created or already exists
+/// - `Err(sqlx::Error)` - Database schema creation error
+///
+/// # Database Schema
+///
+/// Creates table with columns: id, transaction_id, file_name, mime_type,
+/// size_bytes, sha256 (hex digest of the content), data, created_at
+pub async fn create_attachments_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS attachments (
+            id INTEGER PRIMARY KEY,
+            transaction_id INTEGER NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+            file_name TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL CHECK (size_bytes >= 0),
+            sha256 TEXT NOT NULL,
+            data BLOB NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    add_checks_if_missing(
+        pool,
+        "attachments",
+        &[Check {
+            name: "size",
+            constraint: "size_bytes >= 0",
+            columns: "size_bytes",
+            condition: "NEW.size_bytes >= 0",
+        }],
+    )
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_attachments_transaction ON attachments (transaction_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The undo history's operations. IDs are `AUTOINCREMENT` so an operation
+/// never takes the ID of a discarded one, whose changes are still in the log.
+const OPERATIONS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS operations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        label TEXT NOT NULL,
+        created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+        undone BOOLEAN NOT NULL DEFAULT FALSE
+    )
+"#;
+
+/// Creates the append-only change log if it doesn't already exist.
+///
+/// Every create, update, archive and delete of accounts, categories, transactions
+/// and tags records one row holding JSON snapshots of the record before and after
+/// the change, when it happened and where the change came from. Triggers reject
+/// any UPDATE or DELETE against the log so history can't be rewritten.
+///
+/// Also creates the `operations` table behind undo and redo. Each data-changing
+/// command opens one operation and its log entries point back to it, so the
+/// undo stack lives in the database and survives a restart.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Table and triggers created or already exist
+/// - `Err(sqlx::Error)` - Database schema creation error
+///
+/// # Database Schema
+///
+/// Creates table with columns: id, entity_type, entity_id, action, source,
+/// changed_at, before_json, after_json, operation_id
+pub async fn create_change_log_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS change_log (
+            id INTEGER PRIMARY KEY,
+            entity_type TEXT NOT NULL,
+            entity_id INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            source TEXT NOT NULL,
+            changed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            before_json TEXT,
+            after_json TEXT,
+            operation_id INTEGER
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(OPERATIONS_TABLE).execute(pool).await?;
+
+    // Operations used to take the IDs of discarded ones, and the change log
+    // still held the discarded changes under them. The table is rebuilt so
+    // IDs are never reused, starting above every ID the change log has seen.
+    let definition: String = sqlx::query_scalar(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'operations'",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !definition.contains("AUTOINCREMENT") {
+        let mut tx = pool.begin().await?;
+        sqlx::query("ALTER TABLE operations RENAME TO operations_old")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(OPERATIONS_TABLE).execute(&mut *tx).await?;
+        sqlx::query(
+            "INSERT INTO operations (id, label, created_at, undone) SELECT id, label, created_at, undone FROM operations_old",
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DROP TABLE operations_old")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM sqlite_sequence WHERE name = 'operations'")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"INSERT INTO sqlite_sequence (name, seq) VALUES ('operations', MAX(
+                (SELECT COALESCE(MAX(id), 0) FROM operations),
+                (SELECT COALESCE(MAX(operation_id), 0) FROM change_log)
+            ))"#,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_change_log_operation ON change_log (operation_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_change_log_entity ON change_log (entity_type, entity_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS change_log_no_update BEFORE UPDATE ON change_log BEGIN
+            SELECT RAISE(ABORT, 'change_log is append-only');
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS change_log_no_delete BEFORE DELETE ON change_log BEGIN
+            SELECT RAISE(ABORT, 'change_log is append-only');
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Creates every table the app needs and seeds the system categories.
+///
+/// Each step only adds what is missing, so this is safe to run on every start
+/// and brings a database written by an older version up to date. The desktop
+/// app and the command-line tool both open the database through it.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Schema is up to date
+/// - `Err(sqlx::Error)` - Database schema creation error
+///
+/// # Examples
+///
+/// ```rust
+/// let pool = create_connection(Path::new(DATABASE_PATH), None).await?;
+/// create_schema(&pool).await?;
+/// ```
+pub async fn create_schema(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    create_accounts_table(pool).await?;
+    create_transactions_table(pool).await?;
+    create_transactions_fts_table(pool).await?;
+    create_categories_table(pool).await?;
+    create_transfers_table(pool).await?;
+    create_tags_table(pool).await?;
+    create_attachments_table(pool).await?;
+    create_change_log_table(pool).await?;
+
+    seed_default_categories(pool).await
+}
+
+/// Retrieves a specific account by its ID.
+///
+/// Fetches complete account information for a single non-archived account,
+/// returning the same full Account struct as get_accounts() for consistency.
+/// Currency amounts are automatically converted from cents to dollars.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `id` - The unique ID of the account to retrieve
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Some(Account))` - Complete account information
+/// - `Ok(None)` - No account found with given ID (or account is archived)
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// let account = get_account(&pool, 123).await?;
+/// if let Some(account) = account {
+///     println!("Found account: {} ({})", account.name, account.account_type);
+///     println!("Balance: ${:.2}", account.current_balance.unwrap_or(0.0));
+/// }
+/// ```
+pub async fn get_account(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Account>, sqlx::Error> {
+    let account = sqlx::query_as::<_, (i64, String, String, DateTime<Utc>, DateTime<Utc>, Option<i64>, Option<String>, Option<i32>, bool, bool, Option<String>)>(
+        "SELECT id, name, type, created_at, updated_at, current_balance, institution, display_order, archived, include_in_net_worth, account_number_last4 FROM accounts WHERE id = ? AND archived = FALSE",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    let result = account.map(
+        |(
+            id,
+            name,
+            account_type,
+            created_at,
+            updated_at,
+            balance_cents,
+            institution,
+            display_order,
+            archived,
+            include_in_net_worth,
+            account_number_last4,
+        )| {
+            Account {
+                id,
+                name,
+                account_type,
+                created_at,
+                updated_at,
+                current_balance: cents_to_dollars_option(balance_cents),
+                institution,
+                display_order,
+                archived,
+                include_in_net_worth,
+                account_number_last4,
+            }
+        },
+    );
+
+    Ok(result)
+}
+
+/// Retrieves all non-archived accounts from the database.
+///
+/// Returns a vector of Account structs with all fields populated, including
+/// currency conversion from integer cents to floating-point dollars for the balance.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Account>)` - Vector of Account structs with all database fields
+/// - `Err(sqlx::Error)` - Database query or connection error
+///
+/// # Ordering
+///
+/// Results are ordered by `display_order` first, then by `name` alphabetically.
+///
+/// # Examples
+///
+/// ```rust
+/// let accounts = get_accounts(&pool).await?;
+/// for account in accounts {
+///     println!("{}: ${:.2}", account.name, account.current_balance.unwrap_or(0.0));
+/// }
+/// ```
+pub async fn get_accounts(pool: &Pool<Sqlite>) -> Result<Vec<Account>, sqlx::Error> {
+    accounts_by_archived(pool, false).await
+}
+
+/// Retrieves all archived accounts, in the same order as [`get_accounts`].
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Account>)` - Archived accounts that can be restored with [`restore_account`]
+/// - `Err(sqlx::Error)` - Database query or connection error
+pub async fn get_archived_accounts(pool: &Pool<Sqlite>) -> Result<Vec<Account>, sqlx::Error> {
+    accounts_by_archived(pool, true).await
+}
+
+async fn accounts_by_archived(
+    pool: &Pool<Sqlite>,
+    archived: bool,
+) -> Result<Vec<Account>, sqlx::Error> {
+    let accounts = sqlx::query_as::<_, (i64, String, String, DateTime<Utc>, DateTime<Utc>, Option<i64>, Option<String>, Option<i32>, bool, bool, Option<String>)>(
+       "SELECT id, name, type, created_at, updated_at, current_balance, institution, display_order, archived, include_in_net_worth, account_number_last4 FROM accounts WHERE archived = ? ORDER BY display_order, name",
+   ).bind(archived).fetch_all(pool).await?;
+
+    let result = accounts
+        .into_iter()
+        .map(
+            |(
+                id,
+                name,
+                account_type,
+                created_at,
+                updated_at,
+                balance_cents,
+                institution,
+                display_order,
+                archived,
+                include_in_net_worth,
+                account_number_last4,
+            )| {
+                Account {
+                    id,
+                    name,
+                    account_type,
+                    created_at,
+                    updated_at,
+                    current_balance: cents_to_dollars_option(balance_cents),
+                    institution,
+                    display_order,
+                    archived,
+                    include_in_net_worth,
+                    account_number_last4,
+                }
+            },
+        )
+        .collect();
+
+    Ok(result)
+}
+
+/// Inserts a new account into the database with all user-provided fields.
+///
+/// Creates a new account record with automatic timestamp generation and default values
+/// for system-managed fields. Currency amounts are automatically converted from dollars
+/// to integer cents for precise storage.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `request` - Account creation request containing all user-settable fields
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The auto-generated ID of the newly inserted account
+/// - `Err(DatabaseError)` - Database operation error (constraint violations, connection issues, etc.)
+///
+/// # Database Behavior
+///
+/// - `created_at` and `updated_at` are set to CURRENT_TIMESTAMP automatically
+/// - `archived` defaults to FALSE for new accounts
+/// - `include_in_net_worth` defaults to TRUE if not specified in request
+/// - `current_balance` is converted from dollars to cents before storage
+/// - Returns the `last_insert_rowid()` as the new account ID
+///
+/// # Examples
+///
+/// ```rust
+/// let request = CreateAccountRequest {
+///     name: "Checking Account".to_string(),
+///     account_type: "checking".to_string(),
+///     institution: Some("Bank of America".to_string()),
+///     current_balance: Some(1000.50),
+///     display_order: Some(1),
+///     include_in_net_worth: Some(true),
+///     account_number_last4: Some("1234".to_string()),
+/// };
+/// let account_id = insert_account(&pool, &request).await?;
+/// ```
+pub async fn insert_account(
+    pool: &Pool<Sqlite>,
+    request: &CreateAccountRequest,
+) -> Result<i64, DatabaseError> {
+    validation::validate_account(request).map_err(DatabaseError::Validation)?;
+
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Add account", ChangeSource::Manual);
+
+    let result = sqlx::query(
+        r#"INSERT INTO accounts (name, type, institution, current_balance, display_order, include_in_net_worth, account_number_last4) 
+           VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(&request.name)
+    .bind(&request.account_type)
+    .bind(&request.institution)
+    .bind(dollars_to_cents_option(request.current_balance))
+    .bind(request.display_order)
+    .bind(request.include_in_net_worth.unwrap_or(true))
+    .bind(&request.account_number_last4)
+    .execute(&mut *tx)
+    .await?;
+
+    let account_id = result.last_insert_rowid();
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Account,
+        account_id,
+        ChangeAction::Create,
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(account_id)
+}
+
+/// Inserts a new category into the database.
+///
+/// Creates a new category record with automatic timestamp generation and default values
+/// for system-managed fields. User categories are created with is_system_category = FALSE.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `request` - Category creation request containing all user-settable fields
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The auto-generated ID of the newly inserted category
+/// - `Err(DatabaseError::NotFound)` - The parent category doesn't exist or is archived
+/// - `Err(DatabaseError::Sqlx)` - Database operation error (constraint violations, connection issues, etc.)
+///
+/// # Database Behavior
+///
+/// - `created_at` is set to CURRENT_TIMESTAMP automatically
+/// - `archived` defaults to FALSE for new categories
+/// - `is_system_category` defaults to FALSE for user-created categories
+/// - Returns the `last_insert_rowid()` as the new category ID
+///
+/// # Examples
+///
+/// ```rust
+/// let request = CreateCategoryRequest {
+///     name: "Groceries".to_string(),
+///     display_order: Some(10),
+///     parent_category_id: Some(1), // Food category
+///     default_discretionary: Some(true),
+///     default_fixed: Some(false),
+/// };
+/// let category_id = insert_category(&pool, &request).await?;
+/// ```
+pub async fn insert_category(
+    pool: &Pool<Sqlite>,
+    request: &CreateCategoryRequest,
+) -> Result<i64, DatabaseError> {
+    validation::validate_category(request).map_err(DatabaseError::Validation)?;
+
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Add category", ChangeSource::Manual);
+
+    validate_category_parent(&mut tx, None, request.parent_category_id).await?;
+
+    let result = sqlx::query(
+        r#"INSERT INTO categories (name, display_order, parent_category_id, default_discretionary, default_fixed) 
+           VALUES (?, ?, ?, ?, ?)"#,
+    )
+    .bind(&request.name)
+    .bind(request.display_order)
+    .bind(request.parent_category_id)
+    .bind(request.default_discretionary)
+    .bind(request.default_fixed)
+    .execute(&mut *tx)
+    .await?;
+
+    let category_id = result.last_insert_rowid();
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Category,
+        category_id,
+        ChangeAction::Create,
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(category_id)
+}
+
+/// Retrieves all non-archived categories from the database.
+///
+/// Returns a vector of Category structs with all fields populated, ordered by
+/// display_order first, then alphabetically by name for consistent listing.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Category>)` - Vector of Category structs with all database fields
+/// - `Err(sqlx::Error)` - Database query or connection error
+///
+/// # Ordering
+///
+/// Results are ordered by `display_order` first (NULL values last), then by `name` alphabetically.
+/// This ensures system categories and user-ordered categories appear in logical order.
+///
+/// # Examples
+///
+/// ```rust
+/// let categories = get_categories(&pool).await?;
+/// for category in categories {
+///     println!("{}: {}", category.id, category.name);
+///     if category.is_system_category {
+///         println!("  (System category - cannot be deleted)");
+///     }
+/// }
+/// ```
+pub async fn get_categories(pool: &Pool<Sqlite>) -> Result<Vec<Category>, sqlx::Error> {
+    categories_by_archived(pool, false).await
+}
+
+/// Retrieves all archived categories, in the same order as [`get_categories`].
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Category>)` - Archived categories that can be restored with [`restore_category`]
+/// - `Err(sqlx::Error)` - Database query or connection error
+pub async fn get_archived_categories(pool: &Pool<Sqlite>) -> Result<Vec<Category>, sqlx::Error> {
+    categories_by_archived(pool, true).await
+}
+
+async fn categories_by_archived(
+    pool: &Pool<Sqlite>,
+    archived: bool,
+) -> Result<Vec<Category>, sqlx::Error> {
+    let categories = sqlx::query_as::<_, (i64, String, bool, DateTime<Utc>, Option<i32>, Option<i64>, Option<bool>, Option<bool>, Option<NaiveDate>, i64, bool)>(
+        "SELECT id, name, archived, created_at, display_order, parent_category_id, default_discretionary, default_fixed, last_used_date, usage_count, is_system_category FROM categories WHERE archived = ? ORDER BY display_order, name",
+    ).bind(archived).fetch_all(pool).await?;
+
+    let result = categories
+        .into_iter()
+        .map(
+            |(
+                id,
+                name,
+                archived,
+                created_at,
+                display_order,
+                parent_category_id,
+                default_discretionary,
+                default_fixed,
+                last_used_date,
+                usage_count,
+                is_system_category,
+            )| {
+                Category {
+                    id,
+                    name,
+                    archived,
+                    created_at,
+                    display_order,
+                    parent_category_id,
+                    default_discretionary,
+                    default_fixed,
+                    last_used_date,
+                    usage_count,
+                    is_system_category,
+                }
+            },
+        )
+        .collect();
+
+    Ok(result)
+}
+
+/// Retrieves a specific category by its ID.
+///
+/// Fetches complete category information for a single non-archived category,
+/// returning the same full Category struct as get_categories() for consistency.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `id` - The unique ID of the category to retrieve
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Some(Category))` - Complete category information
+/// - `Ok(None)` - No category found with given ID (or category is archived)
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// let category = get_category(&pool, 123).await?;
+/// if let Some(category) = category {
+///     println!("Found category: {}", category.name);
+///     if category.is_system_category {
+///         println!("This is a system category");
+///     }
+/// }
+/// ```
+pub async fn get_category(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Category>, sqlx::Error> {
+    let category = sqlx::query_as::<_, (i64, String, bool, DateTime<Utc>, Option<i32>, Option<i64>, Option<bool>, Option<bool>, Option<NaiveDate>, i64, bool)>(
+        "SELECT id, name, archived, created_at, display_order, parent_category_id, default_discretionary, default_fixed, last_used_date, usage_count, is_system_category FROM categories WHERE id = ? AND archived = FALSE",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    let result = category.map(
+        |(
+            id,
+            name,
+            archived,
+            created_at,
+            display_order,
+            parent_category_id,
+            default_discretionary,
+            default_fixed,
+            last_used_date,
+            usage_count,
+            is_system_category,
+        )| {
+            Category {
+                id,
+                name,
+                archived,
+                created_at,
+                display_order,
+                parent_category_id,
+                default_discretionary,
+                default_fixed,
+                last_used_date,
+                usage_count,
+                is_system_category,
+            }
+        },
+    );
+
+    Ok(result)
+}
+
+/// Updates an existing category with new information.
+///
+/// Modifies an existing category record with the provided data while preserving
+/// system-managed fields like timestamps and ID. System categories cannot be updated
+/// to prevent accidental modification of critical categories like "Uncategorized".
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `category_id` - The ID of the category to update
+/// * `request` - Category update request containing new field values
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Category successfully updated
+/// - `Err(DatabaseError::NotFound)` - The new parent category doesn't exist or is archived
+/// - `Err(DatabaseError::Invalid)` - The parent is the category itself or one of its descendants
+/// - `Err(DatabaseError::Sqlx)` - Database operation error (constraint violations, connection issues, etc.)
+///
+/// # Database Behavior
+///
+/// - Updates all user-settable fields with new values
+/// - Only checks the parent when it changes, so a category whose parent was
+///   archived later can still be edited
+/// - Only updates non-archived, non-system categories (`WHERE archived = FALSE AND is_system_category = FALSE`)
+/// - Preserves `id`, `created_at`, `archived`, and `is_system_category` fields
+///
+/// # Examples
+///
+/// ```rust
+/// let request = CreateCategoryRequest {
+///     name: "Updated Category Name".to_string(),
+///     display_order: Some(5),
+///     parent_category_id: Some(2),
+///     default_discretionary: Some(false),
+///     default_fixed: Some(true),
+/// };
+/// update_category(&pool, 123, &request).await?;
+/// ```
+pub async fn update_category(
+    pool: &Pool<Sqlite>,
+    category_id: i64,
+    request: &CreateCategoryRequest,
+) -> Result<(), DatabaseError> {
+    validation::validate_category(request).map_err(DatabaseError::Validation)?;
+
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Update category", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Category, category_id).await?;
+
+    // A category keeps its parent after the parent is archived, so only a new
+    // parent has to be active
+    let parent_id: Option<Option<i64>> =
+        sqlx::query_scalar("SELECT parent_category_id FROM categories WHERE id = ?")
+            .bind(category_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if parent_id != Some(request.parent_category_id) {
+        validate_category_parent(&mut tx, Some(category_id), request.parent_category_id).await?;
+    }
+
+    sqlx::query(
+        r#"UPDATE categories 
+           SET name = ?, display_order = ?, parent_category_id = ?, 
+               default_discretionary = ?, default_fixed = ?
+           WHERE id = ? AND archived = FALSE AND is_system_category = FALSE"#,
+    )
+    .bind(&request.name)
+    .bind(request.display_order)
+    .bind(request.parent_category_id)
+    .bind(request.default_discretionary)
+    .bind(request.default_fixed)
+    .bind(category_id)
+    .execute(&mut *tx)
+    .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Category,
+        category_id,
+        ChangeAction::Update,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Archives a category by setting its archived flag to true.
+///
+/// Soft deletes a category by marking it as archived, which will hide it from
+/// standard category listings while preserving all data and transaction history.
+/// System categories cannot be archived to protect critical categories like "Uncategorized".
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `category_id` - The ID of the category to archive
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Category successfully archived
+/// - `Err(DatabaseError)` - Database operation error or category not found/is system category
+///
+/// # Database Behavior
+///
+/// - Sets `archived = TRUE` for the specified category
+/// - Only archives non-system categories (`WHERE is_system_category = FALSE`)
+/// - Category will no longer appear in `get_categories()` results
+/// - All associated transactions remain intact but may show "Uncategorized" in UI
+///
+/// # Examples
+///
+/// ```rust
+/// archive_category(&pool, 123).await?;
+/// ```
+pub async fn archive_category(pool: &Pool<Sqlite>, category_id: i64) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Archive category", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Category, category_id).await?;
+
+    sqlx::query(
+        "UPDATE categories SET archived = TRUE WHERE id = ? AND archived = FALSE AND is_system_category = FALSE",
+    )
+    .bind(category_id)
+    .execute(&mut *tx)
+    .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Category,
+        category_id,
+        ChangeAction::Archive,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Restores an archived category, making it active again.
+///
+/// The category keeps its parent. If that parent is still archived, the
+/// category shows up at the top level of the tree until the parent is restored
+/// too.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `category_id` - The ID of the archived category
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Category restored, or it wasn't archived
+/// - `Err(DatabaseError)` - Database operation error
+///
+/// # Examples
+///
+/// ```rust
+/// restore_category(&pool, 123).await?;
+/// ```
+pub async fn restore_category(pool: &Pool<Sqlite>, category_id: i64) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Restore category", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Category, category_id).await?;
+
+    sqlx::query("UPDATE categories SET archived = FALSE WHERE id = ? AND archived = TRUE")
+        .bind(category_id)
+        .execute(&mut *tx)
+        .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Category,
+        category_id,
+        ChangeAction::Restore,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Merges one category into another.
+///
+/// Moves every transaction from `source_id` to `target_id`, re-parents the
+/// source's child categories under the target and then archives the source, all
+/// in one database transaction that is undone as a single step. Trashed
+/// transactions are moved too, so restoring one never brings back a reference to
+/// the archived category.
+///
+/// Splits, budgets and rules don't exist in the schema yet; when they are added
+/// their category references must be moved here as well.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `source_id` - The category to merge away
+/// * `target_id` - The category that receives its transactions and children
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions moved to the target
+/// - `Err(DatabaseError::NotFound)` - Either category doesn't exist or is archived
+/// - `Err(DatabaseError::Invalid)` - Merging a category into itself or one of its
+///   descendants, or merging away a system category
+/// - `Err(DatabaseError::Sqlx)` - Database operation error; nothing was changed
+///
+/// # Examples
+///
+/// ```rust
+/// // Fold "Dining Out" into "Restaurants"
+/// let moved = merge_categories(&pool, dining_out_id, restaurants_id).await?;
+/// ```
+pub async fn merge_categories(
+    pool: &Pool<Sqlite>,
+    source_id: i64,
+    target_id: i64,
+) -> Result<u64, DatabaseError> {
+    if source_id == target_id {
+        return Err(DatabaseError::Invalid(
+            "a category can't be merged into itself".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Merge categories", ChangeSource::Manual);
+
+    let active_category =
+        "SELECT is_system_category FROM categories WHERE id = ? AND archived = FALSE";
+    let source_is_system: bool = sqlx::query_scalar(active_category)
+        .bind(source_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(DatabaseError::NotFound {
+            entity: "category",
+            id: source_id,
+        })?;
+    sqlx::query_scalar::<_, bool>(active_category)
+        .bind(target_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(DatabaseError::NotFound {
+            entity: "category",
+            id: target_id,
+        })?;
+
+    if source_is_system {
+        return Err(DatabaseError::Invalid(
+            "system categories can't be merged away".to_string(),
+        ));
+    }
+
+    let target_is_descendant = is_descendant_category(&mut tx, source_id, target_id).await?;
+
+    if target_is_descendant {
+        return Err(DatabaseError::Invalid(
+            "a category can't be merged into one of its own subcategories".to_string(),
+        ));
+    }
+
+    let transactions =
+        sqlx::query_scalar::<_, i64>("SELECT id FROM transactions WHERE category_id = ?")
+            .bind(source_id)
+            .fetch_all(&mut *tx)
+            .await?;
+    let before = snapshot_all(&mut tx, EntityType::Transaction, &transactions).await?;
+    sqlx::query("UPDATE transactions SET category_id = ? WHERE category_id = ?")
+        .bind(target_id)
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+    record_changes(
+        &mut tx,
+        &mut change,
+        EntityType::Transaction,
+        ChangeAction::Update,
+        before,
+    )
+    .await?;
+
+    let children =
+        sqlx::query_scalar::<_, i64>("SELECT id FROM categories WHERE parent_category_id = ?")
+            .bind(source_id)
+            .fetch_all(&mut *tx)
+            .await?;
+    let before = snapshot_all(&mut tx, EntityType::Category, &children).await?;
+    sqlx::query("UPDATE categories SET parent_category_id = ? WHERE parent_category_id = ?")
+        .bind(target_id)
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+    record_changes(
+        &mut tx,
+        &mut change,
+        EntityType::Category,
+        ChangeAction::Update,
+        before,
+    )
+    .await?;
+
+    let before = snapshot(&mut tx, EntityType::Category, source_id).await?;
+    sqlx::query("UPDATE categories SET archived = TRUE WHERE id = ?")
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Category,
+        source_id,
+        ChangeAction::Archive,
+        before,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(transactions.len() as u64)
+}
+
+/// Whether `id` sits anywhere below `ancestor_id` in the category hierarchy.
+async fn is_descendant_category(
+    conn: &mut SqliteConnection,
+    ancestor_id: i64,
+    id: i64,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+            WITH RECURSIVE descendants(id) AS (
+                SELECT id FROM categories WHERE parent_category_id = ?
+                UNION
+                SELECT c.id FROM categories c JOIN descendants d ON c.parent_category_id = d.id
+            )
+            SELECT EXISTS (SELECT 1 FROM descendants WHERE id = ?)
+        "#,
+    )
+    .bind(ancestor_id)
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// Checks that `parent_id` can be the parent of `category_id`, which is `None`
+/// for a category that doesn't exist yet.
+///
+/// The parent must be an active category other than the category itself or one
+/// of its descendants, so the hierarchy never contains a cycle.
+async fn validate_category_parent(
+    conn: &mut SqliteConnection,
+    category_id: Option<i64>,
+    parent_id: Option<i64>,
+) -> Result<(), DatabaseError> {
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+
+    if category_id == Some(parent_id) {
+        return Err(DatabaseError::Invalid(
+            "a category can't be its own parent".to_string(),
+        ));
+    }
+
+    let parent_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM categories WHERE id = ? AND archived = FALSE)",
+    )
+    .bind(parent_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if !parent_exists {
+        return Err(DatabaseError::NotFound {
+            entity: "category",
+            id: parent_id,
+        });
+    }
+
+    if let Some(category_id) = category_id
+        && is_descendant_category(conn, category_id, parent_id).await?
+    {
+        return Err(DatabaseError::Invalid(
+            "a category can't be moved under one of its own subcategories".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Moves a category, with all of its subcategories, under a new parent.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `category_id` - The root of the subtree to move
+/// * `parent_id` - The new parent, or `None` to make it a top-level category
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Subtree moved
+/// - `Err(DatabaseError::NotFound)` - The category or new parent doesn't exist or is archived
+/// - `Err(DatabaseError::Invalid)` - The new parent is inside the subtree, or the
+///   category is a system category
+/// - `Err(DatabaseError::Sqlx)` - Database operation error
+///
+/// # Examples
+///
+/// ```rust
+/// // Move "Coffee" and everything under it from "Food" to "Treats"
+/// move_category(&pool, coffee_id, Some(treats_id)).await?;
+/// ```
+pub async fn move_category(
+    pool: &Pool<Sqlite>,
+    category_id: i64,
+    parent_id: Option<i64>,
+) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Move category", ChangeSource::Manual);
+
+    let is_system: bool = sqlx::query_scalar(
+        "SELECT is_system_category FROM categories WHERE id = ? AND archived = FALSE",
+    )
+    .bind(category_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(DatabaseError::NotFound {
+        entity: "category",
+        id: category_id,
+    })?;
+    if is_system {
+        return Err(DatabaseError::Invalid(
+            "system categories can't be moved".to_string(),
+        ));
+    }
+
+    validate_category_parent(&mut tx, Some(category_id), parent_id).await?;
+
+    let before = snapshot(&mut tx, EntityType::Category, category_id).await?;
+    sqlx::query("UPDATE categories SET parent_category_id = ? WHERE id = ?")
+        .bind(parent_id)
+        .bind(category_id)
+        .execute(&mut *tx)
+        .await?;
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Category,
+        category_id,
+        ChangeAction::Update,
+        before,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Returns the active categories as a tree, flattened in display order.
+///
+/// Each category is followed by its subcategories, depth first, so the list can
+/// be rendered directly as an indented tree. Siblings keep the `display_order`,
+/// then name, order of [`get_categories`]. A category whose parent is archived
+/// is shown at the top level.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<CategoryTreeNode>)` - Every active category with its depth (0 for
+///   top-level) and full path such as `"Food > Groceries"`
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// for node in get_category_tree(&pool).await? {
+///     println!("{}{}", "  ".repeat(node.depth as usize), node.category.name);
+/// }
+/// ```
+pub async fn get_category_tree(pool: &Pool<Sqlite>) -> Result<Vec<CategoryTreeNode>, sqlx::Error> {
+    let categories = get_categories(pool).await?;
+    let active: HashSet<i64> = categories.iter().map(|category| category.id).collect();
+
+    let mut children: HashMap<Option<i64>, Vec<&Category>> = HashMap::new();
+    for category in &categories {
+        let parent = category
+            .parent_category_id
+            .filter(|parent| active.contains(parent));
+        children.entry(parent).or_default().push(category);
+    }
+
+    // Depth-first walk with an explicit stack; children are pushed in reverse
+    // so they come off the stack in display order
+    let mut nodes = Vec::with_capacity(categories.len());
+    let mut stack: Vec<(&Category, u32, String)> = children
+        .get(&None)
+        .into_iter()
+        .flatten()
+        .rev()
+        .map(|category| (*category, 0, category.name.clone()))
+        .collect();
+
+    while let Some((category, depth, path)) = stack.pop() {
+        for child in children.get(&Some(category.id)).into_iter().flatten().rev() {
+            stack.push((child, depth + 1, format!("{path} > {}", child.name)));
+        }
+        nodes.push(CategoryTreeNode {
+            category: category.clone(),
+            depth,
+            path,
+        });
+    }
+
+    Ok(nodes)
+}
+
+/// Suggests categories for a new transaction, best match first.
+///
+/// Categories the payee's earlier transactions were assigned to come first,
+/// most used for that payee first. The rest follow by how recently and then how
+/// often they were used. Payees match ignoring case and surrounding spaces.
+/// Archived categories, the system category and categories that were never
+/// used are not suggested.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `payee` - Payee of the transaction being entered, if known
+/// * `limit` - Maximum number of suggestions
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<CategorySuggestion>)` - Ranked categories with their uses for the payee
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// let suggestions = suggest_categories(&pool, Some("Safeway"), 5).await?;
+/// if let Some(best) = suggestions.first() {
+///     println!("{} (used {} times for Safeway)", best.category.name, best.payee_uses);
+/// }
+/// ```
+pub async fn suggest_categories(
+    pool: &Pool<Sqlite>,
+    payee: Option<&str>,
+    limit: i32,
+) -> Result<Vec<CategorySuggestion>, sqlx::Error> {
+    let payee = payee.map(str::trim).filter(|payee| !payee.is_empty());
+
+    let ranked = sqlx::query_as::<_, (i64, i64)>(
+        r#"
+        SELECT c.id, COALESCE(p.uses, 0) AS payee_uses
+        FROM categories c
+        LEFT JOIN (
+            SELECT category_id, COUNT(*) AS uses, MAX(date) AS last_date
+            FROM transactions
+            WHERE category_id IS NOT NULL
+              AND deleted_at IS NULL
+              AND trim(payee) = ? COLLATE NOCASE
+            GROUP BY category_id
+        ) p ON p.category_id = c.id
+        WHERE c.archived = FALSE
+          AND c.is_system_category = FALSE
+          AND (p.uses IS NOT NULL OR c.usage_count > 0)
+        ORDER BY payee_uses DESC, p.last_date DESC, c.last_used_date DESC, c.usage_count DESC, c.name
+        LIMIT ?
+        "#,
+    )
+    .bind(payee)
+    .bind(limit.max(0))
+    .fetch_all(pool)
+    .await?;
+
+    let mut categories: HashMap<i64, Category> = get_categories(pool)
+        .await?
+        .into_iter()
+        .map(|category| (category.id, category))
+        .collect();
+
+    let suggestions = ranked
+        .into_iter()
+        .filter_map(|(id, payee_uses)| {
+            Some(CategorySuggestion {
+                category: categories.remove(&id)?,
+                payee_uses,
+            })
+        })
+        .collect();
+
+    Ok(suggestions)
+}
+
+/// Updates an existing account with new information.
+///
+/// Modifies an existing account record with the provided data while preserving
+/// system-managed fields like timestamps and ID. Currency amounts are automatically
+/// converted from dollars to integer cents for precise storage.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `account_id` - The ID of the account to update
+/// * `request` - Account update request containing new field values
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Account successfully updated
+/// - `Err(DatabaseError)` - Database operation error (constraint violations, connection issues, account not found, etc.)
+///
+/// # Database Behavior
+///
+/// - Updates all user-settable fields with new values
+/// - `updated_at` is set to CURRENT_TIMESTAMP automatically
+/// - Only updates non-archived accounts (`WHERE archived = FALSE`)
+/// - Currency amounts are converted from dollars to cents before storage
+/// - Preserves `id`, `created_at`, and `archived` fields
+///
+/// # Examples
+///
+/// ```rust
+/// let request = CreateAccountRequest {
+///     name: "Updated Account Name".to_string(),
+///     account_type: "savings".to_string(),
+///     institution: Some("New Bank".to_string()),
+///     current_balance: Some(2000.75),
+///     display_order: Some(3),
+///     include_in_net_worth: Some(false),
+///     account_number_last4: Some("9876".to_string()),
+/// };
+/// update_account(&pool, 123, &request).await?;
+/// ```
+pub async fn update_account(
+    pool: &Pool<Sqlite>,
+    account_id: i64,
+    request: &CreateAccountRequest,
+) -> Result<(), DatabaseError> {
+    validation::validate_account(request).map_err(DatabaseError::Validation)?;
+
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Update account", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Account, account_id).await?;
+
+    sqlx::query(
+        r#"UPDATE accounts 
+           SET name = ?, type = ?, institution = ?, current_balance = ?, 
+               display_order = ?, include_in_net_worth = ?, account_number_last4 = ?,
+               updated_at = CURRENT_TIMESTAMP
+           WHERE id = ? AND archived = FALSE"#,
+    )
+    .bind(&request.name)
+    .bind(&request.account_type)
+    .bind(&request.institution)
+    .bind(dollars_to_cents_option(request.current_balance))
+    .bind(request.display_order)
+    .bind(request.include_in_net_worth.unwrap_or(true))
+    .bind(&request.account_number_last4)
+    .bind(account_id)
+    .execute(&mut *tx)
+    .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Account,
+        account_id,
+        ChangeAction::Update,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Archives an account by setting its archived flag to true.
+///
+/// Soft deletes an account by marking it as archived, which will hide it from
+/// the standard account listings while preserving all data and transaction history.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `account_id` - The ID of the account to archive
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Account successfully archived
+/// - `Err(DatabaseError)` - Database operation error or account not found
+///
+/// # Database Behavior
+///
+/// - Sets `archived = TRUE` for the specified account
+/// - Updates `updated_at` timestamp automatically
+/// - Account will no longer appear in `get_accounts()` results
+/// - All associated transactions remain intact
+///
+/// # Examples
+///
+/// ```rust
+/// archive_account(&pool, 123).await?;
+/// ```
+pub async fn archive_account(pool: &Pool<Sqlite>, account_id: i64) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Archive account", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Account, account_id).await?;
+
+    sqlx::query(
+        "UPDATE accounts SET archived = TRUE, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND archived = FALSE",
+    )
+    .bind(account_id)
+    .execute(&mut *tx)
+    .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Account,
+        account_id,
+        ChangeAction::Archive,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Restores an archived account, making it active again.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `account_id` - The ID of the archived account
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Account restored, or it wasn't archived
+/// - `Err(DatabaseError)` - Database operation error
+///
+/// # Examples
+///
+/// ```rust
+/// restore_account(&pool, 123).await?;
+/// ```
+pub async fn restore_account(pool: &Pool<Sqlite>, account_id: i64) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Restore account", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Account, account_id).await?;
+
+    sqlx::query(
+        "UPDATE accounts SET archived = FALSE, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND archived = TRUE",
+    )
+    .bind(account_id)
+    .execute(&mut *tx)
+    .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Account,
+        account_id,
+        ChangeAction::Restore,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub fn dollars_to_cents(dollars: f64) -> i64 {
+    (dollars * 100.0).round() as i64
+}
+
+pub fn cents_to_dollars(cents: i64) -> f64 {
+    cents as f64 / 100.0
+}
+
+pub fn dollars_to_cents_option(dollars: Option<f64>) -> Option<i64> {
+    dollars.map(dollars_to_cents)
+}
+
+pub fn cents_to_dollars_option(cents: Option<i64>) -> Option<f64> {
+    cents.map(cents_to_dollars)
+}
+
+pub async fn insert_transaction(
+    pool: &Pool<Sqlite>,
+    request: &CreateTransactionRequest,
+) -> Result<i64, DatabaseError> {
+    validation::validate_transaction(request).map_err(DatabaseError::Validation)?;
+
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Add transaction", ChangeSource::Manual);
+    let transaction_id = insert_transaction_row(&mut tx, &mut change, request).await?;
+    tx.commit().await?;
+
+    Ok(transaction_id)
+}
+
+/// Adds a validated transaction with its tags and logs it under `change`.
+async fn insert_transaction_row(
+    conn: &mut SqliteConnection,
+    change: &mut ChangeContext,
+    request: &CreateTransactionRequest,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"INSERT INTO transactions (account_id, date, amount, description, payee, memo, category_id, pending, cleared, spending_kind, transaction_type, source) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'expense', 'manual')"#,
+    )
+    .bind(request.account_id)
+    .bind(request.date)
+    .bind(dollars_to_cents(request.amount))
+    .bind(request.description.as_deref())
+    .bind(request.payee.as_deref())
+    .bind(request.memo.as_deref())
+    .bind(request.category_id)
+    .bind(request.pending)
+    .bind(request.cleared)
+    .bind(request.spending_kind.map(SpendingKind::as_str))
+    .execute(&mut *conn)
+    .await?;
+
+    let transaction_id = result.last_insert_rowid();
+
+    if let Some(tag_ids) = &request.tag_ids {
+        set_transaction_tags(conn, transaction_id, tag_ids).await?;
+    }
+
+    record_change(
+        conn,
+        change,
+        EntityType::Transaction,
+        transaction_id,
+        ChangeAction::Create,
+        None,
+    )
+    .await?;
+
+    Ok(transaction_id)
+}
+
+/// Replaces the full set of tags on a transaction.
+async fn set_transaction_tags(
+    conn: &mut SqliteConnection,
+    transaction_id: i64,
+    tag_ids: &[i64],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM transaction_tags WHERE transaction_id = ?")
+        .bind(transaction_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        "INSERT OR IGNORE INTO transaction_tags (transaction_id, tag_id) SELECT ?, value FROM json_each(?)",
+    )
+    .bind(transaction_id)
+    .bind(ids_to_json(tag_ids))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Encodes a list of IDs as a JSON array for binding to `json_each(?)`.
+fn ids_to_json(ids: &[i64]) -> String {
+    serde_json::to_string(ids).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Parses the comma-separated `tag_ids` column produced by `group_concat`.
+fn parse_tag_ids(tag_ids: Option<String>) -> Vec<i64> {
+    let mut ids: Vec<i64> = tag_ids
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|id| id.parse().ok())
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// Reads a transaction from a row, failing rather than panicking on a value
+/// that doesn't decode, such as a date an older version stored in another format.
+fn transaction_from_row(row: &SqliteRow) -> Result<Transaction, sqlx::Error> {
+    Ok(Transaction {
+        id: row.try_get("id")?,
+        account_id: row.try_get("account_id")?,
+        date: row.try_get("date")?,
+        amount: cents_to_dollars(row.try_get("amount")?),
+        description: row.try_get("description")?,
+        category_id: row.try_get("category_id")?,
+        pending: row.try_get("pending")?,
+        transaction_type: row.try_get("transaction_type")?,
+        created_at: row.try_get("created_at")?,
+        reconciled: row.try_get("reconciled")?,
+        import_id: row.try_get("import_id")?,
+        source: row.try_get("source")?,
+        payee: row.try_get("payee")?,
+        original_description: row.try_get("original_description")?,
+        memo: row.try_get("memo")?,
+        spending_kind: row
+            .try_get::<Option<String>, _>("spending_kind")?
+            .as_deref()
+            .and_then(SpendingKind::parse),
+        tag_ids: parse_tag_ids(row.try_get("tag_ids")?),
+    })
+}
+
+/// Retrieves a specific transaction by its ID.
+///
+/// Fetches complete transaction information for a single transaction,
+/// returning the same full Transaction struct as get_account_transactions() for consistency.
+/// Currency amounts are automatically converted from cents to dollars.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `id` - The unique ID of the transaction to retrieve
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Some(Transaction))` - Complete transaction information
+/// - `Ok(None)` - No transaction found with given ID
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// let transaction = get_transaction(&pool, 123).await?;
+/// if let Some(transaction) = transaction {
+///     println!("Found transaction: {} - ${:.2}", transaction.description, transaction.amount);
+///     println!("Account ID: {}", transaction.account_id);
+/// }
+/// ```
+pub async fn get_transaction(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Transaction>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+            SELECT
+                id, account_id, date, amount, description, category_id, pending, transaction_type, created_at, reconciled, import_id, source, payee, original_description, memo, spending_kind,
+                (SELECT group_concat(tag_id) FROM transaction_tags WHERE transaction_id = transactions.id) AS tag_ids
+            FROM transactions
+            WHERE id = ? AND deleted_at IS NULL
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|row| transaction_from_row(&row)).transpose()
+}
+
+/// Updates an existing transaction with new information.
+///
+/// Modifies an existing transaction record with the provided data while preserving
+/// system-managed fields like ID, account_id, and timestamps. Currency amounts are
+/// automatically converted from dollars to integer cents for precise storage.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `transaction_id` - The ID of the transaction to update
+/// * `request` - Transaction update request containing new field values
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Transaction successfully updated
+/// - `Err(DatabaseError)` - Database operation error (constraint violations, connection issues, transaction not found, etc.)
+///
+/// # Database Behavior
+///
+/// - Updates all user-settable fields with new values
+/// - Replaces the transaction's tags when `tag_ids` is provided; `None` leaves them unchanged
+/// - Preserves `id`, `account_id`, `created_at`, `transaction_type`, `reconciled`, `import_id`, `source`, and `original_description` fields
+/// - Currency amounts are converted from dollars to cents before storage
+///
+/// # Examples
+///
+/// ```rust
+/// let request = UpdateTransactionRequest {
+///     date: NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+///     amount: 25.50,
+///     description: Some("Updated grocery expense".to_string()),
+///     payee: Some("Safeway".to_string()),
+///     memo: Some("Weekly shopping".to_string()),
+///     category_id: Some(5),
+///     pending: false,
+///     cleared: true,
+///     tag_ids: Some(vec![2]),
+///     spending_kind: None,
+/// };
+/// update_transaction(&pool, 123, &request).await?;
+/// ```
+pub async fn update_transaction(
+    pool: &Pool<Sqlite>,
+    transaction_id: i64,
+    request: &UpdateTransactionRequest,
+) -> Result<(), DatabaseError> {
+    validation::validate_transaction_update(request).map_err(DatabaseError::Validation)?;
+
+    let amount_cents = dollars_to_cents(request.amount);
+
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Update transaction", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Transaction, transaction_id).await?;
+
+    let result = sqlx::query(
+        r#"UPDATE transactions 
+           SET date = ?, amount = ?, description = ?, payee = ?, memo = ?, 
+               category_id = ?, pending = ?, cleared = ?, spending_kind = ?
+           WHERE id = ? AND deleted_at IS NULL"#,
+    )
+    .bind(request.date)
+    .bind(amount_cents)
+    .bind(request.description.as_deref())
+    .bind(request.payee.as_deref())
+    .bind(request.memo.as_deref())
+    .bind(request.category_id)
+    .bind(request.pending)
+    .bind(request.cleared)
+    .bind(request.spending_kind.map(SpendingKind::as_str))
+    .bind(transaction_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if let Some(tag_ids) = &request.tag_ids
+        && result.rows_affected() > 0
+    {
+        set_transaction_tags(&mut tx, transaction_id, tag_ids).await?;
+    }
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Transaction,
+        transaction_id,
+        ChangeAction::Update,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Moves a transaction to the trash.
+///
+/// The transaction disappears from listings, search and reports but stays in the
+/// database, along with its tags, attachments and any transfer that references
+/// it, until it is restored or purged from the trash.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `transaction_id` - The ID of the transaction to delete
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Transaction moved to the trash
+/// - `Err(DatabaseError)` - Database operation error
+///
+/// # Database Behavior
+///
+/// - Sets `deleted_at` to the current time; no rows are removed
+/// - Returns success even if no transaction with the given ID exists or it is
+///   already in the trash
+/// - Use [`restore_transaction`] to take it back out, [`purge_trash`] to remove it for good
+///
+/// # Examples
+///
+/// ```rust
+/// delete_transaction(&pool, 123).await?;
+/// ```
+pub async fn delete_transaction(
+    pool: &Pool<Sqlite>,
+    transaction_id: i64,
+) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Delete transaction", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Transaction, transaction_id).await?;
+
+    sqlx::query(
+        "UPDATE transactions SET deleted_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(transaction_id)
+    .execute(&mut *tx)
+    .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Transaction,
+        transaction_id,
+        ChangeAction::Delete,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Lists the transactions in the trash, most recently deleted first.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<TrashedTransaction>)` - Each trashed transaction with when it was deleted
+/// - `Err(sqlx::Error)` - Database query error
+pub async fn get_trash(pool: &Pool<Sqlite>) -> Result<Vec<TrashedTransaction>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+            SELECT
+                id, account_id, date, amount, description, category_id, pending, transaction_type, created_at, reconciled, import_id, source, payee, original_description, memo, spending_kind,
+                (SELECT group_concat(tag_id) FROM transaction_tags WHERE transaction_id = transactions.id) AS tag_ids,
+                deleted_at
+            FROM transactions
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC, id DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(TrashedTransaction {
+                transaction: transaction_from_row(row)?,
+                deleted_at: row.try_get("deleted_at")?,
+            })
+        })
+        .collect()
+}
+
+/// Takes a transaction back out of the trash.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `transaction_id` - The ID of the trashed transaction
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Transaction restored, or it wasn't in the trash
+/// - `Err(DatabaseError)` - Database operation error
+pub async fn restore_transaction(
+    pool: &Pool<Sqlite>,
+    transaction_id: i64,
+) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Restore transaction", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Transaction, transaction_id).await?;
+
+    sqlx::query(
+        "UPDATE transactions SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+    )
+    .bind(transaction_id)
+    .execute(&mut *tx)
+    .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Transaction,
+        transaction_id,
+        ChangeAction::Restore,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Permanently removes transactions that have been in the trash for at least
+/// `older_than_days` days.
+///
+/// Purging is recorded in the change log but can't be undone. Pass `0` to empty
+/// the trash entirely.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `older_than_days` - Minimum age of a trashed transaction before it is purged
+/// * `source` - What triggered the purge: the user, or the retention policy on startup
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions purged
+/// - `Err(DatabaseError)` - Database operation error; nothing was purged
+///
+/// # Database Behavior
+///
+/// - Deletes the transactions together with their attachments and tag links
+/// - Deletes transfer links that reference a purged transaction, so no transfer
+///   is left pointing at a missing row; the other side of the transfer is kept
+/// - Drops the undo and redo operations that changed a purged transaction or
+///   transfer, since replaying them would bring it back
+///
+/// # Examples
+///
+/// ```rust
+/// // Apply a 30 day retention policy
+/// let purged = purge_trash(&pool, 30, ChangeSource::Schedule).await?;
+/// ```
+pub async fn purge_trash(
+    pool: &Pool<Sqlite>,
+    older_than_days: u32,
+    source: ChangeSource,
+) -> Result<u64, DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::untracked(source);
+
+    let expired = sqlx::query_scalar::<_, i64>(
+        "SELECT id FROM transactions WHERE deleted_at <= datetime('now', ?)",
+    )
+    .bind(format!("-{older_than_days} days"))
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let before = snapshot_all(&mut tx, EntityType::Transaction, &expired).await?;
+    let expired = ids_to_json(&expired);
+
+    let transfers = sqlx::query_scalar::<_, i64>(
+        r#"SELECT id FROM transfers
+           WHERE from_transaction_id IN (SELECT value FROM json_each(?))
+              OR to_transaction_id IN (SELECT value FROM json_each(?))"#,
+    )
+    .bind(&expired)
+    .bind(&expired)
+    .fetch_all(&mut *tx)
+    .await?;
+    let transfers_before = snapshot_all(&mut tx, EntityType::Transfer, &transfers).await?;
+
+    sqlx::query("DELETE FROM transfers WHERE id IN (SELECT value FROM json_each(?))")
+        .bind(ids_to_json(&transfers))
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM attachments WHERE transaction_id IN (SELECT value FROM json_each(?))")
+        .bind(&expired)
+        .execute(&mut *tx)
+        .await?;
+
+    let result =
+        sqlx::query("DELETE FROM transactions WHERE id IN (SELECT value FROM json_each(?))")
+            .bind(&expired)
+            .execute(&mut *tx)
+            .await?;
+
+    sqlx::query(
+        r#"DELETE FROM operations WHERE id IN (
+               SELECT operation_id FROM change_log
+               WHERE (entity_type = 'transaction' AND entity_id IN (SELECT value FROM json_each(?)))
+                  OR (entity_type = 'transfer' AND entity_id IN (SELECT value FROM json_each(?)))
+           )"#,
+    )
+    .bind(&expired)
+    .bind(ids_to_json(&transfers))
+    .execute(&mut *tx)
+    .await?;
+
+    record_changes(
+        &mut tx,
+        &mut change,
+        EntityType::Transfer,
+        ChangeAction::Purge,
+        transfers_before,
+    )
+    .await?;
+    record_changes(
+        &mut tx,
+        &mut change,
+        EntityType::Transaction,
+        ChangeAction::Purge,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Sorted, de-duplicated tag IDs as a JSON array, or `None` when there is no tag filter.
+fn tag_filter_json(tag_ids: Option<&[i64]>) -> Option<String> {
+    tag_ids.filter(|ids| !ids.is_empty()).map(|ids| {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+        ids_to_json(&ids)
+    })
+}
+
+/// Resolves a bulk target to the IDs of the transactions it covers.
+///
+/// A filter matches the same transactions as [`search_transactions`], but
+/// returns all of them unless the filter sets a limit. Like a search, a filter
+/// without search text or tags matches nothing.
+async fn bulk_target_ids(
+    conn: &mut SqliteConnection,
+    target: &BulkTarget,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let request = match target {
+        BulkTarget::Ids(ids) => return Ok(ids.clone()),
+        BulkTarget::Filter(request) => request,
+    };
+
+    let match_query = build_fts_query(&request.query);
+    let tag_filter = tag_filter_json(request.tag_ids.as_deref());
+
+    if match_query.is_none() && tag_filter.is_none() {
+        return Ok(Vec::new());
+    }
+
+    sqlx::query_scalar(
+        r#"
+            SELECT t.id FROM transactions t
+            WHERE t.deleted_at IS NULL
+              AND (? IS NULL OR t.id IN (SELECT rowid FROM transactions_fts WHERE transactions_fts MATCH ?))
+              AND (? IS NULL OR t.account_id = ?)
+              AND (? IS NULL OR t.id IN (
+                  SELECT transaction_id FROM transaction_tags
+                  WHERE tag_id IN (SELECT value FROM json_each(?))
+                  GROUP BY transaction_id
+                  HAVING COUNT(*) = json_array_length(?)
+              ))
+            ORDER BY t.id
+            LIMIT ?
+        "#,
+    )
+    .bind(&match_query)
+    .bind(&match_query)
+    .bind(request.account_id)
+    .bind(request.account_id)
+    .bind(&tag_filter)
+    .bind(&tag_filter)
+    .bind(&tag_filter)
+    .bind(request.limit.unwrap_or(-1))
+    .fetch_all(&mut *conn)
+    .await
+}
+
+/// Applies one change to many transactions in a single database transaction.
+///
+/// Either every targeted transaction is changed or, on error, none is. The whole
+/// command is a single entry on the undo stack, and each changed transaction
+/// gets its own change log entry.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `target` - A list of transaction IDs, or a search filter
+/// * `change` - The change to apply
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions that actually changed
+/// - `Err(DatabaseError)` - Database operation error, such as an unknown category
+///   or account; nothing was changed
+///
+/// # Database Behavior
+///
+/// - Trashed and unknown transaction IDs are skipped
+/// - Transactions that already have the requested value are skipped and not counted
+/// - `Delete` moves the transactions to the trash
+///
+/// # Examples
+///
+/// ```rust
+/// let target = BulkTarget::Filter(TransactionSearchRequest {
+///     query: "amazon".to_string(),
+///     account_id: None,
+///     tag_ids: None,
+///     limit: None,
+/// });
+/// let changed = bulk_update_transactions(&pool, &target, &BulkChange::SetCategory { category_id: Some(7) }).await?;
+/// ```
+pub async fn bulk_update_transactions(
+    pool: &Pool<Sqlite>,
+    target: &BulkTarget,
+    change: &BulkChange,
+) -> Result<u64, DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut context = ChangeContext::operation(change.label(), ChangeSource::Manual);
+
+    let targets = ids_to_json(&bulk_target_ids(&mut tx, target).await?);
+
+    // Narrow the targets down to the transactions the change actually affects
+    let affected: Vec<i64> = match change {
+        BulkChange::SetCategory { category_id } => {
+            sqlx::query_scalar(
+                r#"SELECT id FROM transactions
+                   WHERE id IN (SELECT value FROM json_each(?)) AND deleted_at IS NULL
+                     AND category_id IS NOT ?"#,
+            )
+            .bind(&targets)
+            .bind(category_id)
+            .fetch_all(&mut *tx)
+            .await?
+        }
+        BulkChange::SetPayee { payee } => {
+            sqlx::query_scalar(
+                r#"SELECT id FROM transactions
+                   WHERE id IN (SELECT value FROM json_each(?)) AND deleted_at IS NULL
+                     AND payee IS NOT ?"#,
+            )
+            .bind(&targets)
+            .bind(payee)
+            .fetch_all(&mut *tx)
+            .await?
+        }
+        BulkChange::SetCleared { cleared } => {
+            sqlx::query_scalar(
+                r#"SELECT id FROM transactions
+                   WHERE id IN (SELECT value FROM json_each(?)) AND deleted_at IS NULL
+                     AND cleared IS NOT ?"#,
+            )
+            .bind(&targets)
+            .bind(cleared)
+            .fetch_all(&mut *tx)
+            .await?
+        }
+        BulkChange::AddTag { tag_id } => {
+            sqlx::query_scalar(
+                r#"SELECT id FROM transactions
+                   WHERE id IN (SELECT value FROM json_each(?)) AND deleted_at IS NULL
+                     AND id NOT IN (SELECT transaction_id FROM transaction_tags WHERE tag_id = ?)
+                     AND EXISTS (SELECT 1 FROM tags WHERE id = ?)"#,
+            )
+            .bind(&targets)
+            .bind(tag_id)
+            .bind(tag_id)
+            .fetch_all(&mut *tx)
+            .await?
+        }
+        BulkChange::RemoveTag { tag_id } => {
+            sqlx::query_scalar(
+                r#"SELECT id FROM transactions
+                   WHERE id IN (SELECT value FROM json_each(?)) AND deleted_at IS NULL
+                     AND id IN (SELECT transaction_id FROM transaction_tags WHERE tag_id = ?)"#,
+            )
+            .bind(&targets)
+            .bind(tag_id)
+            .fetch_all(&mut *tx)
+            .await?
+        }
+        BulkChange::MoveToAccount { account_id } => {
+            sqlx::query_scalar(
+                r#"SELECT id FROM transactions
+                   WHERE id IN (SELECT value FROM json_each(?)) AND deleted_at IS NULL
+                     AND account_id != ?"#,
+            )
+            .bind(&targets)
+            .bind(account_id)
+            .fetch_all(&mut *tx)
+            .await?
+        }
+        BulkChange::Delete => {
+            sqlx::query_scalar(
+                "SELECT id FROM transactions WHERE id IN (SELECT value FROM json_each(?)) AND deleted_at IS NULL",
+            )
+            .bind(&targets)
+            .fetch_all(&mut *tx)
+            .await?
+        }
+    };
+
+    let before = snapshot_all(&mut tx, EntityType::Transaction, &affected).await?;
+    let affected_ids = ids_to_json(&affected);
+
+    let query = match change {
+        BulkChange::SetCategory { category_id } => {
+            sqlx::query("UPDATE transactions SET category_id = ? WHERE id IN (SELECT value FROM json_each(?))")
+                .bind(*category_id)
+        }
+        BulkChange::SetPayee { payee } => {
+            sqlx::query("UPDATE transactions SET payee = ? WHERE id IN (SELECT value FROM json_each(?))")
+                .bind(payee.as_deref())
+        }
+        BulkChange::SetCleared { cleared } => {
+            sqlx::query("UPDATE transactions SET cleared = ? WHERE id IN (SELECT value FROM json_each(?))")
+                .bind(*cleared)
+        }
+        BulkChange::AddTag { tag_id } => sqlx::query(
+            "INSERT INTO transaction_tags (transaction_id, tag_id) SELECT value, ? FROM json_each(?)",
+        )
+        .bind(*tag_id),
+        BulkChange::RemoveTag { tag_id } => sqlx::query(
+            "DELETE FROM transaction_tags WHERE tag_id = ? AND transaction_id IN (SELECT value FROM json_each(?))",
+        )
+        .bind(*tag_id),
+        BulkChange::MoveToAccount { account_id } => {
+            sqlx::query("UPDATE transactions SET account_id = ? WHERE id IN (SELECT value FROM json_each(?))")
+                .bind(*account_id)
+        }
+        BulkChange::Delete => sqlx::query(
+            "UPDATE transactions SET deleted_at = CURRENT_TIMESTAMP WHERE id IN (SELECT value FROM json_each(?))",
+        ),
+    };
+    query.bind(&affected_ids).execute(&mut *tx).await?;
+
+    let action = match change {
+        BulkChange::Delete => ChangeAction::Delete,
+        _ => ChangeAction::Update,
+    };
+    record_changes(
+        &mut tx,
+        &mut context,
+        EntityType::Transaction,
+        action,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(affected.len() as u64)
+}
+
+/// Retrieves one page of an account's transactions, newest first.
+///
+/// Uses keyset pagination on `(date, id)`, matching the `ORDER BY date DESC, id DESC`
+/// listing order. Each page starts strictly after the cursor's position, so the
+/// cost of a page does not grow with scroll depth and rows inserted while paging
+/// can't shift later pages into skipping or repeating transactions.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `account_id` - The account whose transactions to list
+/// * `limit` - Maximum number of transactions in the page
+/// * `cursor` - Position to continue after, or `None` for the first page
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(TransactionPage)` - The page plus an opaque cursor for the next page, if any
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// let first = get_transactions(&pool, account_id, 50, None).await?;
+/// if let Some(next) = first.next_cursor.as_deref().and_then(TransactionCursor::decode) {
+///     let second = get_transactions(&pool, account_id, 50, Some(&next)).await?;
+/// }
+/// ```
+pub async fn get_transactions(
+    pool: &Pool<Sqlite>,
+    account_id: i64,
+    limit: i32,
+    cursor: Option<&TransactionCursor>,
+) -> Result<TransactionPage, sqlx::Error> {
+    let limit = limit.max(0);
+
+    // Fetch one extra row to learn whether another page follows
+    let rows = match cursor {
+        Some(cursor) => {
+            sqlx::query(
+                r#"
+                    SELECT
+                        id, account_id, date, amount, description, category_id, pending, transaction_type, created_at, reconciled, import_id, source, payee, original_description, memo, spending_kind,
+                        (SELECT group_concat(tag_id) FROM transaction_tags WHERE transaction_id = transactions.id) AS tag_ids
+                    FROM transactions
+                    WHERE account_id = ? AND (date, id) < (?, ?) AND deleted_at IS NULL
+                    ORDER BY date DESC, id DESC
+                    LIMIT ?
+                "#,
+            )
+            .bind(account_id)
+            .bind(cursor.date)
+            .bind(cursor.id)
+            .bind(limit + 1)
+            .fetch_all(pool)
+            .await?
+        }
+        None => {
+            sqlx::query(
+                r#"
+                    SELECT
+                        id, account_id, date, amount, description, category_id, pending, transaction_type, created_at, reconciled, import_id, source, payee, original_description, memo, spending_kind,
+                        (SELECT group_concat(tag_id) FROM transaction_tags WHERE transaction_id = transactions.id) AS tag_ids
+                    FROM transactions
+                    WHERE account_id = ? AND deleted_at IS NULL
+                    ORDER BY date DESC, id DESC
+                    LIMIT ?
+                "#,
+            )
+            .bind(account_id)
+            .bind(limit + 1)
+            .fetch_all(pool)
+            .await?
+        }
+    };
+
+    let mut transactions = rows
+        .iter()
+        .map(transaction_from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let next_cursor = if transactions.len() > limit as usize {
+        transactions.truncate(limit as usize);
+        transactions.last().map(|last| {
+            TransactionCursor {
+                date: last.date,
+                id: last.id,
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
+    Ok(TransactionPage {
+        transactions,
+        next_cursor,
+    })
+}
+
+/// Retrieves every transaction of an account, newest first.
+///
+/// Unlike [`get_transactions`] this isn't paged, so it is meant for summaries
+/// such as balances rather than for listing.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `account_id` - The account whose transactions to fetch
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Transaction>)` - The account's transactions, excluding the trash
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// let transactions = get_account_transactions(&pool, 1).await?;
+/// let pending = transactions.iter().filter(|t| t.pending).count();
+/// ```
+pub async fn get_account_transactions(
+    pool: &Pool<Sqlite>,
+    account_id: i64,
+) -> Result<Vec<Transaction>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+            SELECT
+                id, account_id, date, amount, description, category_id, pending, transaction_type, created_at, reconciled, import_id, source, payee, original_description, memo, spending_kind,
+                (SELECT group_concat(tag_id) FROM transaction_tags WHERE transaction_id = transactions.id) AS tag_ids
+            FROM transactions
+            WHERE account_id = ? AND deleted_at IS NULL
+            ORDER BY date DESC, id DESC
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(transaction_from_row).collect()
+}
+
+/// Sums the transactions of active accounts into their balances.
+///
+/// Amounts are summed in cents by SQLite, so the result is exact and no
+/// transaction is loaded. Trashed transactions are left out, and an account
+/// without transactions has a zero balance.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `account_id` - Only this account, or `None` for every active account
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<AccountBalance>)` - One balance per active account, in account order;
+///   empty if `account_id` doesn't exist or is archived
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// for balance in get_account_balances(&pool, None).await? {
+///     println!("{}: {:.2}", balance.account_id, balance.balance);
+/// }
+/// ```
+pub async fn get_account_balances(
+    pool: &Pool<Sqlite>,
+    account_id: Option<i64>,
+) -> Result<Vec<AccountBalance>, sqlx::Error> {
+    let balances = sqlx::query_as::<_, (i64, i64, i64, i64)>(
+        r#"
+            SELECT
+                a.id,
+                COALESCE(SUM(t.amount), 0),
+                COALESCE(SUM(CASE WHEN t.pending THEN t.amount END), 0),
+                COALESCE(SUM(CASE WHEN t.reconciled THEN t.amount END), 0)
+            FROM accounts a
+            LEFT JOIN transactions t ON t.account_id = a.id AND t.deleted_at IS NULL
+            WHERE a.archived = FALSE AND (? IS NULL OR a.id = ?)
+            GROUP BY a.id
+            ORDER BY a.display_order, a.name
+        "#,
+    )
+    .bind(account_id)
+    .bind(account_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(balances
+        .into_iter()
+        .map(
+            |(account_id, balance, pending, reconciled)| AccountBalance {
+                account_id,
+                balance: cents_to_dollars(balance),
+                pending: cents_to_dollars(pending),
+                reconciled: cents_to_dollars(reconciled),
+            },
+        )
+        .collect())
+}
+
+/// Adds both sides of an internal transfer and links them, all or nothing.
+///
+/// The withdrawal, the deposit and the link are added in one database
+/// transaction and logged as a single operation, so undoing the transfer
+/// removes all three and redoing it brings them back.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `withdrawal` - The transaction money leaves from
+/// * `deposit` - The transaction money arrives in
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The ID of the new transfer
+/// - `Err(DatabaseError::Validation)` - Invalid fields on either side
+/// - `Err(DatabaseError::ForeignKeyViolation)` - Either account or category doesn't exist
+/// - `Err(DatabaseError::InvariantViolation)` - Either account or category is archived
+/// - `Err(DatabaseError::Sqlx)` - Database operation error
+///
+/// # Examples
+///
+/// ```rust
+/// let transfer_id = insert_transfer(&pool, &withdrawal, &deposit).await?;
+/// ```
+pub async fn insert_transfer(
+    pool: &Pool<Sqlite>,
+    withdrawal: &CreateTransactionRequest,
+    deposit: &CreateTransactionRequest,
+) -> Result<i64, DatabaseError> {
+    validation::validate_transaction(withdrawal).map_err(DatabaseError::Validation)?;
+    validation::validate_transaction(deposit).map_err(DatabaseError::Validation)?;
+
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Add transfer", ChangeSource::Manual);
+
+    let from_transaction_id = insert_transaction_row(&mut tx, &mut change, withdrawal).await?;
+    let to_transaction_id = insert_transaction_row(&mut tx, &mut change, deposit).await?;
+    let result = sqlx::query(
+        "INSERT INTO transfers (from_transaction_id, to_transaction_id, transfer_type) VALUES (?, ?, 'internal')",
+    )
+    .bind(from_transaction_id)
+    .bind(to_transaction_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let transfer_id = result.last_insert_rowid();
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Transfer,
+        transfer_id,
+        ChangeAction::Create,
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(transfer_id)
+}
+
+/// Retrieves every transfer, oldest first.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Transfer>)` - All transfers, including ones whose transactions are in the trash
+/// - `Err(sqlx::Error)` - Database query error
+pub async fn get_transfers(pool: &Pool<Sqlite>) -> Result<Vec<Transfer>, sqlx::Error> {
+    let transfers = sqlx::query_as::<_, (i64, i64, i64, Option<String>, bool, DateTime<Utc>)>(
+        "SELECT id, from_transaction_id, to_transaction_id, transfer_type, auto_created, created_at FROM transfers ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(transfers
+        .into_iter()
+        .map(
+            |(
+                id,
+                from_transaction_id,
+                to_transaction_id,
+                transfer_type,
+                auto_created,
+                created_at,
+            )| {
+                Transfer {
+                    id,
+                    from_transaction_id,
+                    to_transaction_id,
+                    transfer_type,
+                    auto_created,
+                    created_at,
+                }
+            },
+        )
+        .collect())
+}
+
+/// Converts free-form user search input into an FTS5 match expression.
+///
+/// Every bare word becomes a quoted term so punctuation in payee names can't
+/// be parsed as FTS5 syntax. A trailing `*` turns a word into a prefix query
+/// and text wrapped in double quotes is kept together as a phrase. Terms are
+/// implicitly ANDed. Returns `None` when the input contains nothing searchable.
+///
+/// # Examples
+///
+/// ```rust
+/// assert_eq!(build_fts_query("groc*"), Some("\"groc\"*".to_string()));
+/// assert_eq!(build_fts_query("\"whole foods\" 2024"), Some("\"whole foods\" \"2024\"".to_string()));
+/// ```
+pub fn build_fts_query(input: &str) -> Option<String> {
+    fn push_term(terms: &mut Vec<String>, text: &str, prefix: bool) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        let quoted = format!("\"{}\"", text.replace('"', "\"\""));
+        terms.push(if prefix { quoted + "*" } else { quoted });
+    }
+
+    let mut terms = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        if c == '"' {
+            let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            push_term(&mut terms, &phrase, false);
+            continue;
+        }
+
+        let mut word = String::from(c);
+        while let Some(&next) = chars.peek() {
+            if next.is_whitespace() || next == '"' {
+                break;
+            }
+            word.push(next);
+            chars.next();
+        }
+
+        let prefix = word.ends_with('*');
+        push_term(&mut terms, word.trim_end_matches('*'), prefix);
+    }
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Searches transaction text using the FTS5 index, best matches first.
+///
+/// Matches the query against description, memo, payee and original description.
+/// Results are ranked with BM25, weighting payee and description above memo and
+/// the raw bank description, and each result carries a snippet of the best
+/// matching column with the matched terms wrapped in `<mark>` tags.
+///
+/// When `tag_ids` is set, only transactions carrying every listed tag match. A
+/// tag filter without search text lists the tagged transactions newest first,
+/// with a zero rank and an empty snippet.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `request` - Search text plus optional account and tag filters and result limit
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<TransactionSearchResult>)` - Matching transactions ordered by relevance
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Query Syntax
+///
+/// - `coffee shop` - Transactions containing both words
+/// - `groc*` - Words starting with "groc"
+/// - `"whole foods"` - The exact phrase
+///
+/// # Examples
+///
+/// ```rust
+/// let request = TransactionSearchRequest {
+///     query: "groc*".to_string(),
+///     account_id: None,
+///     tag_ids: None,
+///     limit: Some(25),
+/// };
+/// for result in search_transactions(&pool, &request).await? {
+///     println!("{:.2}: {}", result.rank, result.snippet);
+/// }
+/// ```
+pub async fn search_transactions(
+    pool: &Pool<Sqlite>,
+    request: &TransactionSearchRequest,
+) -> Result<Vec<TransactionSearchResult>, sqlx::Error> {
+    let match_query = build_fts_query(&request.query);
+    let tag_filter = tag_filter_json(request.tag_ids.as_deref());
+    let limit = request.limit.unwrap_or(50);
+
+    let rows = match (match_query, &tag_filter) {
+        (Some(match_query), _) => {
+            sqlx::query(
+                r#"
+                    SELECT
+                        t.id, t.account_id, t.date, t.amount, t.description, t.category_id, t.pending, t.transaction_type, t.created_at, t.reconciled, t.import_id, t.source, t.payee, t.original_description, t.memo, t.spending_kind,
+                        (SELECT group_concat(tag_id) FROM transaction_tags WHERE transaction_id = t.id) AS tag_ids,
+                        bm25(transactions_fts, 2.0, 1.0, 3.0, 0.5) AS rank,
+                        snippet(transactions_fts, -1, '<mark>', '</mark>', '…', 12) AS snippet
+                    FROM transactions_fts
+                    JOIN transactions t ON t.id = transactions_fts.rowid
+                    WHERE transactions_fts MATCH ?
+                      AND t.deleted_at IS NULL
+                      AND (? IS NULL OR t.account_id = ?)
+                      AND (? IS NULL OR t.id IN (
+                          SELECT transaction_id FROM transaction_tags
+                          WHERE tag_id IN (SELECT value FROM json_each(?))
+                          GROUP BY transaction_id
+                          HAVING COUNT(*) = json_array_length(?)
+                      ))
+                    ORDER BY rank, t.date DESC, t.id DESC
+                    LIMIT ?
+                "#,
+            )
+            .bind(match_query)
+            .bind(request.account_id)
+            .bind(request.account_id)
+            .bind(&tag_filter)
+            .bind(&tag_filter)
+            .bind(&tag_filter)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?
+        }
+        (None, Some(tag_filter)) => {
+            sqlx::query(
+                r#"
+                    SELECT
+                        t.id, t.account_id, t.date, t.amount, t.description, t.category_id, t.pending, t.transaction_type, t.created_at, t.reconciled, t.import_id, t.source, t.payee, t.original_description, t.memo, t.spending_kind,
+                        (SELECT group_concat(tag_id) FROM transaction_tags WHERE transaction_id = t.id) AS tag_ids,
+                        0.0 AS rank,
+                        '' AS snippet
+                    FROM transactions t
+                    WHERE t.deleted_at IS NULL
+                      AND (? IS NULL OR t.account_id = ?)
+                      AND t.id IN (
+                          SELECT transaction_id FROM transaction_tags
+                          WHERE tag_id IN (SELECT value FROM json_each(?))
+                          GROUP BY transaction_id
+                          HAVING COUNT(*) = json_array_length(?)
+                      )
+                    ORDER BY t.date DESC, t.id DESC
+                    LIMIT ?
+                "#,
+            )
+            .bind(request.account_id)
+            .bind(request.account_id)
+            .bind(tag_filter)
+            .bind(tag_filter)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?
+        }
+        (None, None) => return Ok(Vec::new()),
+    };
+
+    rows.iter()
+        .map(|row| {
+            Ok(TransactionSearchResult {
+                transaction: transaction_from_row(row)?,
+                rank: row.try_get("rank")?,
+                snippet: row.try_get("snippet")?,
+            })
+        })
+        .collect()
+}
+
+/// Creates a new tag.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `request` - Tag creation request containing the tag name
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The auto-generated ID of the newly inserted tag
+/// - `Err(DatabaseError)` - Database operation error, including a UNIQUE violation
+///   if a tag with the same name (ignoring case) already exists
+///
+/// # Examples
+///
+/// ```rust
+/// let request = CreateTagRequest { name: "tax-deductible".to_string() };
+/// let tag_id = insert_tag(&pool, &request).await?;
+/// ```
+pub async fn insert_tag(
+    pool: &Pool<Sqlite>,
+    request: &CreateTagRequest,
+) -> Result<i64, DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Add tag", ChangeSource::Manual);
+
+    let result = sqlx::query("INSERT INTO tags (name) VALUES (?)")
+        .bind(request.name.trim())
+        .execute(&mut *tx)
+        .await?;
+
+    let tag_id = result.last_insert_rowid();
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Tag,
+        tag_id,
+        ChangeAction::Create,
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(tag_id)
+}
+
+/// Retrieves all tags ordered alphabetically by name.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Tag>)` - All tags, ordered case-insensitively by name
+/// - `Err(sqlx::Error)` - Database query error
+pub async fn get_tags(pool: &Pool<Sqlite>) -> Result<Vec<Tag>, sqlx::Error> {
+    let tags = sqlx::query_as::<_, (i64, String, DateTime<Utc>)>(
+        "SELECT id, name, created_at FROM tags ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let result = tags
+        .into_iter()
+        .map(|(id, name, created_at)| Tag {
+            id,
+            name,
+            created_at,
+        })
+        .collect();
+
+    Ok(result)
+}
+
+/// Renames an existing tag.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `tag_id` - The ID of the tag to rename
+/// * `request` - Tag request containing the new name
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Tag renamed, or no tag with the given ID exists
+/// - `Err(DatabaseError)` - Database operation error, including a UNIQUE violation
+///   if another tag already uses the name
+pub async fn update_tag(
+    pool: &Pool<Sqlite>,
+    tag_id: i64,
+    request: &CreateTagRequest,
+) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Update tag", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Tag, tag_id).await?;
+
+    sqlx::query("UPDATE tags SET name = ? WHERE id = ?")
+        .bind(request.name.trim())
+        .bind(tag_id)
+        .execute(&mut *tx)
+        .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Tag,
+        tag_id,
+        ChangeAction::Update,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Deletes a tag and removes it from every transaction that carried it.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `tag_id` - The ID of the tag to delete
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Tag deleted, or no tag with the given ID exists
+/// - `Err(DatabaseError)` - Database operation error
+///
+/// # Database Behavior
+///
+/// - The tag's `transaction_tags` rows are deleted in the same transaction
+/// - The tagged transactions themselves are left untouched
+pub async fn delete_tag(pool: &Pool<Sqlite>, tag_id: i64) -> Result<(), DatabaseError> {
+    let mut tx = pool.begin().await?;
+    let mut change = ChangeContext::operation("Delete tag", ChangeSource::Manual);
+    let before = snapshot(&mut tx, EntityType::Tag, tag_id).await?;
+
+    sqlx::query("DELETE FROM transaction_tags WHERE tag_id = ?")
+        .bind(tag_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(tag_id)
+        .execute(&mut *tx)
+        .await?;
+
+    record_change(
+        &mut tx,
+        &mut change,
+        EntityType::Tag,
+        tag_id,
+        ChangeAction::Delete,
+        before,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Applies a tag to many transactions in a single database transaction.
+///
+/// Transactions that already carry the tag and IDs that don't match an existing
+/// transaction are skipped. Shorthand for [`bulk_update_transactions`] with
+/// [`BulkChange::AddTag`].
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `transaction_ids` - The transactions to tag
+/// * `tag_id` - The tag to apply
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions that newly received the tag
+/// - `Err(DatabaseError)` - Database operation error
+///
+/// # Examples
+///
+/// ```rust
+/// let tagged = tag_transactions(&pool, &[101, 102, 103], vacation_tag_id).await?;
+/// ```
+pub async fn tag_transactions(
+    pool: &Pool<Sqlite>,
+    transaction_ids: &[i64],
+    tag_id: i64,
+) -> Result<u64, DatabaseError> {
+    bulk_update_transactions(
+        pool,
+        &BulkTarget::Ids(transaction_ids.to_vec()),
+        &BulkChange::AddTag { tag_id },
+    )
+    .await
+}
+
+/// Removes a tag from many transactions in a single database transaction.
+///
+/// Shorthand for [`bulk_update_transactions`] with [`BulkChange::RemoveTag`].
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `transaction_ids` - The transactions to untag
+/// * `tag_id` - The tag to remove
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions the tag was removed from
+/// - `Err(DatabaseError)` - Database operation error
+pub async fn untag_transactions(
+    pool: &Pool<Sqlite>,
+    transaction_ids: &[i64],
+    tag_id: i64,
+) -> Result<u64, DatabaseError> {
+    bulk_update_transactions(
+        pool,
+        &BulkTarget::Ids(transaction_ids.to_vec()),
+        &BulkChange::RemoveTag { tag_id },
+    )
+    .await
+}
+
+/// Guesses a MIME type from a file name's extension.
+///
+/// Covers the receipt and document formats people typically attach; anything
+/// else is stored as `application/octet-stream`.
+pub fn guess_mime_type(file_name: &str) -> &'static str {
+    let extension = std::path::Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("pdf") => "application/pdf",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("heic") => "image/heic",
+        Some("txt") => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Attaches a file to a transaction.
+///
+/// Stores the file content alongside its name, MIME type, size and SHA-256
+/// checksum. When the request has no MIME type one is guessed from the file
+/// name's extension.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `request` - Target transaction, file name, optional MIME type and content
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The auto-generated ID of the new attachment
+/// - `Err(DatabaseError)` - Database operation error, including a foreign key
+///   violation if the transaction doesn't exist
+///
+/// # Examples
+///
+/// ```rust
+/// let request = CreateAttachmentRequest {
+///     transaction_id: 123,
+///     file_name: "receipt.pdf".to_string(),
+///     mime_type: None,
+///     data: std::fs::read("receipt.pdf")?,
+/// };
+/// let attachment_id = insert_attachment(&pool, &request).await?;
+/// ```
+pub async fn insert_attachment(
+    pool: &Pool<Sqlite>,
+    request: &CreateAttachmentRequest,
+) -> Result<i64, DatabaseError> {
+    let mime_type = request
+        .mime_type
+        .clone()
+        .unwrap_or_else(|| guess_mime_type(&request.file_name).to_string());
+    let checksum = format!("{:x}", Sha256::digest(&request.data));
+
+    let result = sqlx::query(
+        r#"INSERT INTO attachments (transaction_id, file_name, mime_type, size_bytes, sha256, data)
+           VALUES (?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(request.transaction_id)
+    .bind(&request.file_name)
+    .bind(mime_type)
+    .bind(request.data.len() as i64)
+    .bind(checksum)
+    .bind(&request.data)
+    .execute(pool)
+    .await?;
+
+    // Attachments aren't in the change log, so they're noted for events here
+    let id = result.last_insert_rowid();
+    events::note(ChangedRecord::Attachment, ChangeAction::Create, id);
+
+    Ok(id)
+}
+
+/// Lists a transaction's attachments without loading their content.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `transaction_id` - The transaction whose attachments to list
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Attachment>)` - Attachment metadata in the order they were added
+/// - `Err(sqlx::Error)` - Database query error
+pub async fn get_attachments(
+    pool: &Pool<Sqlite>,
+    transaction_id: i64,
+) -> Result<Vec<Attachment>, sqlx::Error> {
+    let attachments = sqlx::query_as::<_, (i64, i64, String, String, i64, String, DateTime<Utc>)>(
+        "SELECT id, transaction_id, file_name, mime_type, size_bytes, sha256, created_at FROM attachments WHERE transaction_id = ? ORDER BY id",
+    )
+    .bind(transaction_id)
+    .fetch_all(pool)
+    .await?;
+
+    let result = attachments
+        .into_iter()
+        .map(
+            |(id, transaction_id, file_name, mime_type, size_bytes, sha256, created_at)| {
+                Attachment {
+                    id,
+                    transaction_id,
+                    file_name,
+                    mime_type,
+                    size_bytes,
+                    sha256,
+                    created_at,
+                }
+            },
+        )
+        .collect();
+
+    Ok(result)
+}
+
+/// Loads the content of a single attachment.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `attachment_id` - The ID of the attachment to load
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Some(Vec<u8>))` - The stored file content
+/// - `Ok(None)` - No attachment found with the given ID
+/// - `Err(sqlx::Error)` - Database query error
+pub async fn get_attachment_data(
+    pool: &Pool<Sqlite>,
+    attachment_id: i64,
+) -> Result<Option<Vec<u8>>, sqlx::Error> {
+    sqlx::query_scalar::<_, Vec<u8>>("SELECT data FROM attachments WHERE id = ?")
+        .bind(attachment_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Removes an attachment.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `attachment_id` - The ID of the attachment to remove
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Attachment removed, or no attachment with the given ID exists
+/// - `Err(DatabaseError)` - Database operation error
+pub async fn delete_attachment(
+    pool: &Pool<Sqlite>,
+    attachment_id: i64,
+) -> Result<(), DatabaseError> {
+    let result = sqlx::query("DELETE FROM attachments WHERE id = ?")
+        .bind(attachment_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() > 0 {
+        events::note(
+            ChangedRecord::Attachment,
+            ChangeAction::Delete,
+            attachment_id,
+        );
+    }
+
+    Ok(())
+}
+
+/// Captures a record as JSON for the change log, or `None` if it doesn't exist.
+///
+/// The snapshot holds every column of the entity's table under its column name,
+/// so it keeps working as columns are added, except for derived columns. Transactions also carry their
+/// `tag_ids` and tags their `transaction_ids`, since those live in the join table.
+pub(crate) async fn snapshot(
+    conn: &mut SqliteConnection,
+    entity: EntityType,
+    id: i64,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    let table = entity.table();
+
+    let columns = sqlx::query_scalar::<_, String>("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    let fields = columns
+        .iter()
+        .filter(|column| !entity.derived_columns().contains(&column.as_str()))
+        .map(|column| format!("'{column}', \"{column}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let related = match entity {
+        EntityType::Transaction => {
+            ", 'tag_ids', (SELECT json_group_array(tag_id) FROM (SELECT tag_id FROM transaction_tags WHERE transaction_id = ? ORDER BY tag_id))"
+        }
+        EntityType::Tag => {
+            ", 'transaction_ids', (SELECT json_group_array(transaction_id) FROM (SELECT transaction_id FROM transaction_tags WHERE tag_id = ? ORDER BY transaction_id))"
+        }
+        EntityType::Account | EntityType::Category | EntityType::Transfer => "",
+    };
+
+    let sql = format!("SELECT json_object({fields}{related}) FROM {table} WHERE id = ?");
+    let mut query = sqlx::query_scalar::<_, String>(&sql);
+    if !related.is_empty() {
+        query = query.bind(id);
+    }
+
+    let json = query.bind(id).fetch_optional(&mut *conn).await?;
+
+    json.map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+/// Snapshots several records of the same type, keyed by ID.
+pub(crate) async fn snapshot_all(
+    conn: &mut SqliteConnection,
+    entity: EntityType,
+    ids: &[i64],
+) -> Result<Vec<(i64, Option<serde_json::Value>)>, sqlx::Error> {
+    let mut snapshots = Vec::with_capacity(ids.len());
+    for &id in ids {
+        snapshots.push((id, snapshot(conn, entity, id).await?));
+    }
+    Ok(snapshots)
+}
+
+/// How a change is attributed in the log: its source and the operation it
+/// belongs to on the undo stack.
+pub(crate) struct ChangeContext {
+    source: ChangeSource,
+    label: Option<&'static str>,
+    operation_id: Option<i64>,
+}
+
+impl ChangeContext {
+    /// Changes that are undone together, under `label`.
+    pub(crate) fn operation(label: &'static str, source: ChangeSource) -> Self {
+        ChangeContext {
+            source,
+            label: Some(label),
+            operation_id: None,
+        }
+    }
+
+    /// Changes that are logged but never pushed onto the undo stack, such as
+    /// the effects of undo and redo themselves.
+    fn untracked(source: ChangeSource) -> Self {
+        ChangeContext {
+            source,
+            label: None,
+            operation_id: None,
+        }
+    }
+}
+
+/// Appends a change log entry comparing `before` with the record's current state.
+///
+/// Must run on the same connection, inside the same database transaction, as
+/// the change itself so the log and the data can never disagree. Nothing is
+/// recorded when the record didn't exist before or after, or when it is
+/// unchanged, such as an update of a missing ID or an archive of an archived row.
+///
+/// The first entry of a tracked context opens its operation, which also drops
+/// everything on the redo stack, just like an editor does after a new edit.
+pub(crate) async fn record_change(
+    conn: &mut SqliteConnection,
+    change: &mut ChangeContext,
+    entity: EntityType,
+    id: i64,
+    action: ChangeAction,
+    before: Option<serde_json::Value>,
+) -> Result<(), sqlx::Error> {
+    let after = snapshot(conn, entity, id).await?;
+
+    if before == after {
+        return Ok(());
+    }
+
+    if let Some(label) = change.label
+        && change.operation_id.is_none()
+    {
+        sqlx::query("DELETE FROM operations WHERE undone = TRUE")
+            .execute(&mut *conn)
+            .await?;
+
+        let result = sqlx::query("INSERT INTO operations (label) VALUES (?)")
+            .bind(label)
+            .execute(&mut *conn)
+            .await?;
+        change.operation_id = Some(result.last_insert_rowid());
+    }
+
+    sqlx::query(
+        r#"INSERT INTO change_log (entity_type, entity_id, action, source, before_json, after_json, operation_id)
+           VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(entity.as_str())
+    .bind(id)
+    .bind(action.as_str())
+    .bind(change.source.as_str())
+    .bind(before.map(|value| value.to_string()))
+    .bind(after.map(|value| value.to_string()))
+    .bind(change.operation_id)
+    .execute(&mut *conn)
+    .await?;
+    events::note_change(entity, action, id);
+
+    Ok(())
+}
+
+/// Records one change log entry per snapshot taken with [`snapshot_all`].
+pub(crate) async fn record_changes(
+    conn: &mut SqliteConnection,
+    change: &mut ChangeContext,
+    entity: EntityType,
+    action: ChangeAction,
+    before: Vec<(i64, Option<serde_json::Value>)>,
+) -> Result<(), sqlx::Error> {
+    for (id, before) in before {
+        record_change(conn, change, entity, id, action, before).await?;
+    }
+    Ok(())
+}
+
+/// Empties the undo and redo stacks, keeping the change log itself.
+///
+/// Used before an import, whose records may reuse the IDs of records that an
+/// older operation would otherwise put back over them. Operation IDs keep
+/// counting up, so the import's own operation never picks up the log entries
+/// of the operations cleared here.
+pub(crate) async fn clear_undo_history(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM operations")
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Retrieves the change history of a single record, oldest change first.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `entity` - The kind of record, such as a transaction or an account
+/// * `id` - The record's ID
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<ChangeLogEntry>)` - Every recorded change with before and after snapshots
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// for entry in get_change_history(&pool, EntityType::Transaction, 123).await? {
+///     println!("{} {} by {}", entry.changed_at, entry.action, entry.source);
+/// }
+/// ```
+pub async fn get_change_history(
+    pool: &Pool<Sqlite>,
+    entity: EntityType,
+    id: i64,
+) -> Result<Vec<ChangeLogEntry>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"SELECT id, entity_type, entity_id, action, source, changed_at, before_json, after_json
+           FROM change_log
+           WHERE entity_type = ? AND entity_id = ?
+           ORDER BY id"#,
+    )
+    .bind(entity.as_str())
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    let parse = |json: Option<String>| -> Result<Option<serde_json::Value>, sqlx::Error> {
+        json.map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+    };
+
+    rows.iter()
+        .map(|row| {
+            Ok(ChangeLogEntry {
+                id: row.get("id"),
+                entity_type: row.get("entity_type"),
+                entity_id: row.get("entity_id"),
+                action: row.get("action"),
+                source: row.get("source"),
+                changed_at: row.get("changed_at"),
+                before: parse(row.get("before_json"))?,
+                after: parse(row.get("after_json"))?,
+            })
+        })
+        .collect()
+}
+
+/// Puts a record back into the state captured by a snapshot.
+///
+/// A missing snapshot means the record didn't exist, so it is deleted. Otherwise
+/// the row is upserted column by column, skipping keys that are no longer
+/// columns, and the record's tag links are reset to those in the snapshot.
+async fn apply_snapshot(
+    conn: &mut SqliteConnection,
+    entity: EntityType,
+    id: i64,
+    snapshot: Option<&serde_json::Value>,
+) -> Result<(), sqlx::Error> {
+    let table = entity.table();
+
+    let Some(fields) = snapshot.and_then(|snapshot| snapshot.as_object()) else {
+        sqlx::query(&format!("DELETE FROM {table} WHERE id = ?"))
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        return Ok(());
+    };
+
+    let columns = sqlx::query_scalar::<_, String>("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    let columns: Vec<&String> = columns
+        .iter()
+        .filter(|column| fields.contains_key(*column))
+        .collect();
+
+    let names = columns
+        .iter()
+        .map(|column| format!("\"{column}\""))
+        .collect::<Vec<_>>();
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let assignments = names
+        .iter()
+        .map(|name| format!("{name} = excluded.{name}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "INSERT INTO {table} ({}) VALUES ({placeholders}) ON CONFLICT(id) DO UPDATE SET {assignments}",
+        names.join(", ")
+    );
+
+    let mut query = sqlx::query(&sql);
+    for column in &columns {
+        query = match &fields[column.as_str()] {
+            serde_json::Value::Null => query.bind(None::<String>),
+            serde_json::Value::Bool(value) => query.bind(*value),
+            serde_json::Value::Number(value) => match value.as_i64() {
+                Some(value) => query.bind(value),
+                None => query.bind(value.as_f64()),
+            },
+            serde_json::Value::String(value) => query.bind(value.clone()),
+            value => query.bind(value.to_string()),
+        };
+    }
+    query.execute(&mut *conn).await?;
+
+    let related_ids = |key: &str| -> Vec<i64> {
+        fields
+            .get(key)
+            .and_then(|ids| ids.as_array())
+            .map(|ids| ids.iter().filter_map(|id| id.as_i64()).collect())
+            .unwrap_or_default()
+    };
+
+    match entity {
+        EntityType::Transaction => {
+            set_transaction_tags(conn, id, &related_ids("tag_ids")).await?;
+        }
+        EntityType::Tag => {
+            sqlx::query("DELETE FROM transaction_tags WHERE tag_id = ?")
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query(
+                r#"INSERT INTO transaction_tags (transaction_id, tag_id)
+                   SELECT id, ? FROM transactions WHERE id IN (SELECT value FROM json_each(?))"#,
+            )
+            .bind(id)
+            .bind(ids_to_json(&related_ids("transaction_ids")))
+            .execute(&mut *conn)
+            .await?;
+        }
+        EntityType::Account | EntityType::Category | EntityType::Transfer => {}
+    }
+
+    Ok(())
+}
+
+/// Loads up to `count` operations from the top of the undo or redo stack.
+async fn stack_operations(
+    conn: &mut SqliteConnection,
+    undone: bool,
+    count: Option<u32>,
+) -> Result<Vec<Operation>, sqlx::Error> {
+    let order = if undone { "ASC" } else { "DESC" };
+    let rows = sqlx::query(&format!(
+        "SELECT id, label, created_at FROM operations WHERE undone = ? ORDER BY id {order} LIMIT ?"
+    ))
+    .bind(undone)
+    .bind(count.map_or(-1, i64::from))
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| Operation {
+            id: row.get("id"),
+            label: row.get("label"),
+            created_at: row.get("created_at"),
+        })
+        .collect())
+}
+
+/// Moves operations between the undo and redo stacks, replaying their snapshots.
+async fn replay_operations(
+    pool: &Pool<Sqlite>,
+    count: u32,
+    undo: bool,
+) -> Result<Vec<Operation>, DatabaseError> {
+    let mut tx = pool.begin().await?;
+    // Records are put back one at a time, so a reference to a row restored
+    // later in the same replay is only checked on commit
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+    let mut change = ChangeContext::untracked(ChangeSource::Manual);
+    let (action, entry_order) = if undo {
+        (ChangeAction::Undo, "DESC")
+    } else {
+        (ChangeAction::Redo, "ASC")
+    };
+
+    let operations = stack_operations(&mut tx, !undo, Some(count)).await?;
+
+    for operation in &operations {
+        let entries = sqlx::query(&format!(
+            r#"SELECT entity_type, entity_id, before_json, after_json FROM change_log
+               WHERE operation_id = ? ORDER BY id {entry_order}"#
+        ))
+        .bind(operation.id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for entry in &entries {
+            let entity_type: String = entry.get("entity_type");
+            let entity = EntityType::parse(&entity_type).ok_or_else(|| {
+                sqlx::Error::Decode(format!("unknown entity type: {entity_type}").into())
+            })?;
+            let id: i64 = entry.get("entity_id");
+            let target: Option<String> = entry.get(if undo { "before_json" } else { "after_json" });
+            let target = target
+                .map(|json| serde_json::from_str::<serde_json::Value>(&json))
+                .transpose()
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+            let before = snapshot(&mut tx, entity, id).await?;
+            apply_snapshot(&mut tx, entity, id, target.as_ref()).await?;
+            record_change(&mut tx, &mut change, entity, id, action, before).await?;
+        }
+
+        sqlx::query("UPDATE operations SET undone = ? WHERE id = ?")
+            .bind(undo)
+            .bind(operation.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(operations)
+}
+
+/// Reverses the last `count` operations in a single database transaction.
+///
+/// Every add, update, archive and delete of accounts, categories, transactions,
+/// tags and transfers is one operation; bulk tagging counts as one operation however many
+/// transactions it touched. Undoing puts each affected record back to its logged
+/// state from before the operation. If any step fails nothing is undone.
+/// Attachments are not part of the undo history.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `count` - How many operations to undo, newest first
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Operation>)` - The operations undone, fewer than `count` if the stack ran out
+/// - `Err(DatabaseError)` - Database error; no changes were made
+///
+/// # Examples
+///
+/// ```rust
+/// delete_transaction(&pool, 123).await?;
+/// undo(&pool, 1).await?; // transaction 123 is back
+/// ```
+pub async fn undo(pool: &Pool<Sqlite>, count: u32) -> Result<Vec<Operation>, DatabaseError> {
+    replay_operations(pool, count, true).await
+}
+
+/// Re-applies the last `count` undone operations in a single database transaction.
+///
+/// The redo stack is cleared as soon as a new change is made.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `count` - How many operations to redo, most recently undone first
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Operation>)` - The operations redone
+/// - `Err(DatabaseError)` - Database error; no changes were made
+pub async fn redo(pool: &Pool<Sqlite>, count: u32) -> Result<Vec<Operation>, DatabaseError> {
+    replay_operations(pool, count, false).await
+}
+
+/// Lists the operations that can currently be undone and redone.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(UndoStack)` - Both stacks, top of stack first
+/// - `Err(sqlx::Error)` - Database query error
+pub async fn get_undo_stack(pool: &Pool<Sqlite>) -> Result<UndoStack, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+
+    Ok(UndoStack {
+        undo: stack_operations(&mut conn, false, None).await?,
+        redo: stack_operations(&mut conn, true, None).await?,
+    })
+}
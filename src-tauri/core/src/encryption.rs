@@ -0,0 +1,286 @@
+use crate::backup;
+use crate::database;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+/// First bytes of every unencrypted SQLite database; SQLCipher encrypts them too.
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// SQLite's "file is not a database" result code, which is also what SQLCipher
+/// returns when the key is wrong.
+const SQLITE_NOTADB: &str = "26";
+
+/// Errors from unlocking an encrypted database or changing its encryption.
+#[derive(Debug)]
+pub enum EncryptionError {
+    /// The database couldn't be read or written
+    Sqlx(sqlx::Error),
+    /// The database file or its encrypted copy couldn't be accessed
+    Io(io::Error),
+    /// The passphrase doesn't decrypt the database
+    WrongPassphrase,
+    /// The request doesn't fit the database, with an explanation
+    Invalid(String),
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionError::Sqlx(e) => write!(f, "{e}"),
+            EncryptionError::Io(e) => write!(f, "{e}"),
+            EncryptionError::WrongPassphrase => write!(f, "wrong passphrase"),
+            EncryptionError::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EncryptionError::Sqlx(e) => Some(e),
+            EncryptionError::Io(e) => Some(e),
+            EncryptionError::WrongPassphrase | EncryptionError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for EncryptionError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::Database(ref db) if db.code().as_deref() == Some(SQLITE_NOTADB) => {
+                EncryptionError::WrongPassphrase
+            }
+            e => EncryptionError::Sqlx(e),
+        }
+    }
+}
+
+impl From<io::Error> for EncryptionError {
+    fn from(e: io::Error) -> Self {
+        EncryptionError::Io(e)
+    }
+}
+
+/// Whether the database is encrypted and whether commands can use it yet.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DatabaseStatus {
+    pub encrypted: bool,
+    /// No pool is open until an encrypted database is unlocked
+    pub locked: bool,
+}
+
+/// Checks whether a database file is encrypted, without a passphrase.
+///
+/// SQLCipher encrypts the whole file, header included, so anything that
+/// doesn't start with SQLite's header is treated as encrypted. A missing or
+/// empty file is a new database and isn't.
+pub fn is_encrypted(path: &Path) -> io::Result<bool> {
+    let mut header = [0; SQLITE_HEADER.len()];
+    let read = File::open(path).and_then(|mut file| file.read_exact(&mut header));
+
+    match read {
+        Ok(()) => Ok(&header != SQLITE_HEADER),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::UnexpectedEof
+            ) =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Opens the database, decrypting it with `passphrase` if one is given.
+///
+/// # Arguments
+///
+/// * `database` - Path of the database file
+/// * `passphrase` - Passphrase of an encrypted database, `None` otherwise
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Pool<Sqlite>)` - A pool that can read the database
+/// - `Err(EncryptionError::WrongPassphrase)` - The passphrase is wrong, or the
+///   database is encrypted and no passphrase was given
+/// - `Err(EncryptionError)` - The database couldn't be opened
+///
+/// # Examples
+///
+/// ```rust
+/// let passphrase = is_encrypted(path)?.then_some("correct horse");
+/// let pool = unlock(path, passphrase).await?;
+/// ```
+pub async fn unlock(
+    database: &Path,
+    passphrase: Option<&str>,
+) -> Result<Pool<Sqlite>, EncryptionError> {
+    Ok(database::create_connection(database, passphrase).await?)
+}
+
+/// Encrypts an unencrypted database with a passphrase.
+///
+/// The database is exported to an encrypted copy which then replaces it, so
+/// the pool is closed and the app must unlock the database again, typically
+/// after restarting, before it can use it. Existing backups stay unencrypted.
+///
+/// # Arguments
+///
+/// * `pool` - Pool of the database being encrypted; closed on success
+/// * `database` - Path of the database file
+/// * `passphrase` - Passphrase to encrypt with; can't be empty
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - The database file is now encrypted
+/// - `Err(EncryptionError::Invalid)` - The database is already encrypted or the
+///   passphrase is empty; nothing was changed
+/// - `Err(EncryptionError)` - The encrypted copy couldn't be written
+pub async fn enable_encryption(
+    pool: &Pool<Sqlite>,
+    database: &Path,
+    passphrase: &str,
+) -> Result<(), EncryptionError> {
+    if is_encrypted(database)? {
+        return Err(EncryptionError::Invalid(
+            "the database is already encrypted".to_string(),
+        ));
+    }
+    check_passphrase(passphrase)?;
+
+    export(pool, database, passphrase).await
+}
+
+/// Re-encrypts an encrypted database with a new passphrase.
+///
+/// Like [`enable_encryption`], this replaces the database file and closes the
+/// pool. Backups taken before the change still need the old passphrase.
+///
+/// # Arguments
+///
+/// * `pool` - Pool of the unlocked database; closed on success
+/// * `database` - Path of the database file
+/// * `current` - The passphrase the database is encrypted with now
+/// * `new` - Passphrase to encrypt with from now on; can't be empty
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - The database file is encrypted with `new`
+/// - `Err(EncryptionError::WrongPassphrase)` - `current` is wrong
+/// - `Err(EncryptionError::Invalid)` - The database isn't encrypted or `new`
+///   is empty; nothing was changed
+/// - `Err(EncryptionError)` - The re-encrypted copy couldn't be written
+pub async fn change_passphrase(
+    pool: &Pool<Sqlite>,
+    database: &Path,
+    current: &str,
+    new: &str,
+) -> Result<(), EncryptionError> {
+    check_current_passphrase(database, current).await?;
+    check_passphrase(new)?;
+
+    export(pool, database, new).await
+}
+
+/// Decrypts an encrypted database, so it opens without a passphrase.
+///
+/// Like [`enable_encryption`], this replaces the database file and closes the
+/// pool. Encrypted backups stay encrypted.
+///
+/// # Arguments
+///
+/// * `pool` - Pool of the unlocked database; closed on success
+/// * `database` - Path of the database file
+/// * `current` - The passphrase the database is encrypted with, confirming the
+///   request
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - The database file is no longer encrypted
+/// - `Err(EncryptionError::WrongPassphrase)` - `current` is wrong
+/// - `Err(EncryptionError::Invalid)` - The database isn't encrypted
+/// - `Err(EncryptionError)` - The decrypted copy couldn't be written
+pub async fn disable_encryption(
+    pool: &Pool<Sqlite>,
+    database: &Path,
+    current: &str,
+) -> Result<(), EncryptionError> {
+    check_current_passphrase(database, current).await?;
+
+    // SQLCipher writes an attached database with an empty key unencrypted
+    export(pool, database, "").await
+}
+
+fn check_passphrase(passphrase: &str) -> Result<(), EncryptionError> {
+    if passphrase.is_empty() {
+        return Err(EncryptionError::Invalid(
+            "the passphrase can't be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Confirms the database is encrypted with `passphrase` by opening it separately.
+async fn check_current_passphrase(
+    database: &Path,
+    passphrase: &str,
+) -> Result<(), EncryptionError> {
+    if !is_encrypted(database)? {
+        return Err(EncryptionError::Invalid(
+            "the database isn't encrypted".to_string(),
+        ));
+    }
+    unlock(database, Some(passphrase)).await?.close().await;
+    Ok(())
+}
+
+/// Copies the database into a file encrypted with `key` and swaps it in.
+///
+/// `sqlcipher_export` copies the schema and every row into an attached
+/// database, which SQLCipher encrypts with the key it was attached with. The
+/// copy is only moved over the database once it is complete.
+async fn export(pool: &Pool<Sqlite>, database: &Path, key: &str) -> Result<(), EncryptionError> {
+    let staging = database.with_extension("db-encrypt");
+    // A leftover copy would be attached as is rather than overwritten
+    match fs::remove_file(&staging) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    let target = staging.to_str().ok_or_else(|| {
+        EncryptionError::Invalid(format!("unsupported path {}", staging.display()))
+    })?;
+
+    let exported = async {
+        let mut conn = pool.acquire().await?;
+        sqlx::query("ATTACH DATABASE ? AS encrypted KEY ?")
+            .bind(target)
+            .bind(key)
+            .execute(&mut *conn)
+            .await?;
+        let result = sqlx::query("SELECT sqlcipher_export('encrypted')")
+            .execute(&mut *conn)
+            .await;
+        sqlx::query("DETACH DATABASE encrypted")
+            .execute(&mut *conn)
+            .await?;
+        result.map(|_| ())
+    }
+    .await;
+
+    if let Err(e) = exported {
+        let _ = fs::remove_file(&staging);
+        return Err(EncryptionError::Sqlx(e));
+    }
+    backup::replace_database(pool, database, &staging).await?;
+
+    Ok(())
+}
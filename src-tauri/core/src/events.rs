@@ -0,0 +1,126 @@
+use crate::database::{ChangeAction, EntityType};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+/// Kind of record a change event is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangedRecord {
+    Account,
+    Category,
+    Transaction,
+    Tag,
+    Attachment,
+}
+
+impl ChangedRecord {
+    /// Name of the event the frontend listens to for this kind of record.
+    pub fn event(self) -> &'static str {
+        match self {
+            ChangedRecord::Account => "account-changed",
+            ChangedRecord::Category => "category-changed",
+            ChangedRecord::Transaction => "transaction-changed",
+            ChangedRecord::Tag => "tag-changed",
+            ChangedRecord::Attachment => "attachment-changed",
+        }
+    }
+
+    /// The record kind of a change log entity; transfers are announced
+    /// through their transactions.
+    fn of(entity: EntityType) -> Option<Self> {
+        match entity {
+            EntityType::Account => Some(ChangedRecord::Account),
+            EntityType::Category => Some(ChangedRecord::Category),
+            EntityType::Transaction => Some(ChangedRecord::Transaction),
+            EntityType::Tag => Some(ChangedRecord::Tag),
+            EntityType::Transfer => None,
+        }
+    }
+}
+
+/// Records of one kind that changed the same way, sent to the frontend as the
+/// record's `*-changed` event so views can refresh just those records.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub record: ChangedRecord,
+    pub kind: ChangeAction,
+    /// Affected IDs, in the order they changed
+    pub ids: Vec<i64>,
+}
+
+impl ChangeEvent {
+    pub fn new(record: ChangedRecord, kind: ChangeAction, ids: Vec<i64>) -> Self {
+        Self { record, kind, ids }
+    }
+
+    /// Name of the event, such as `transaction-changed`.
+    pub fn name(&self) -> &'static str {
+        self.record.event()
+    }
+}
+
+tokio::task_local! {
+    /// Events of the changes made on this task since [`capture`] started.
+    static CAPTURED: RefCell<Vec<ChangeEvent>>;
+}
+
+/// Runs `change` and lists everything it changed, as one event per kind of
+/// record and kind of change.
+///
+/// Changes are noted as they are written to the change log, so edits,
+/// archives, deletes, bulk changes, merges, purges, imports and undo/redo are
+/// all covered whatever made them. Attachments aren't in the change log and
+/// are noted when they're added or removed. Only changes made on the current
+/// task count, so commands running at the same time don't see each other's
+/// changes.
+///
+/// # Arguments
+///
+/// * `change` - The command's change, such as a `database` function call
+///
+/// # Returns
+///
+/// Returns the output of `change` together with its events, in the order
+/// their first change was made. The events are empty when nothing changed,
+/// and can include changes that were rolled back when `change` failed.
+///
+/// # Examples
+///
+/// ```rust
+/// let (result, events) = capture(database::insert_account(&pool, &request)).await;
+/// for event in events {
+///     println!("{} {:?} {:?}", event.name(), event.kind, event.ids);
+/// }
+/// ```
+pub async fn capture<F: Future>(change: F) -> (F::Output, Vec<ChangeEvent>) {
+    CAPTURED
+        .scope(RefCell::new(Vec::new()), async move {
+            let output = change.await;
+            (output, CAPTURED.with(RefCell::take))
+        })
+        .await
+}
+
+/// Notes a change for the [`capture`] running on this task, if any.
+pub(crate) fn note(record: ChangedRecord, kind: ChangeAction, id: i64) {
+    let _ = CAPTURED.try_with(|events| push(&mut events.borrow_mut(), record, kind, id));
+}
+
+/// Notes a change log entry for the [`capture`] running on this task, if any.
+pub(crate) fn note_change(entity: EntityType, action: ChangeAction, id: i64) {
+    if let Some(record) = ChangedRecord::of(entity) {
+        note(record, action, id);
+    }
+}
+
+/// Adds `id` to the event for `record` and `kind`, starting one if needed.
+fn push(events: &mut Vec<ChangeEvent>, record: ChangedRecord, kind: ChangeAction, id: i64) {
+    match events
+        .iter_mut()
+        .find(|event| event.record == record && event.kind == kind)
+    {
+        Some(event) if event.ids.contains(&id) => {}
+        Some(event) => event.ids.push(id),
+        None => events.push(ChangeEvent::new(record, kind, vec![id])),
+    }
+}
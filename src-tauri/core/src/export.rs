@@ -0,0 +1,1057 @@
+use crate::database::{
+    self, ChangeAction, ChangeContext, ChangeSource, EntityType, SpendingKind, Tag,
+};
+use chrono::{DateTime, Datelike, NaiveDate, SecondsFormat, Timelike, Utc};
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Row, Sqlite, SqliteConnection};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Identifies a Tally export, so other JSON documents are refused on import.
+pub const EXPORT_FORMAT: &str = "tally-export";
+
+/// Version of the export document, raised whenever its shape changes.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Errors from exporting data or importing an export.
+#[derive(Debug)]
+pub enum ExportError {
+    /// The database couldn't be read or written
+    Sqlx(sqlx::Error),
+    /// The export file couldn't be read or written
+    Io(io::Error),
+    /// The export couldn't be encoded
+    Json(serde_json::Error),
+    /// The file isn't an export that can be imported, with an explanation
+    Invalid(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Sqlx(e) => write!(f, "{e}"),
+            ExportError::Io(e) => write!(f, "{e}"),
+            ExportError::Json(e) => write!(f, "{e}"),
+            ExportError::Invalid(message) => write!(f, "invalid export: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Sqlx(e) => Some(e),
+            ExportError::Io(e) => Some(e),
+            ExportError::Json(e) => Some(e),
+            ExportError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for ExportError {
+    fn from(e: sqlx::Error) -> Self {
+        ExportError::Sqlx(e)
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(e: io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(e: serde_json::Error) -> Self {
+        ExportError::Json(e)
+    }
+}
+
+/// A versioned snapshot of the user's data, as written to a JSON export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Export {
+    /// Always [`EXPORT_FORMAT`]
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Hex SHA-256 of the data, checked on import to catch damaged or edited files
+    pub sha256: String,
+    #[serde(flatten)]
+    pub data: ExportData,
+}
+
+/// Every record the user owns, keyed by the same ids as in the database.
+///
+/// Amounts are in cents. Derived values such as category usage are left out
+/// and rebuilt on import, as are attachments, the change log and the undo
+/// history. Budgets and rules don't exist in the schema yet; when they are
+/// added they belong here too, under a new [`EXPORT_VERSION`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportData {
+    pub accounts: Vec<ExportedAccount>,
+    /// Parents are referenced by `parent_category_id`
+    pub categories: Vec<ExportedCategory>,
+    pub tags: Vec<Tag>,
+    /// Includes transactions in the trash, marked by `deleted_at`
+    pub transactions: Vec<ExportedTransaction>,
+    pub transfers: Vec<ExportedTransfer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedAccount {
+    pub id: i64,
+    pub name: String,
+    pub account_type: String,
+    pub institution: Option<String>,
+    pub current_balance: Option<i64>,
+    pub display_order: Option<i64>,
+    pub archived: bool,
+    pub include_in_net_worth: bool,
+    pub account_number_last4: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedCategory {
+    pub id: i64,
+    pub name: String,
+    pub parent_category_id: Option<i64>,
+    pub display_order: Option<i64>,
+    pub archived: bool,
+    pub default_discretionary: Option<bool>,
+    pub default_fixed: Option<bool>,
+    pub is_system_category: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedTransaction {
+    pub id: i64,
+    pub account_id: i64,
+    pub date: NaiveDate,
+    pub amount: i64,
+    pub transaction_type: String,
+    pub description: Option<String>,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub original_description: Option<String>,
+    pub category_id: Option<i64>,
+    pub spending_kind: Option<SpendingKind>,
+    pub tag_ids: Vec<i64>,
+    pub pending: bool,
+    pub cleared: bool,
+    pub reconciled: bool,
+    pub import_id: Option<String>,
+    pub source: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedTransfer {
+    pub id: i64,
+    pub from_transaction_id: i64,
+    pub to_transaction_id: i64,
+    pub transfer_type: Option<String>,
+    pub auto_created: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How many records of each kind an export or import covered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataCounts {
+    pub accounts: usize,
+    pub categories: usize,
+    pub tags: usize,
+    pub transactions: usize,
+    pub transfers: usize,
+}
+
+impl ExportData {
+    pub fn counts(&self) -> DataCounts {
+        DataCounts {
+            accounts: self.accounts.len(),
+            categories: self.categories.len(),
+            tags: self.tags.len(),
+            transactions: self.transactions.len(),
+            transfers: self.transfers.len(),
+        }
+    }
+
+    /// Hex SHA-256 of the data as serialized in an export.
+    pub fn checksum(&self) -> Result<String, ExportError> {
+        Ok(format!("{:x}", Sha256::digest(serde_json::to_vec(self)?)))
+    }
+}
+
+/// File formats data can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// A single versioned JSON document, which can be imported again
+    Json,
+    /// A zip with one CSV file per kind of record, for spreadsheets
+    CsvZip,
+}
+
+/// Reads all of the user's data into an export.
+///
+/// Everything is read in one database transaction, so the export is a
+/// consistent snapshot even while the app keeps writing.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Export)` - The data, stamped with the format version and its checksum
+/// - `Err(ExportError)` - Database read error
+///
+/// # Examples
+///
+/// ```rust
+/// let export = export_data(&pool).await?;
+/// println!("{} transactions", export.data.transactions.len());
+/// ```
+pub async fn export_data(pool: &Pool<Sqlite>) -> Result<Export, ExportError> {
+    let mut tx = pool.begin().await?;
+    let data = read_data(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(Export {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: Utc::now(),
+        sha256: data.checksum()?,
+        data,
+    })
+}
+
+async fn read_data(conn: &mut SqliteConnection) -> Result<ExportData, sqlx::Error> {
+    let accounts = sqlx::query(
+        r#"
+        SELECT id, name, type, institution, current_balance, display_order, archived,
+               include_in_net_worth, account_number_last4, created_at, updated_at
+        FROM accounts
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .iter()
+    .map(|row| ExportedAccount {
+        id: row.get("id"),
+        name: row.get("name"),
+        account_type: row.get("type"),
+        institution: row.get("institution"),
+        current_balance: row.get("current_balance"),
+        display_order: row.get("display_order"),
+        archived: row.get("archived"),
+        include_in_net_worth: row.get("include_in_net_worth"),
+        account_number_last4: row.get("account_number_last4"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+    .collect();
+
+    let categories = sqlx::query(
+        r#"
+        SELECT id, name, parent_category_id, display_order, archived, default_discretionary,
+               default_fixed, is_system_category, created_at
+        FROM categories
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .iter()
+    .map(|row| ExportedCategory {
+        id: row.get("id"),
+        name: row.get("name"),
+        parent_category_id: row.get("parent_category_id"),
+        display_order: row.get("display_order"),
+        archived: row.get("archived"),
+        default_discretionary: row.get("default_discretionary"),
+        default_fixed: row.get("default_fixed"),
+        is_system_category: row.get("is_system_category"),
+        created_at: row.get("created_at"),
+    })
+    .collect();
+
+    let tags = sqlx::query_as::<_, (i64, String, DateTime<Utc>)>(
+        "SELECT id, name, created_at FROM tags ORDER BY id",
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|(id, name, created_at)| Tag {
+        id,
+        name,
+        created_at,
+    })
+    .collect();
+
+    let mut tag_ids: HashMap<i64, Vec<i64>> = HashMap::new();
+    let pairs = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT transaction_id, tag_id FROM transaction_tags ORDER BY transaction_id, tag_id",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (transaction_id, tag_id) in pairs {
+        tag_ids.entry(transaction_id).or_default().push(tag_id);
+    }
+
+    let transactions = sqlx::query(
+        r#"
+        SELECT id, account_id, date, amount, transaction_type, description, payee, memo,
+               original_description, category_id, spending_kind, pending, cleared, reconciled,
+               import_id, source, created_at, deleted_at
+        FROM transactions
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .iter()
+    .map(|row| {
+        let id = row.try_get("id")?;
+        Ok(ExportedTransaction {
+            id,
+            account_id: row.try_get("account_id")?,
+            date: row.try_get("date")?,
+            amount: row.try_get("amount")?,
+            transaction_type: row.try_get("transaction_type")?,
+            description: row.try_get("description")?,
+            payee: row.try_get("payee")?,
+            memo: row.try_get("memo")?,
+            original_description: row.try_get("original_description")?,
+            category_id: row.try_get("category_id")?,
+            spending_kind: row
+                .try_get::<Option<String>, _>("spending_kind")?
+                .as_deref()
+                .and_then(SpendingKind::parse),
+            tag_ids: tag_ids.remove(&id).unwrap_or_default(),
+            pending: row.try_get("pending")?,
+            cleared: row.try_get("cleared")?,
+            reconciled: row.try_get("reconciled")?,
+            import_id: row.try_get("import_id")?,
+            source: row.try_get("source")?,
+            created_at: row.try_get("created_at")?,
+            deleted_at: row.try_get("deleted_at")?,
+        })
+    })
+    .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+    let transfers = sqlx::query(
+        r#"
+        SELECT id, from_transaction_id, to_transaction_id, transfer_type, auto_created, created_at
+        FROM transfers
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .iter()
+    .map(|row| ExportedTransfer {
+        id: row.get("id"),
+        from_transaction_id: row.get("from_transaction_id"),
+        to_transaction_id: row.get("to_transaction_id"),
+        transfer_type: row.get("transfer_type"),
+        auto_created: row.get("auto_created"),
+        created_at: row.get("created_at"),
+    })
+    .collect();
+
+    Ok(ExportData {
+        accounts,
+        categories,
+        tags,
+        transactions,
+        transfers,
+    })
+}
+
+/// Exports all data to a file.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `path` - File to write; an existing file is overwritten
+/// * `format` - JSON document or zip of CSV files
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(DataCounts)` - How many records were exported
+/// - `Err(ExportError)` - The data couldn't be read or the file written
+///
+/// # Examples
+///
+/// ```rust
+/// let counts = export_to_file(&pool, Path::new("tally.json"), ExportFormat::Json).await?;
+/// ```
+pub async fn export_to_file(
+    pool: &Pool<Sqlite>,
+    path: &Path,
+    format: ExportFormat,
+) -> Result<DataCounts, ExportError> {
+    let export = export_data(pool).await?;
+
+    let contents = match format {
+        ExportFormat::Json => serde_json::to_vec_pretty(&export)?,
+        ExportFormat::CsvZip => csv_zip(&export)?,
+    };
+    fs::write(path, contents)?;
+
+    Ok(export.data.counts())
+}
+
+/// Imports a JSON export into an empty database.
+///
+/// The file's format, version and checksum are checked before anything is
+/// written. Records keep their ids, so references between them survive, and
+/// everything is inserted in one database transaction that is only committed
+/// once every reference resolves. Category usage and the search index are
+/// rebuilt as the transactions go in. The import is logged as one operation,
+/// so a single undo reverses it, and the undo and redo history from before it
+/// is cleared.
+///
+/// A database counts as empty when it has no accounts, transactions or tags
+/// and only system categories, which the export's own categories replace.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `path` - JSON file written by [`export_to_file`]
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(DataCounts)` - How many records were imported
+/// - `Err(ExportError::Invalid)` - The file isn't a valid export, references
+///   records it doesn't contain, or the database isn't empty; nothing was changed
+/// - `Err(ExportError)` - The file couldn't be read or a write failed; nothing
+///   was changed
+///
+/// # Examples
+///
+/// ```rust
+/// let counts = import_from_file(&pool, Path::new("tally.json")).await?;
+/// println!("Imported {} transactions", counts.transactions);
+/// ```
+pub async fn import_from_file(pool: &Pool<Sqlite>, path: &Path) -> Result<DataCounts, ExportError> {
+    let export: Export = serde_json::from_slice(&fs::read(path)?)
+        .map_err(|e| ExportError::Invalid(format!("not a Tally export ({e})")))?;
+
+    import_data(pool, &export).await
+}
+
+/// Imports an export into an empty database; see [`import_from_file`].
+pub async fn import_data(pool: &Pool<Sqlite>, export: &Export) -> Result<DataCounts, ExportError> {
+    if export.format != EXPORT_FORMAT {
+        return Err(ExportError::Invalid("not a Tally export".to_string()));
+    }
+    if export.version != EXPORT_VERSION {
+        return Err(ExportError::Invalid(format!(
+            "version {} isn't supported by this version of Tally",
+            export.version
+        )));
+    }
+    if export.data.checksum()? != export.sha256 {
+        return Err(ExportError::Invalid(
+            "checksum mismatch, the file is damaged or was edited".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let existing: i64 = sqlx::query_scalar(
+        r#"
+        SELECT (SELECT COUNT(*) FROM accounts)
+             + (SELECT COUNT(*) FROM transactions)
+             + (SELECT COUNT(*) FROM tags)
+             + (SELECT COUNT(*) FROM categories WHERE NOT is_system_category)
+        "#,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if existing > 0 {
+        return Err(ExportError::Invalid(
+            "the database already has data, import into an empty one".to_string(),
+        ));
+    }
+
+    // References are checked at commit, so records can go in any order
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+
+    // Older operations could put records back over imported ones with the same
+    // ID, so the import starts a new undo history as a single operation
+    database::clear_undo_history(&mut tx).await?;
+    let mut change = ChangeContext::operation("Import data", ChangeSource::Import);
+
+    let seeded = sqlx::query_scalar::<_, i64>("SELECT id FROM categories")
+        .fetch_all(&mut *tx)
+        .await?;
+    let before = database::snapshot_all(&mut tx, EntityType::Category, &seeded).await?;
+    sqlx::query("DELETE FROM categories")
+        .execute(&mut *tx)
+        .await?;
+    database::record_changes(
+        &mut tx,
+        &mut change,
+        EntityType::Category,
+        ChangeAction::Delete,
+        before,
+    )
+    .await?;
+
+    insert_data(&mut tx, &mut change, &export.data).await?;
+
+    // An export without system categories still needs them
+    if let Some(id) = database::insert_system_category(&mut tx).await? {
+        created(&mut tx, &mut change, EntityType::Category, id).await?;
+    }
+
+    let broken_references = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(&mut *tx)
+        .await?
+        .len();
+    if broken_references > 0 {
+        return Err(ExportError::Invalid(format!(
+            "{broken_references} records reference records that aren't in the export"
+        )));
+    }
+    tx.commit().await?;
+
+    Ok(export.data.counts())
+}
+
+/// Logs a record an import added.
+pub(crate) async fn created(
+    conn: &mut SqliteConnection,
+    change: &mut ChangeContext,
+    entity: EntityType,
+    id: i64,
+) -> Result<(), sqlx::Error> {
+    database::record_change(conn, change, entity, id, ChangeAction::Create, None).await
+}
+
+async fn insert_data(
+    conn: &mut SqliteConnection,
+    change: &mut ChangeContext,
+    data: &ExportData,
+) -> Result<(), sqlx::Error> {
+    // Transactions can't be added to archived accounts or categories, so
+    // everything goes in active and is archived once the transactions are in
+    for account in &data.accounts {
+        sqlx::query(
+            r#"
+            INSERT INTO accounts (id, name, type, institution, current_balance, display_order, archived,
+                                  include_in_net_worth, account_number_last4, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, FALSE, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(account.id)
+        .bind(&account.name)
+        .bind(&account.account_type)
+        .bind(&account.institution)
+        .bind(account.current_balance)
+        .bind(account.display_order)
+        .bind(account.include_in_net_worth)
+        .bind(&account.account_number_last4)
+        .bind(account.created_at.naive_utc())
+        .bind(account.updated_at.naive_utc())
+        .execute(&mut *conn)
+        .await?;
+        created(conn, change, EntityType::Account, account.id).await?;
+    }
+
+    for category in &data.categories {
+        sqlx::query(
+            r#"
+            INSERT INTO categories (id, name, parent_category_id, display_order, archived,
+                                    default_discretionary, default_fixed, is_system_category, created_at)
+            VALUES (?, ?, ?, ?, FALSE, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(category.id)
+        .bind(&category.name)
+        .bind(category.parent_category_id)
+        .bind(category.display_order)
+        .bind(category.default_discretionary)
+        .bind(category.default_fixed)
+        .bind(category.is_system_category)
+        .bind(category.created_at.naive_utc())
+        .execute(&mut *conn)
+        .await?;
+        created(conn, change, EntityType::Category, category.id).await?;
+    }
+
+    for tag in &data.tags {
+        sqlx::query("INSERT INTO tags (id, name, created_at) VALUES (?, ?, ?)")
+            .bind(tag.id)
+            .bind(&tag.name)
+            .bind(tag.created_at.naive_utc())
+            .execute(&mut *conn)
+            .await?;
+        created(conn, change, EntityType::Tag, tag.id).await?;
+    }
+
+    for transaction in &data.transactions {
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (id, account_id, date, amount, transaction_type, description, payee,
+                                      memo, original_description, category_id, spending_kind, pending,
+                                      cleared, reconciled, import_id, source, created_at, deleted_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(transaction.id)
+        .bind(transaction.account_id)
+        .bind(transaction.date)
+        .bind(transaction.amount)
+        .bind(&transaction.transaction_type)
+        .bind(&transaction.description)
+        .bind(&transaction.payee)
+        .bind(&transaction.memo)
+        .bind(&transaction.original_description)
+        .bind(transaction.category_id)
+        .bind(transaction.spending_kind.map(SpendingKind::as_str))
+        .bind(transaction.pending)
+        .bind(transaction.cleared)
+        .bind(transaction.reconciled)
+        .bind(&transaction.import_id)
+        .bind(&transaction.source)
+        .bind(transaction.created_at.naive_utc())
+        .bind(transaction.deleted_at.map(|deleted_at| deleted_at.naive_utc()))
+        .execute(&mut *conn)
+        .await?;
+
+        for &tag_id in &transaction.tag_ids {
+            sqlx::query("INSERT INTO transaction_tags (transaction_id, tag_id) VALUES (?, ?)")
+                .bind(transaction.id)
+                .bind(tag_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        created(conn, change, EntityType::Transaction, transaction.id).await?;
+    }
+
+    for transfer in &data.transfers {
+        sqlx::query(
+            r#"
+            INSERT INTO transfers (id, from_transaction_id, to_transaction_id, transfer_type, auto_created, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(transfer.id)
+        .bind(transfer.from_transaction_id)
+        .bind(transfer.to_transaction_id)
+        .bind(&transfer.transfer_type)
+        .bind(transfer.auto_created)
+        .bind(transfer.created_at.naive_utc())
+        .execute(&mut *conn)
+        .await?;
+        created(conn, change, EntityType::Transfer, transfer.id).await?;
+    }
+
+    for account in data.accounts.iter().filter(|account| account.archived) {
+        let before = database::snapshot(conn, EntityType::Account, account.id).await?;
+        sqlx::query("UPDATE accounts SET archived = TRUE WHERE id = ?")
+            .bind(account.id)
+            .execute(&mut *conn)
+            .await?;
+        database::record_change(
+            conn,
+            change,
+            EntityType::Account,
+            account.id,
+            ChangeAction::Archive,
+            before,
+        )
+        .await?;
+    }
+    for category in data.categories.iter().filter(|category| category.archived) {
+        let before = database::snapshot(conn, EntityType::Category, category.id).await?;
+        sqlx::query("UPDATE categories SET archived = TRUE WHERE id = ?")
+            .bind(category.id)
+            .execute(&mut *conn)
+            .await?;
+        database::record_change(
+            conn,
+            change,
+            EntityType::Category,
+            category.id,
+            ChangeAction::Archive,
+            before,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Writes the export as a zip holding one CSV file per kind of record.
+///
+/// Amounts are written in dollars and references are accompanied by names,
+/// such as the full category path of each transaction, so the files read well
+/// in a spreadsheet.
+fn csv_zip(export: &Export) -> Result<Vec<u8>, ExportError> {
+    let data = &export.data;
+    let account_names: HashMap<i64, &str> = data
+        .accounts
+        .iter()
+        .map(|account| (account.id, account.name.as_str()))
+        .collect();
+    let category_paths = category_paths(&data.categories);
+    let tag_names: HashMap<i64, &str> = data
+        .tags
+        .iter()
+        .map(|tag| (tag.id, tag.name.as_str()))
+        .collect();
+
+    let mut accounts = Csv::new(&[
+        "id",
+        "name",
+        "type",
+        "institution",
+        "current_balance",
+        "display_order",
+        "archived",
+        "include_in_net_worth",
+        "account_number_last4",
+        "created_at",
+        "updated_at",
+    ]);
+    for account in &data.accounts {
+        accounts.row([
+            account.id.to_string(),
+            account.name.clone(),
+            account.account_type.clone(),
+            optional(&account.institution),
+            account
+                .current_balance
+                .map(format_cents)
+                .unwrap_or_default(),
+            optional(&account.display_order),
+            account.archived.to_string(),
+            account.include_in_net_worth.to_string(),
+            optional(&account.account_number_last4),
+            format_timestamp(&account.created_at),
+            format_timestamp(&account.updated_at),
+        ]);
+    }
+
+    let mut categories = Csv::new(&[
+        "id",
+        "name",
+        "parent_category_id",
+        "path",
+        "display_order",
+        "archived",
+        "default_discretionary",
+        "default_fixed",
+        "is_system_category",
+        "created_at",
+    ]);
+    for category in &data.categories {
+        categories.row([
+            category.id.to_string(),
+            category.name.clone(),
+            optional(&category.parent_category_id),
+            category_paths[&category.id].clone(),
+            optional(&category.display_order),
+            category.archived.to_string(),
+            optional(&category.default_discretionary),
+            optional(&category.default_fixed),
+            category.is_system_category.to_string(),
+            format_timestamp(&category.created_at),
+        ]);
+    }
+
+    let mut tags = Csv::new(&["id", "name", "created_at"]);
+    for tag in &data.tags {
+        tags.row([
+            tag.id.to_string(),
+            tag.name.clone(),
+            format_timestamp(&tag.created_at),
+        ]);
+    }
+
+    let mut transactions = Csv::new(&[
+        "id",
+        "date",
+        "account_id",
+        "account",
+        "amount",
+        "payee",
+        "description",
+        "memo",
+        "original_description",
+        "category_id",
+        "category",
+        "tags",
+        "spending_kind",
+        "transaction_type",
+        "pending",
+        "cleared",
+        "reconciled",
+        "import_id",
+        "source",
+        "created_at",
+        "deleted_at",
+    ]);
+    for transaction in &data.transactions {
+        let category = transaction
+            .category_id
+            .and_then(|id| category_paths.get(&id))
+            .cloned()
+            .unwrap_or_default();
+        let tags = transaction
+            .tag_ids
+            .iter()
+            .filter_map(|id| tag_names.get(id).copied())
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        transactions.row([
+            transaction.id.to_string(),
+            transaction.date.to_string(),
+            transaction.account_id.to_string(),
+            account_names
+                .get(&transaction.account_id)
+                .copied()
+                .unwrap_or_default()
+                .to_string(),
+            format_cents(transaction.amount),
+            optional(&transaction.payee),
+            optional(&transaction.description),
+            optional(&transaction.memo),
+            optional(&transaction.original_description),
+            optional(&transaction.category_id),
+            category,
+            tags,
+            transaction
+                .spending_kind
+                .map(SpendingKind::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            transaction.transaction_type.clone(),
+            transaction.pending.to_string(),
+            transaction.cleared.to_string(),
+            transaction.reconciled.to_string(),
+            optional(&transaction.import_id),
+            optional(&transaction.source),
+            format_timestamp(&transaction.created_at),
+            transaction
+                .deleted_at
+                .as_ref()
+                .map(format_timestamp)
+                .unwrap_or_default(),
+        ]);
+    }
+
+    let mut transfers = Csv::new(&[
+        "id",
+        "from_transaction_id",
+        "to_transaction_id",
+        "transfer_type",
+        "auto_created",
+        "created_at",
+    ]);
+    for transfer in &data.transfers {
+        transfers.row([
+            transfer.id.to_string(),
+            transfer.from_transaction_id.to_string(),
+            transfer.to_transaction_id.to_string(),
+            optional(&transfer.transfer_type),
+            transfer.auto_created.to_string(),
+            format_timestamp(&transfer.created_at),
+        ]);
+    }
+
+    let mut zip = ZipWriter::new(export.exported_at);
+    zip.add("accounts.csv", accounts.text.as_bytes())?;
+    zip.add("categories.csv", categories.text.as_bytes())?;
+    zip.add("tags.csv", tags.text.as_bytes())?;
+    zip.add("transactions.csv", transactions.text.as_bytes())?;
+    zip.add("transfers.csv", transfers.text.as_bytes())?;
+    zip.finish()
+}
+
+/// Builds each category's full path, such as "Food > Groceries".
+fn category_paths(categories: &[ExportedCategory]) -> HashMap<i64, String> {
+    let by_id: HashMap<i64, &ExportedCategory> = categories
+        .iter()
+        .map(|category| (category.id, category))
+        .collect();
+
+    categories
+        .iter()
+        .map(|category| {
+            let mut names = vec![category.name.as_str()];
+            let mut parent_id = category.parent_category_id;
+            // The depth limit guards against a cycle in damaged data
+            while let Some(parent) = parent_id.and_then(|id| by_id.get(&id)) {
+                if names.len() > categories.len() {
+                    break;
+                }
+                names.push(&parent.name);
+                parent_id = parent.parent_category_id;
+            }
+            names.reverse();
+            (category.id, names.join(" > "))
+        })
+        .collect()
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+/// Formats cents as a plain decimal amount, such as `-1234.50`.
+pub fn format_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// A CSV file as described by RFC 4180, with a header row.
+struct Csv {
+    text: String,
+}
+
+impl Csv {
+    fn new(header: &[&str]) -> Self {
+        let mut csv = Csv {
+            text: String::new(),
+        };
+        csv.row(header);
+        csv
+    }
+
+    fn row<I, S>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                self.text.push(',');
+            }
+            let field = field.as_ref();
+            if field.contains([',', '"', '\n', '\r']) {
+                self.text.push('"');
+                self.text.push_str(&field.replace('"', "\"\""));
+                self.text.push('"');
+            } else {
+                self.text.push_str(field);
+            }
+        }
+        self.text.push_str("\r\n");
+    }
+}
+
+/// Writes a zip archive of deflate-compressed files in memory.
+///
+/// Only what an export needs is supported: a handful of files, each well
+/// under 4 GiB, so the zip64 extensions are never required.
+struct ZipWriter {
+    archive: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+    /// Modification time and date of every file, in MS-DOS format
+    dos_time: u16,
+    dos_date: u16,
+}
+
+impl ZipWriter {
+    fn new(modified: DateTime<Utc>) -> Self {
+        ZipWriter {
+            archive: Vec::new(),
+            central_directory: Vec::new(),
+            entries: 0,
+            dos_time: ((modified.hour() << 11) | (modified.minute() << 5) | (modified.second() / 2))
+                as u16,
+            dos_date: (((modified.year().clamp(1980, 2107) - 1980) as u32) << 9
+                | (modified.month() << 5)
+                | modified.day()) as u16,
+        }
+    }
+
+    fn add(&mut self, name: &str, contents: &[u8]) -> Result<(), ExportError> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents)?;
+        let compressed = encoder.finish()?;
+
+        let too_large = |_| ExportError::Invalid(format!("{name} is too large for a zip file"));
+        let offset = u32::try_from(self.archive.len()).map_err(too_large)?;
+        let compressed_size = u32::try_from(compressed.len()).map_err(too_large)?;
+        let size = u32::try_from(contents.len()).map_err(too_large)?;
+        let crc = crc32fast::hash(contents);
+
+        // Fields shared by the local header and the central directory entry:
+        // version needed, UTF-8 names flag, deflate, time, date, CRC and sizes
+        let mut common = Vec::new();
+        common.extend(20u16.to_le_bytes());
+        common.extend(0x0800u16.to_le_bytes());
+        common.extend(8u16.to_le_bytes());
+        common.extend(self.dos_time.to_le_bytes());
+        common.extend(self.dos_date.to_le_bytes());
+        common.extend(crc.to_le_bytes());
+        common.extend(compressed_size.to_le_bytes());
+        common.extend(size.to_le_bytes());
+        common.extend((name.len() as u16).to_le_bytes());
+        common.extend(0u16.to_le_bytes());
+
+        self.archive.extend(0x04034b50u32.to_le_bytes());
+        self.archive.extend(&common);
+        self.archive.extend(name.as_bytes());
+        self.archive.extend(&compressed);
+
+        self.central_directory.extend(0x02014b50u32.to_le_bytes());
+        self.central_directory.extend(20u16.to_le_bytes());
+        self.central_directory.extend(&common);
+        // Comment length, disk number, internal and external attributes
+        self.central_directory.extend([0; 10]);
+        self.central_directory.extend(offset.to_le_bytes());
+        self.central_directory.extend(name.as_bytes());
+
+        self.entries += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>, ExportError> {
+        let too_large =
+            |_| ExportError::Invalid("the export is too large for a zip file".to_string());
+        let offset = u32::try_from(self.archive.len()).map_err(too_large)?;
+        let size = u32::try_from(self.central_directory.len()).map_err(too_large)?;
+
+        self.archive.extend(&self.central_directory);
+        self.archive.extend(0x06054b50u32.to_le_bytes());
+        // This disk and the disk the central directory starts on
+        self.archive.extend([0; 4]);
+        self.archive.extend(self.entries.to_le_bytes());
+        self.archive.extend(self.entries.to_le_bytes());
+        self.archive.extend(size.to_le_bytes());
+        self.archive.extend(offset.to_le_bytes());
+        // Comment length
+        self.archive.extend([0; 2]);
+
+        Ok(self.archive)
+    }
+}
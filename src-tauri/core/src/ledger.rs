@@ -0,0 +1,1464 @@
+use crate::database::{ChangeContext, ChangeSource, EntityType};
+use crate::export::{self, Export, ExportError, ExportedTransaction, created};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Commodity every amount is written in; Tally only tracks US dollars.
+pub const COMMODITY: &str = "USD";
+
+/// Account for outflows that have no category.
+pub const UNCATEGORIZED_EXPENSES: &str = "Expenses:Uncategorized";
+
+/// Account for inflows that have no category.
+pub const UNCATEGORIZED_INCOME: &str = "Income:Uncategorized";
+
+/// Plain-text accounting formats transactions can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerFormat {
+    /// Ledger journal syntax, which hledger reads as well
+    Ledger,
+    /// Beancount syntax, which is stricter about account names
+    Beancount,
+}
+
+/// A journal rendered from an export.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    /// The journal file's contents
+    pub text: String,
+    /// How many transaction entries the journal has
+    pub entries: usize,
+}
+
+/// Whether an entry has cleared the bank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Cleared,
+    Pending,
+    Uncleared,
+}
+
+impl Status {
+    fn of(transaction: &ExportedTransaction) -> Status {
+        if transaction.cleared || transaction.reconciled {
+            Status::Cleared
+        } else if transaction.pending {
+            Status::Pending
+        } else {
+            Status::Uncleared
+        }
+    }
+}
+
+/// A metadata value attached to an entry.
+enum Value {
+    Number(i64),
+    Text(String),
+    True,
+}
+
+struct Entry {
+    date: NaiveDate,
+    id: i64,
+    status: Status,
+    payee: Option<String>,
+    narration: Option<String>,
+    metadata: Vec<(&'static str, Value)>,
+    postings: Vec<(String, i64)>,
+}
+
+/// Renders an export as a Ledger or Beancount journal.
+///
+/// Accounts become `Assets:Name`, or `Liabilities:Name` for credit cards, and
+/// categories become `Expenses:Parent:Child`. Each transaction is an entry
+/// with two postings, one to its account and one to its category; those
+/// without a category post to `Expenses:Uncategorized` or
+/// `Income:Uncategorized` depending on their direction. A transfer whose two
+/// sides cancel out becomes a single entry between the two accounts instead.
+///
+/// Cleared and reconciled transactions are marked `*`. Ledger marks pending
+/// ones `!` and leaves the rest unmarked, while Beancount marks everything
+/// that hasn't cleared `!`. The Tally id, memo and reconciled and pending
+/// flags are kept as entry metadata. Trashed transactions are left out.
+///
+/// # Arguments
+///
+/// * `export` - Data read by [`export::export_data`]
+/// * `format` - Syntax to write
+///
+/// # Returns
+///
+/// The journal, with its accounts declared up front and its entries sorted by
+/// date.
+///
+/// # Examples
+///
+/// ```rust
+/// let export = export_data(&pool).await?;
+/// let journal = journal(&export, LedgerFormat::Beancount);
+/// println!("{} entries", journal.entries);
+/// ```
+pub fn journal(export: &Export, format: LedgerFormat) -> Journal {
+    let data = &export.data;
+    let mut names = HashSet::new();
+
+    let mut accounts = HashMap::new();
+    let mut opened = BTreeMap::new();
+    for account in &data.accounts {
+        let root = match account.account_type.as_str() {
+            "credit_card" => "Liabilities",
+            _ => "Assets",
+        };
+        let name = unique_name(
+            &mut names,
+            format!("{root}:{}", component(format, &account.name)),
+            account.id,
+        );
+        opened.insert(name.clone(), account.created_at.date_naive());
+        accounts.insert(account.id, name);
+    }
+
+    let parents: HashMap<i64, _> = data
+        .categories
+        .iter()
+        .map(|category| (category.id, category))
+        .collect();
+    let mut categories = HashMap::new();
+    for category in &data.categories {
+        // Walk up to the top-level category, bounded in case of a cycle
+        let mut path = vec![component(format, &category.name)];
+        let mut parent_id = category.parent_category_id;
+        while let Some(parent) = parent_id.and_then(|id| parents.get(&id)) {
+            if path.len() > data.categories.len() {
+                break;
+            }
+            path.push(component(format, &parent.name));
+            parent_id = parent.parent_category_id;
+        }
+        path.push("Expenses".to_string());
+        path.reverse();
+
+        let name = unique_name(&mut names, path.join(":"), category.id);
+        opened.insert(name.clone(), category.created_at.date_naive());
+        categories.insert(category.id, name);
+    }
+
+    let account = |id: i64| {
+        accounts
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| "Assets:Unknown".to_string())
+    };
+    let transactions: HashMap<i64, &ExportedTransaction> = data
+        .transactions
+        .iter()
+        .filter(|transaction| transaction.deleted_at.is_none())
+        .map(|transaction| (transaction.id, transaction))
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut transferred = HashSet::new();
+    for transfer in &data.transfers {
+        let (Some(from), Some(to)) = (
+            transactions.get(&transfer.from_transaction_id),
+            transactions.get(&transfer.to_transaction_id),
+        ) else {
+            continue;
+        };
+        // Sides that don't cancel out, say because of a fee, stay as they are
+        if from.amount + to.amount != 0
+            || transferred.contains(&from.id)
+            || transferred.contains(&to.id)
+        {
+            continue;
+        }
+        transferred.insert(from.id);
+        transferred.insert(to.id);
+
+        let status = match (Status::of(from), Status::of(to)) {
+            (Status::Cleared, Status::Cleared) => Status::Cleared,
+            (Status::Pending, _) | (_, Status::Pending) => Status::Pending,
+            _ => Status::Uncleared,
+        };
+        let mut metadata = vec![("transfer_id", Value::Number(transfer.id))];
+        if let Some(memo) = from.memo.as_ref().or(to.memo.as_ref()) {
+            metadata.push(("memo", Value::Text(memo.clone())));
+        }
+        if from.reconciled && to.reconciled {
+            metadata.push(("reconciled", Value::True));
+        }
+        if from.pending || to.pending {
+            metadata.push(("pending", Value::True));
+        }
+
+        entries.push(Entry {
+            date: from.date,
+            id: from.id,
+            status,
+            payee: from.payee.clone().or_else(|| to.payee.clone()),
+            narration: from.description.clone().or_else(|| to.description.clone()),
+            metadata,
+            postings: vec![
+                (account(to.account_id), to.amount),
+                (account(from.account_id), from.amount),
+            ],
+        });
+    }
+
+    for transaction in &data.transactions {
+        if transaction.deleted_at.is_some() || transferred.contains(&transaction.id) {
+            continue;
+        }
+
+        let category = match transaction.category_id.and_then(|id| categories.get(&id)) {
+            Some(category) => category.clone(),
+            None if transaction.amount > 0 => UNCATEGORIZED_INCOME.to_string(),
+            None => UNCATEGORIZED_EXPENSES.to_string(),
+        };
+        let mut metadata = vec![("tally_id", Value::Number(transaction.id))];
+        if let Some(memo) = &transaction.memo {
+            metadata.push(("memo", Value::Text(memo.clone())));
+        }
+        if transaction.reconciled {
+            metadata.push(("reconciled", Value::True));
+        }
+        if transaction.pending {
+            metadata.push(("pending", Value::True));
+        }
+
+        entries.push(Entry {
+            date: transaction.date,
+            id: transaction.id,
+            status: Status::of(transaction),
+            payee: transaction.payee.clone(),
+            narration: transaction.description.clone(),
+            metadata,
+            postings: vec![
+                (category, -transaction.amount),
+                (account(transaction.account_id), transaction.amount),
+            ],
+        });
+    }
+    entries.sort_by_key(|entry| (entry.date, entry.id));
+
+    // Beancount needs every account opened on or before its first posting
+    for entry in &entries {
+        for (name, _) in &entry.postings {
+            opened
+                .entry(name.clone())
+                .and_modify(|date: &mut NaiveDate| *date = (*date).min(entry.date))
+                .or_insert(entry.date);
+        }
+    }
+
+    let mut text = format!(
+        "; Exported from Tally on {}\n\n",
+        export.exported_at.date_naive()
+    );
+    match format {
+        LedgerFormat::Ledger => {
+            text.push_str(&format!("commodity {COMMODITY}\n"));
+            for name in opened.keys() {
+                text.push_str(&format!("account {name}\n"));
+            }
+        }
+        LedgerFormat::Beancount => {
+            text.push_str(&format!(
+                "option \"operating_currency\" \"{COMMODITY}\"\n\n"
+            ));
+            for (name, date) in &opened {
+                text.push_str(&format!("{date} open {name} {COMMODITY}\n"));
+            }
+        }
+    }
+    for entry in &entries {
+        text.push('\n');
+        write_entry(&mut text, format, entry);
+    }
+
+    Journal {
+        text,
+        entries: entries.len(),
+    }
+}
+
+/// Exports all transactions to a Ledger or Beancount journal file.
+///
+/// See [`journal`] for how Tally's records map onto the journal.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `path` - File to write; an existing file is overwritten
+/// * `format` - Syntax to write
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(usize)` - How many entries the journal has
+/// - `Err(ExportError)` - Database read error or file write error
+///
+/// # Examples
+///
+/// ```rust
+/// let entries = export_ledger(&pool, Path::new("tally.beancount"), LedgerFormat::Beancount).await?;
+/// ```
+pub async fn export_ledger(
+    pool: &Pool<Sqlite>,
+    path: &Path,
+    format: LedgerFormat,
+) -> Result<usize, ExportError> {
+    let export = export::export_data(pool).await?;
+    let journal = journal(&export, format);
+    fs::write(path, journal.text)?;
+
+    Ok(journal.entries)
+}
+
+/// What importing a journal did.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalImport {
+    /// Accounts created for asset and liability accounts Tally didn't have
+    pub accounts: usize,
+    /// Categories created for expense and income accounts Tally didn't have
+    pub categories: usize,
+    /// Tags created for entry tags Tally didn't have
+    pub tags: usize,
+    /// Transactions imported, counting both sides of each transfer
+    pub transactions: usize,
+    pub transfers: usize,
+    /// Entries and directives that weren't imported, in file order
+    pub skipped: Vec<SkippedLine>,
+}
+
+/// A part of a journal that wasn't imported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedLine {
+    /// Line the entry or directive starts on, counting from 1
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Imports a Ledger, hledger or Beancount journal file.
+///
+/// See [`import_journal`] for what is imported.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `path` - Journal file to read
+/// * `format` - Syntax of the file; hledger journals use [`LedgerFormat::Ledger`]
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(JournalImport)` - What was created and what was skipped
+/// - `Err(ExportError)` - The file couldn't be read or a write failed; nothing
+///   was changed
+///
+/// # Examples
+///
+/// ```rust
+/// let import = import_ledger(&pool, Path::new("2025.journal"), LedgerFormat::Ledger).await?;
+/// for skipped in &import.skipped {
+///     println!("line {}: {}", skipped.line, skipped.reason);
+/// }
+/// ```
+pub async fn import_ledger(
+    pool: &Pool<Sqlite>,
+    path: &Path,
+    format: LedgerFormat,
+) -> Result<JournalImport, ExportError> {
+    let text = fs::read_to_string(path)?;
+    import_journal(pool, &text, format).await
+}
+
+/// Imports the entries of a journal into the database.
+///
+/// Asset and liability accounts become checking and credit card accounts,
+/// and expense and income accounts become categories, with each component
+/// of the account name under the root becoming a level of the category tree.
+/// Accounts and categories that already exist with the same name are reused,
+/// and declared accounts are created even when no entry uses them. Entries
+/// that use an account or category that only exists archived are skipped,
+/// since archived records can't take new transactions.
+/// `Expenses:Uncategorized` and `Income:Uncategorized` leave transactions
+/// without a category.
+///
+/// Only entries with two postings in US dollars are imported. One between an
+/// account and a category becomes a transaction, and one between two
+/// accounts becomes a transaction on each side linked by a `transfers` row.
+/// The `*` marker makes transactions cleared, and Ledger's `!` makes them
+/// pending, as do `pending` and `reconciled` metadata. A `memo` metadata value
+/// becomes the memo and tags become Tally tags. Everything else, such as other
+/// commodities, prices, equity accounts, entries with more postings and
+/// directives that change how the journal reads, is skipped and reported
+/// with its line.
+///
+/// Everything is written in one database transaction and logged as one
+/// operation, so a single undo reverses the whole import.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `text` - Contents of the journal
+/// * `format` - Syntax of the journal
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(JournalImport)` - What was created and what was skipped
+/// - `Err(ExportError)` - Database write error; nothing was changed
+///
+/// # Examples
+///
+/// ```rust
+/// let import = import_journal(&pool, &fs::read_to_string(path)?, LedgerFormat::Beancount).await?;
+/// println!("Imported {} transactions", import.transactions);
+/// ```
+pub async fn import_journal(
+    pool: &Pool<Sqlite>,
+    text: &str,
+    format: LedgerFormat,
+) -> Result<JournalImport, ExportError> {
+    let journal = parse_journal(text, format);
+    let mut import = JournalImport {
+        skipped: journal.skipped,
+        ..JournalImport::default()
+    };
+    let mut records = Records::default();
+    let mut change = ChangeContext::operation("Import journal", ChangeSource::Import);
+
+    let mut tx = pool.begin().await?;
+    for name in &journal.declared {
+        // Declarations of accounts Tally has no equivalent for are harmless,
+        // and so are declarations of archived ones
+        match classify(name) {
+            Ok(Side::Account(account)) => {
+                if !records.archived_account(&mut tx, &account).await? {
+                    records
+                        .account(&mut tx, &mut change, &account, &mut import)
+                        .await?;
+                }
+            }
+            Ok(Side::Category(path)) => {
+                if records.archived_category(&mut tx, &path).await?.is_none() {
+                    records
+                        .category(&mut tx, &mut change, &path, &mut import)
+                        .await?;
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    for entry in &journal.entries {
+        let plan = match plan(entry) {
+            Ok(plan) => plan,
+            Err(reason) => {
+                import.skipped.push(SkippedLine {
+                    line: entry.line,
+                    reason,
+                });
+                continue;
+            }
+        };
+        if let Some(reason) = records.archived(&mut tx, &plan).await? {
+            import.skipped.push(SkippedLine {
+                line: entry.line,
+                reason,
+            });
+            continue;
+        }
+
+        match plan {
+            Plan::Transaction {
+                account,
+                amount,
+                category,
+            } => {
+                let account_id = records
+                    .account(&mut tx, &mut change, &account, &mut import)
+                    .await?;
+                let category_id = records
+                    .category(&mut tx, &mut change, &category, &mut import)
+                    .await?;
+                let transaction_type = if amount < 0 { "expense" } else { "income" };
+                let id = insert_entry(
+                    &mut tx,
+                    entry,
+                    account_id,
+                    amount,
+                    category_id,
+                    transaction_type,
+                )
+                .await?;
+                records
+                    .tag(&mut tx, &mut change, id, &entry.tags, &mut import)
+                    .await?;
+                created(&mut tx, &mut change, EntityType::Transaction, id).await?;
+                import.transactions += 1;
+            }
+            Plan::Transfer { from, to, amount } => {
+                let from_account_id = records
+                    .account(&mut tx, &mut change, &from, &mut import)
+                    .await?;
+                let to_account_id = records
+                    .account(&mut tx, &mut change, &to, &mut import)
+                    .await?;
+                let from_id =
+                    insert_entry(&mut tx, entry, from_account_id, -amount, None, "transfer")
+                        .await?;
+                let to_id =
+                    insert_entry(&mut tx, entry, to_account_id, amount, None, "transfer").await?;
+                for id in [from_id, to_id] {
+                    records
+                        .tag(&mut tx, &mut change, id, &entry.tags, &mut import)
+                        .await?;
+                    created(&mut tx, &mut change, EntityType::Transaction, id).await?;
+                }
+
+                let transfer = sqlx::query(
+                    "INSERT INTO transfers (from_transaction_id, to_transaction_id, transfer_type) VALUES (?, ?, 'internal')",
+                )
+                .bind(from_id)
+                .bind(to_id)
+                .execute(&mut *tx)
+                .await?;
+                created(
+                    &mut tx,
+                    &mut change,
+                    EntityType::Transfer,
+                    transfer.last_insert_rowid(),
+                )
+                .await?;
+                import.transactions += 2;
+                import.transfers += 1;
+            }
+        }
+    }
+    tx.commit().await?;
+
+    import.skipped.sort_by_key(|skipped| skipped.line);
+    Ok(import)
+}
+
+fn write_entry(text: &mut String, format: LedgerFormat, entry: &Entry) {
+    let (indent, marker) = match (format, entry.status) {
+        (LedgerFormat::Ledger, Status::Uncleared) => ("    ", ""),
+        (LedgerFormat::Ledger, _) => ("    ", " "),
+        (LedgerFormat::Beancount, _) => ("  ", " "),
+    };
+    let flag = match (format, entry.status) {
+        (_, Status::Cleared) => "*",
+        (LedgerFormat::Ledger, Status::Uncleared) => "",
+        _ => "!",
+    };
+    let payee = entry.payee.as_deref().map(one_line);
+    let narration = entry.narration.as_deref().map(one_line);
+
+    text.push_str(&format!("{}{marker}{flag}", entry.date));
+    match format {
+        LedgerFormat::Ledger => {
+            let description: Vec<String> = payee.into_iter().chain(narration).collect();
+            if !description.is_empty() {
+                text.push_str(&format!(" {}", description.join(" | ")));
+            }
+        }
+        LedgerFormat::Beancount => {
+            if let Some(payee) = payee {
+                text.push_str(&format!(" {}", quote(&payee)));
+            }
+            text.push_str(&format!(" {}", quote(&narration.unwrap_or_default())));
+        }
+    }
+    text.push('\n');
+
+    for (key, value) in &entry.metadata {
+        if format == LedgerFormat::Ledger && *key == "pending" {
+            // The `!` marker already says so
+            continue;
+        }
+        let value = match (format, value) {
+            (_, Value::Number(number)) => number.to_string(),
+            (LedgerFormat::Ledger, Value::Text(text)) => one_line(text),
+            (LedgerFormat::Ledger, Value::True) => "true".to_string(),
+            (LedgerFormat::Beancount, Value::Text(text)) => quote(&one_line(text)),
+            (LedgerFormat::Beancount, Value::True) => "TRUE".to_string(),
+        };
+        match format {
+            LedgerFormat::Ledger => text.push_str(&format!("{indent}; {key}: {value}\n")),
+            LedgerFormat::Beancount => text.push_str(&format!("{indent}{key}: {value}\n")),
+        }
+    }
+
+    let amounts: Vec<String> = entry
+        .postings
+        .iter()
+        .map(|(_, cents)| export::format_cents(*cents))
+        .collect();
+    let name_width = entry.postings.iter().map(|(name, _)| name.len()).max();
+    let amount_width = amounts.iter().map(String::len).max();
+    for ((name, _), amount) in entry.postings.iter().zip(&amounts) {
+        text.push_str(&format!(
+            "{indent}{name:<name_width$}  {amount:>amount_width$} {COMMODITY}\n",
+            name_width = name_width.unwrap_or_default(),
+            amount_width = amount_width.unwrap_or_default(),
+        ));
+    }
+}
+
+/// Turns a Tally name into one component of a ledger account name.
+///
+/// Ledger only reserves `:` and runs of spaces, but Beancount components
+/// must start with a capital letter or digit and contain only letters,
+/// digits and dashes.
+fn component(format: LedgerFormat, name: &str) -> String {
+    let component = match format {
+        LedgerFormat::Ledger => one_line(name).replace(':', "-"),
+        LedgerFormat::Beancount => {
+            let mut component = String::new();
+            for c in name.chars() {
+                if c.is_alphanumeric() {
+                    if component.is_empty() {
+                        component.extend(c.to_uppercase());
+                    } else {
+                        component.push(c);
+                    }
+                } else if !component.is_empty() && !component.ends_with('-') {
+                    component.push('-');
+                }
+            }
+            component.trim_end_matches('-').to_string()
+        }
+    };
+
+    if component.is_empty() {
+        "Unnamed".to_string()
+    } else {
+        component
+    }
+}
+
+/// Keeps account names unique by suffixing the record id to repeats, which
+/// happen when names only differ in characters the format can't hold.
+fn unique_name(names: &mut HashSet<String>, name: String, id: i64) -> String {
+    let name = if names.contains(&name) {
+        format!("{name}-{id}")
+    } else {
+        name
+    };
+    names.insert(name.clone());
+    name
+}
+
+/// Collapses line breaks and runs of whitespace, which both formats treat
+/// as syntax.
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Everything read from a journal, before it is matched to Tally's records.
+struct ParsedJournal {
+    /// Account names from `account` and `open` directives
+    declared: Vec<String>,
+    entries: Vec<ParsedEntry>,
+    skipped: Vec<SkippedLine>,
+}
+
+struct ParsedEntry {
+    line: usize,
+    date: NaiveDate,
+    status: Status,
+    payee: Option<String>,
+    narration: Option<String>,
+    metadata: HashMap<String, String>,
+    tags: Vec<String>,
+    /// Account names and amounts in cents; at most one amount may be left out
+    postings: Vec<(String, Option<i64>)>,
+    /// The first thing in the entry that can't be imported
+    unsupported: Option<String>,
+}
+
+impl ParsedEntry {
+    fn new(line: usize, date: NaiveDate) -> Self {
+        ParsedEntry {
+            line,
+            date,
+            status: Status::Uncleared,
+            payee: None,
+            narration: None,
+            metadata: HashMap::new(),
+            tags: Vec::new(),
+            postings: Vec::new(),
+            unsupported: None,
+        }
+    }
+
+    fn unsupported(&mut self, reason: impl Into<String>) {
+        self.unsupported.get_or_insert_with(|| reason.into());
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.metadata
+            .get(key)
+            .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+    }
+}
+
+/// A line that starts at the left margin, read together with the indented
+/// lines under it.
+enum Block {
+    Entry(ParsedEntry),
+    Declaration(String),
+    Ignored,
+    Unsupported(String),
+}
+
+fn parse_journal(text: &str, format: LedgerFormat) -> ParsedJournal {
+    let mut journal = ParsedJournal {
+        declared: Vec::new(),
+        entries: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    let indented = |line: &str| line.starts_with([' ', '\t']) && !line.trim().is_empty();
+
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        let number = index + 1;
+        index += 1;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indented(line) {
+            if !line.trim_start().starts_with(';') {
+                journal.skipped.push(SkippedLine {
+                    line: number,
+                    reason: "indented line outside of an entry".to_string(),
+                });
+            }
+            continue;
+        }
+
+        let mut body = Vec::new();
+        while let Some(next) = lines.get(index).filter(|next| indented(next)) {
+            body.push(next.trim());
+            index += 1;
+        }
+
+        if line.starts_with([';', '#', '*', '%', '|']) {
+            continue;
+        }
+        // Ledger's block comments run until their `end` line
+        if format == LedgerFormat::Ledger && matches!(line.trim_end(), "comment" | "test") {
+            while index < lines.len() && !lines[index].starts_with("end") {
+                index += 1;
+            }
+            index += 1;
+            continue;
+        }
+
+        let block = match format {
+            LedgerFormat::Ledger => ledger_block(number, line, &body),
+            LedgerFormat::Beancount => beancount_block(number, line, &body),
+        };
+        match block {
+            Block::Entry(entry) => journal.entries.push(entry),
+            Block::Declaration(name) => journal.declared.push(name),
+            Block::Ignored => {}
+            Block::Unsupported(reason) => journal.skipped.push(SkippedLine {
+                line: number,
+                reason,
+            }),
+        }
+    }
+
+    journal
+}
+
+fn ledger_block(number: usize, line: &str, body: &[&str]) -> Block {
+    let (header, comment) = split_comment(line);
+    let first = header.split_whitespace().next().unwrap_or_default();
+    if !first.starts_with(|c: char| c.is_ascii_digit()) {
+        return match first {
+            "account" => {
+                let name = header["account".len()..].trim();
+                Block::Declaration(name.split("  ").next().unwrap_or(name).to_string())
+            }
+            "commodity" | "P" | "D" | "payee" | "tag" | "year" | "Y" | "decimal-mark" => {
+                Block::Ignored
+            }
+            directive => Block::Unsupported(format!("unsupported directive `{directive}`")),
+        };
+    }
+
+    // A secondary date after `=` is dropped
+    let Some(date) = parse_date(first.split('=').next().unwrap_or(first)) else {
+        return Block::Unsupported(format!("couldn't read the date `{first}`"));
+    };
+    let mut entry = ParsedEntry::new(number, date);
+
+    let mut rest = header[first.len()..].trim_start();
+    if let Some(after) = rest.strip_prefix('*') {
+        entry.status = Status::Cleared;
+        rest = after.trim_start();
+    } else if let Some(after) = rest.strip_prefix('!') {
+        entry.status = Status::Pending;
+        rest = after.trim_start();
+    }
+    if rest.starts_with('(')
+        && let Some(end) = rest.find(')')
+    {
+        rest = rest[end + 1..].trim_start();
+    }
+    // hledger separates the payee from the note with `|`
+    match rest.split_once('|') {
+        Some((payee, narration)) => {
+            entry.payee = non_empty(payee);
+            entry.narration = non_empty(narration);
+        }
+        None => entry.narration = non_empty(rest),
+    }
+    ledger_comment(&mut entry, comment);
+
+    for line in body {
+        let (posting, comment) = split_comment(line);
+        ledger_comment(&mut entry, comment);
+        let posting = posting.trim();
+        if posting.is_empty() {
+            continue;
+        }
+
+        let posting = posting
+            .strip_prefix(['*', '!'])
+            .map_or(posting, str::trim_start);
+        let (account, amount) = match posting
+            .find("  ")
+            .into_iter()
+            .chain(posting.find('\t'))
+            .min()
+        {
+            Some(at) => (posting[..at].trim(), posting[at..].trim()),
+            None => (posting, ""),
+        };
+        if account.starts_with(['(', '[']) {
+            entry.unsupported("virtual postings aren't supported");
+        }
+
+        // Balance assertions after `=` are dropped
+        let amount = amount.split('=').next().unwrap_or_default().trim();
+        let amount = if amount.is_empty() {
+            None
+        } else {
+            parse_amount(amount)
+                .map_err(|reason| entry.unsupported(reason))
+                .ok()
+        };
+        entry.postings.push((account.to_string(), amount));
+    }
+
+    Block::Entry(entry)
+}
+
+/// Reads a Ledger comment as metadata (`key: value`) or tags (`:one:two:`).
+fn ledger_comment(entry: &mut ParsedEntry, comment: Option<&str>) {
+    let Some(comment) = comment.map(str::trim) else {
+        return;
+    };
+
+    if comment.len() > 1
+        && comment.starts_with(':')
+        && comment.ends_with(':')
+        && !comment.contains(char::is_whitespace)
+    {
+        entry.tags.extend(
+            comment
+                .split(':')
+                .filter(|tag| !tag.is_empty())
+                .map(String::from),
+        );
+    } else if let Some((key, value)) = comment.split_once(':')
+        && !key.is_empty()
+        && !key.contains(char::is_whitespace)
+    {
+        entry
+            .metadata
+            .insert(key.to_string(), value.trim().to_string());
+    }
+}
+
+/// A word or a quoted string on a Beancount line.
+enum Token<'a> {
+    Word(&'a str),
+    Text(String),
+}
+
+fn beancount_block(number: usize, line: &str, body: &[&str]) -> Block {
+    let tokens = tokenize(line);
+    let word = |index: usize| match tokens.get(index) {
+        Some(Token::Word(word)) => *word,
+        _ => "",
+    };
+
+    let Some(date) = parse_date(word(0)) else {
+        return match word(0) {
+            "option" => Block::Ignored,
+            directive => Block::Unsupported(format!("unsupported directive `{directive}`")),
+        };
+    };
+    match word(1) {
+        "*" | "!" | "txn" => {}
+        "open" => return Block::Declaration(word(2).to_string()),
+        "close" | "commodity" | "balance" | "price" | "note" | "document" | "event" | "query"
+        | "custom" => return Block::Ignored,
+        directive => {
+            return Block::Unsupported(format!("unsupported directive `{directive}`"));
+        }
+    }
+
+    let mut entry = ParsedEntry::new(number, date);
+    if word(1) == "*" {
+        entry.status = Status::Cleared;
+    }
+    let mut strings = Vec::new();
+    for token in &tokens[2..] {
+        match token {
+            Token::Text(text) => strings.push(text.as_str()),
+            Token::Word(word) => {
+                if let Some(tag) = word.strip_prefix('#') {
+                    entry.tags.push(tag.to_string());
+                }
+            }
+        }
+    }
+    // A lone string is the narration
+    if let [payee, _] = strings[..] {
+        entry.payee = non_empty(payee);
+    }
+    entry.narration = strings.last().and_then(|narration| non_empty(narration));
+
+    for line in body {
+        let tokens = tokenize(line);
+        match tokens.first() {
+            None => {}
+            Some(Token::Word(key))
+                if key.ends_with(':') && key.starts_with(|c: char| c.is_ascii_lowercase()) =>
+            {
+                let value = match tokens.get(1) {
+                    Some(Token::Text(text)) => text.clone(),
+                    Some(Token::Word(word)) => word.to_string(),
+                    None => String::new(),
+                };
+                entry
+                    .metadata
+                    .insert(key.trim_end_matches(':').to_string(), value);
+            }
+            Some(Token::Word(_)) => {
+                let words: Vec<&str> = tokens
+                    .iter()
+                    .filter_map(|token| match token {
+                        Token::Word(word) => Some(*word),
+                        Token::Text(_) => None,
+                    })
+                    .collect();
+                let words = match words[..] {
+                    ["*" | "!", ref rest @ ..] => rest,
+                    ref words => words,
+                };
+                let Some((account, amount)) = words.split_first() else {
+                    continue;
+                };
+                let amount = if amount.is_empty() {
+                    None
+                } else {
+                    parse_amount(&amount.join(" "))
+                        .map_err(|reason| entry.unsupported(reason))
+                        .ok()
+                };
+                entry.postings.push((account.to_string(), amount));
+            }
+            Some(Token::Text(_)) => entry.unsupported("couldn't read a posting"),
+        }
+    }
+
+    Block::Entry(entry)
+}
+
+/// Splits a Beancount line into words and unescaped strings, up to any comment.
+fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() && !rest.starts_with(';') {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let mut text = String::new();
+            let mut end = quoted.len();
+            let mut chars = quoted.char_indices();
+            while let Some((at, c)) = chars.next() {
+                match c {
+                    '\\' => text.extend(chars.next().map(|(_, escaped)| escaped)),
+                    '"' => {
+                        end = at + 1;
+                        break;
+                    }
+                    _ => text.push(c),
+                }
+            }
+            tokens.push(Token::Text(text));
+            rest = quoted[end..].trim_start();
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            tokens.push(Token::Word(&rest[..end]));
+            rest = rest[end..].trim_start();
+        }
+    }
+    tokens
+}
+
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    match line.split_once(';') {
+        Some((before, comment)) => (before, Some(comment)),
+        None => (line, None),
+    }
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+/// Reads an amount such as `-42.50 USD`, `$-42.50` or `1,250.00` as cents.
+fn parse_amount(text: &str) -> Result<i64, String> {
+    if text.contains(['@', '{']) {
+        return Err("prices and costs aren't supported".to_string());
+    }
+
+    let is_number = |c: char| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | ',');
+    let commodity: String = text
+        .chars()
+        .filter(|&c| !is_number(c) && !c.is_whitespace())
+        .collect();
+    if !commodity.is_empty() && commodity != "$" && commodity != COMMODITY {
+        return Err(format!("amounts in {commodity} aren't supported"));
+    }
+
+    let number: String = text.chars().filter(|&c| is_number(c) && c != ',').collect();
+    parse_cents(&number).ok_or_else(|| format!("couldn't read the amount `{text}`"))
+}
+
+fn parse_cents(number: &str) -> Option<i64> {
+    let (negative, digits) = match number.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, number.strip_prefix('+').unwrap_or(number)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let all_digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !all_digits(whole) || !all_digits(fraction) {
+        return None;
+    }
+    // Fractions of a cent can't be stored
+    if fraction.len() > 2 && fraction[2..].chars().any(|c| c != '0') {
+        return None;
+    }
+
+    let whole: i64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction = format!("{:0<2}", &fraction[..fraction.len().min(2)]);
+    let cents = whole
+        .checked_mul(100)?
+        .checked_add(fraction.parse().ok()?)?;
+    Some(if negative { -cents } else { cents })
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// An asset or liability account from a journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct JournalAccount {
+    /// Components under the root, such as `Chase:Checking`
+    name: String,
+    liability: bool,
+}
+
+/// What a journal account maps to in Tally.
+enum Side {
+    Account(JournalAccount),
+    /// Category path from the top level down; empty when uncategorized
+    Category(Vec<String>),
+}
+
+fn classify(name: &str) -> Result<Side, String> {
+    let mut components = name.split(':').map(str::trim).filter(|c| !c.is_empty());
+    let root = components.next().unwrap_or_default().to_ascii_lowercase();
+    let path: Vec<String> = components.map(String::from).collect();
+
+    match root.as_str() {
+        "assets" | "liabilities" if !path.is_empty() => Ok(Side::Account(JournalAccount {
+            name: path.join(":"),
+            liability: root == "liabilities",
+        })),
+        "expenses" | "income" | "revenue" | "revenues" => Ok(Side::Category(path)),
+        _ => Err(format!(
+            "`{name}` isn't an asset, liability, expense or income account"
+        )),
+    }
+}
+
+/// The records an importable entry turns into.
+enum Plan {
+    Transaction {
+        account: JournalAccount,
+        amount: i64,
+        category: Vec<String>,
+    },
+    Transfer {
+        from: JournalAccount,
+        to: JournalAccount,
+        amount: i64,
+    },
+}
+
+fn plan(entry: &ParsedEntry) -> Result<Plan, String> {
+    if let Some(reason) = &entry.unsupported {
+        return Err(reason.clone());
+    }
+    if entry.postings.len() != 2 {
+        return Err(format!(
+            "entries with {} postings aren't supported",
+            entry.postings.len()
+        ));
+    }
+
+    let total: i64 = entry
+        .postings
+        .iter()
+        .filter_map(|(_, amount)| *amount)
+        .sum();
+    let missing = entry
+        .postings
+        .iter()
+        .filter(|(_, amount)| amount.is_none())
+        .count();
+    let amounts: Vec<i64> = match missing {
+        0 if total != 0 => {
+            return Err(format!(
+                "the postings are off balance by {}",
+                export::format_cents(total)
+            ));
+        }
+        0 | 1 => entry
+            .postings
+            .iter()
+            .map(|(_, amount)| amount.unwrap_or(-total))
+            .collect(),
+        _ => return Err("more than one posting has no amount".to_string()),
+    };
+
+    match (
+        classify(&entry.postings[0].0)?,
+        classify(&entry.postings[1].0)?,
+    ) {
+        (Side::Account(first), Side::Account(second)) => {
+            if first == second {
+                return Err("both postings are to the same account".to_string());
+            }
+            // Money leaves the source of a transfer
+            let (from, to) = if amounts[0] <= 0 {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            Ok(Plan::Transfer {
+                from,
+                to,
+                amount: amounts[0].abs(),
+            })
+        }
+        (Side::Account(account), Side::Category(category)) => Ok(Plan::Transaction {
+            account,
+            amount: amounts[0],
+            category,
+        }),
+        (Side::Category(category), Side::Account(account)) => Ok(Plan::Transaction {
+            account,
+            amount: amounts[1],
+            category,
+        }),
+        (Side::Category(_), Side::Category(_)) => {
+            Err("entries between two expense or income accounts aren't supported".to_string())
+        }
+    }
+}
+
+/// Tally records found or created for journal names during one import.
+#[derive(Default)]
+struct Records {
+    accounts: HashMap<String, i64>,
+    categories: HashMap<Vec<String>, i64>,
+    tags: HashMap<String, i64>,
+}
+
+impl Records {
+    /// Says why an entry can't be imported when one of its accounts or
+    /// categories only exists archived.
+    async fn archived(
+        &self,
+        conn: &mut SqliteConnection,
+        plan: &Plan,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let (accounts, category) = match plan {
+            Plan::Transaction {
+                account, category, ..
+            } => (vec![account], category.as_slice()),
+            Plan::Transfer { from, to, .. } => (vec![from, to], [].as_slice()),
+        };
+
+        for account in accounts {
+            if self.archived_account(conn, account).await? {
+                return Ok(Some(format!("the account `{}` is archived", account.name)));
+            }
+        }
+        if let Some(name) = self.archived_category(conn, category).await? {
+            return Ok(Some(format!("the category `{name}` is archived")));
+        }
+
+        Ok(None)
+    }
+
+    /// Whether the account only exists archived.
+    async fn archived_account(
+        &self,
+        conn: &mut SqliteConnection,
+        account: &JournalAccount,
+    ) -> Result<bool, sqlx::Error> {
+        if self.accounts.contains_key(&account.name) {
+            return Ok(false);
+        }
+
+        let archived: Option<bool> = sqlx::query_scalar(
+            "SELECT archived FROM accounts WHERE name = ? ORDER BY archived, id LIMIT 1",
+        )
+        .bind(&account.name)
+        .fetch_optional(&mut *conn)
+        .await?;
+        Ok(archived == Some(true))
+    }
+
+    /// Returns the first level of the category path that only exists
+    /// archived, as a journal account name under the root.
+    async fn archived_category(
+        &self,
+        conn: &mut SqliteConnection,
+        path: &[String],
+    ) -> Result<Option<String>, sqlx::Error> {
+        let mut parent_id = None;
+        for depth in 1..=path.len() {
+            if let Some(id) = self.categories.get(&path[..depth]) {
+                parent_id = Some(*id);
+                continue;
+            }
+
+            let existing: Option<(i64, bool)> = sqlx::query_as(
+                "SELECT id, archived FROM categories WHERE name = ? AND parent_category_id IS ? ORDER BY archived, id LIMIT 1",
+            )
+            .bind(&path[depth - 1])
+            .bind(parent_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+            match existing {
+                // The rest of the path is created
+                None => break,
+                Some((_, true)) => return Ok(Some(path[..depth].join(":"))),
+                Some((id, false)) => parent_id = Some(id),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn account(
+        &mut self,
+        conn: &mut SqliteConnection,
+        change: &mut ChangeContext,
+        account: &JournalAccount,
+        import: &mut JournalImport,
+    ) -> Result<i64, sqlx::Error> {
+        if let Some(id) = self.accounts.get(&account.name) {
+            return Ok(*id);
+        }
+
+        let existing: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM accounts WHERE name = ? AND NOT archived ORDER BY id LIMIT 1",
+        )
+        .bind(&account.name)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                let account_type = if account.liability {
+                    "credit_card"
+                } else {
+                    "checking"
+                };
+                let result = sqlx::query("INSERT INTO accounts (name, type) VALUES (?, ?)")
+                    .bind(&account.name)
+                    .bind(account_type)
+                    .execute(&mut *conn)
+                    .await?;
+                let id = result.last_insert_rowid();
+                created(conn, change, EntityType::Account, id).await?;
+                import.accounts += 1;
+                id
+            }
+        };
+
+        self.accounts.insert(account.name.clone(), id);
+        Ok(id)
+    }
+
+    async fn category(
+        &mut self,
+        conn: &mut SqliteConnection,
+        change: &mut ChangeContext,
+        path: &[String],
+        import: &mut JournalImport,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        // Tally exports transactions without a category this way
+        if let [name] = path
+            && name.eq_ignore_ascii_case("uncategorized")
+        {
+            return Ok(None);
+        }
+
+        let mut parent_id = None;
+        for depth in 1..=path.len() {
+            let key = path[..depth].to_vec();
+            if let Some(id) = self.categories.get(&key) {
+                parent_id = Some(*id);
+                continue;
+            }
+
+            let name = &path[depth - 1];
+            let existing: Option<i64> = sqlx::query_scalar(
+                "SELECT id FROM categories WHERE name = ? AND parent_category_id IS ? AND NOT archived ORDER BY id LIMIT 1",
+            )
+            .bind(name)
+            .bind(parent_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+            let id = match existing {
+                Some(id) => id,
+                None => {
+                    let result = sqlx::query(
+                        "INSERT INTO categories (name, parent_category_id) VALUES (?, ?)",
+                    )
+                    .bind(name)
+                    .bind(parent_id)
+                    .execute(&mut *conn)
+                    .await?;
+                    let id = result.last_insert_rowid();
+                    created(conn, change, EntityType::Category, id).await?;
+                    import.categories += 1;
+                    id
+                }
+            };
+
+            self.categories.insert(key, id);
+            parent_id = Some(id);
+        }
+
+        Ok(parent_id)
+    }
+
+    async fn tag(
+        &mut self,
+        conn: &mut SqliteConnection,
+        change: &mut ChangeContext,
+        transaction_id: i64,
+        names: &[String],
+        import: &mut JournalImport,
+    ) -> Result<(), sqlx::Error> {
+        for name in names {
+            let tag_id = match self.tags.get(name) {
+                Some(id) => *id,
+                None => {
+                    let result =
+                        sqlx::query("INSERT INTO tags (name) VALUES (?) ON CONFLICT DO NOTHING")
+                            .bind(name)
+                            .execute(&mut *conn)
+                            .await?;
+                    import.tags += result.rows_affected() as usize;
+
+                    let id: i64 = sqlx::query_scalar("SELECT id FROM tags WHERE name = ?")
+                        .bind(name)
+                        .fetch_one(&mut *conn)
+                        .await?;
+                    if result.rows_affected() > 0 {
+                        created(conn, change, EntityType::Tag, id).await?;
+                    }
+                    self.tags.insert(name.clone(), id);
+                    id
+                }
+            };
+
+            sqlx::query(
+                "INSERT OR IGNORE INTO transaction_tags (transaction_id, tag_id) VALUES (?, ?)",
+            )
+            .bind(transaction_id)
+            .bind(tag_id)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn insert_entry(
+    conn: &mut SqliteConnection,
+    entry: &ParsedEntry,
+    account_id: i64,
+    amount: i64,
+    category_id: Option<i64>,
+    transaction_type: &str,
+) -> Result<i64, sqlx::Error> {
+    let reconciled = entry.flag("reconciled");
+    let result = sqlx::query(
+        r#"INSERT INTO transactions (account_id, date, amount, description, payee, memo, category_id, pending, cleared, reconciled, transaction_type, source)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'import')"#,
+    )
+    .bind(account_id)
+    .bind(entry.date)
+    .bind(amount)
+    .bind(&entry.narration)
+    .bind(&entry.payee)
+    .bind(entry.metadata.get("memo"))
+    .bind(category_id)
+    .bind(entry.status == Status::Pending || entry.flag("pending"))
+    .bind(entry.status == Status::Cleared || reconciled)
+    .bind(reconciled)
+    .bind(transaction_type)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
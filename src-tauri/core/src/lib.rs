@@ -0,0 +1,20 @@
+//! Core of the Tally personal finance application: the data model, SQLite
+//! storage and migrations, validation, backups, encryption, import and export,
+//! and reports.
+//!
+//! Nothing here depends on Tauri. The desktop app exposes these modules to the
+//! frontend as commands, and `tally-cli` drives them from the command line, so
+//! both read and write the database the same way.
+
+pub mod backup;
+pub mod database;
+pub mod encryption;
+pub mod events;
+pub mod export;
+pub mod ledger;
+pub mod reports;
+pub mod storage;
+pub mod validation;
+
+#[cfg(test)]
+mod tests;
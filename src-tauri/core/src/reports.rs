@@ -0,0 +1,255 @@
+use crate::database::{SpendingKind, cents_to_dollars};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::BTreeMap;
+
+/// Dimension a spending report totals transactions by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGrouping {
+    Category,
+    Tag,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingReportRequest {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub account_id: Option<i64>,
+    pub group_by: ReportGrouping,
+    /// Also count transactions of archived accounts, e.g. for past years
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingReportRow {
+    pub group_id: Option<i64>,
+    pub group_name: String,
+    pub total: f64,
+    pub transaction_count: i64,
+}
+
+/// Totals transaction amounts over a date range, grouped by category or tag.
+///
+/// Both ends of the date range are inclusive. Transactions without a category
+/// are reported under "Uncategorized" and transactions without tags under
+/// "Untagged", each with a `group_id` of `None`. Because a transaction can carry
+/// several tags, a tag report counts it once under every tag it has, so tag
+/// totals may add up to more than the period's spending.
+///
+/// Transactions of archived accounts are left out unless `include_archived` is
+/// set or the archived account is selected with `account_id`. Trashed
+/// transactions are never counted.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `request` - Date range, optional account filter and grouping dimension
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<SpendingReportRow>)` - One row per group, largest total first
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// let request = SpendingReportRequest {
+///     start_date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+///     end_date: NaiveDate::from_ymd_opt(2026, 12, 31).unwrap(),
+///     account_id: None,
+///     group_by: ReportGrouping::Tag,
+///     include_archived: true,
+/// };
+/// for row in get_spending_report(&pool, &request).await? {
+///     println!("{}: ${:.2} ({} transactions)", row.group_name, row.total, row.transaction_count);
+/// }
+/// ```
+pub async fn get_spending_report(
+    pool: &Pool<Sqlite>,
+    request: &SpendingReportRequest,
+) -> Result<Vec<SpendingReportRow>, sqlx::Error> {
+    let sql = match request.group_by {
+        ReportGrouping::Category => {
+            r#"
+                SELECT
+                    t.category_id AS group_id,
+                    COALESCE(c.name, 'Uncategorized') AS group_name,
+                    SUM(t.amount) AS total,
+                    COUNT(*) AS transaction_count
+                FROM transactions t
+                JOIN accounts a ON a.id = t.account_id
+                LEFT JOIN categories c ON c.id = t.category_id
+                WHERE t.date BETWEEN ? AND ?
+                  AND t.deleted_at IS NULL
+                  AND (? IS NULL OR t.account_id = ?)
+                  AND (? OR ? IS NOT NULL OR a.archived = FALSE)
+                GROUP BY t.category_id
+                ORDER BY total DESC, group_name
+            "#
+        }
+        ReportGrouping::Tag => {
+            r#"
+                SELECT
+                    tt.tag_id AS group_id,
+                    COALESCE(g.name, 'Untagged') AS group_name,
+                    SUM(t.amount) AS total,
+                    COUNT(*) AS transaction_count
+                FROM transactions t
+                JOIN accounts a ON a.id = t.account_id
+                LEFT JOIN transaction_tags tt ON tt.transaction_id = t.id
+                LEFT JOIN tags g ON g.id = tt.tag_id
+                WHERE t.date BETWEEN ? AND ?
+                  AND t.deleted_at IS NULL
+                  AND (? IS NULL OR t.account_id = ?)
+                  AND (? OR ? IS NOT NULL OR a.archived = FALSE)
+                GROUP BY tt.tag_id
+                ORDER BY total DESC, group_name
+            "#
+        }
+    };
+
+    let rows = sqlx::query(sql)
+        .bind(request.start_date)
+        .bind(request.end_date)
+        .bind(request.account_id)
+        .bind(request.account_id)
+        .bind(request.include_archived)
+        .bind(request.account_id)
+        .fetch_all(pool)
+        .await?;
+
+    let result = rows
+        .iter()
+        .map(|row| SpendingReportRow {
+            group_id: row.get("group_id"),
+            group_name: row.get("group_name"),
+            total: cents_to_dollars(row.get("total")),
+            transaction_count: row.get("transaction_count"),
+        })
+        .collect();
+
+    Ok(result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingSplitRequest {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub account_id: Option<i64>,
+    /// Also count transactions of archived accounts, e.g. for past years
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendingSplitRow {
+    /// First day of the month
+    pub month: NaiveDate,
+    pub fixed: f64,
+    pub discretionary: f64,
+    /// Spending whose category sets neither flag and that has no override
+    pub unclassified: f64,
+    pub total: f64,
+}
+
+/// Splits each month's spending into fixed and discretionary amounts.
+///
+/// A transaction's `spending_kind` decides where it is counted. Without one it
+/// inherits from its category: fixed if `default_fixed` is set, otherwise
+/// discretionary if `default_discretionary` is set. Everything else, including
+/// uncategorized transactions, is reported as unclassified.
+///
+/// The date range, account filter and archived-account handling are the same as
+/// for [`get_spending_report`]. Months without transactions are left out.
+///
+/// # Arguments
+///
+/// * `pool` - SQLite connection pool reference
+/// * `request` - Date range and optional account filter
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<SpendingSplitRow>)` - One row per month, oldest first
+/// - `Err(sqlx::Error)` - Database query error
+///
+/// # Examples
+///
+/// ```rust
+/// let request = SpendingSplitRequest {
+///     start_date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+///     end_date: NaiveDate::from_ymd_opt(2026, 12, 31).unwrap(),
+///     account_id: None,
+///     include_archived: false,
+/// };
+/// for row in get_spending_split_report(&pool, &request).await? {
+///     println!("{}: ${:.2} of ${:.2} is flexible", row.month, row.discretionary, row.total);
+/// }
+/// ```
+pub async fn get_spending_split_report(
+    pool: &Pool<Sqlite>,
+    request: &SpendingSplitRequest,
+) -> Result<Vec<SpendingSplitRow>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+            SELECT
+                date(t.date, 'start of month') AS month,
+                COALESCE(
+                    t.spending_kind,
+                    CASE
+                        WHEN c.default_fixed THEN 'fixed'
+                        WHEN c.default_discretionary THEN 'discretionary'
+                    END
+                ) AS kind,
+                SUM(t.amount) AS total
+            FROM transactions t
+            JOIN accounts a ON a.id = t.account_id
+            LEFT JOIN categories c ON c.id = t.category_id
+            WHERE t.date BETWEEN ? AND ?
+              AND t.deleted_at IS NULL
+              AND (? IS NULL OR t.account_id = ?)
+              AND (? OR ? IS NOT NULL OR a.archived = FALSE)
+            GROUP BY month, kind
+            ORDER BY month
+        "#,
+    )
+    .bind(request.start_date)
+    .bind(request.end_date)
+    .bind(request.account_id)
+    .bind(request.account_id)
+    .bind(request.include_archived)
+    .bind(request.account_id)
+    .fetch_all(pool)
+    .await?;
+
+    // Fixed, discretionary and unclassified cents per month
+    let mut months: BTreeMap<NaiveDate, [i64; 3]> = BTreeMap::new();
+    for row in &rows {
+        let kind = row.get::<Option<String>, _>("kind");
+        let index = match kind.as_deref().and_then(SpendingKind::parse) {
+            Some(SpendingKind::Fixed) => 0,
+            Some(SpendingKind::Discretionary) => 1,
+            None => 2,
+        };
+        months.entry(row.get("month")).or_default()[index] += row.get::<i64, _>("total");
+    }
+
+    let result = months
+        .into_iter()
+        .map(
+            |(month, [fixed, discretionary, unclassified])| SpendingSplitRow {
+                month,
+                fixed: cents_to_dollars(fixed),
+                discretionary: cents_to_dollars(discretionary),
+                unclassified: cents_to_dollars(unclassified),
+                total: cents_to_dollars(fixed + discretionary + unclassified),
+            },
+        )
+        .collect();
+
+    Ok(result)
+}
@@ -0,0 +1,915 @@
+use crate::database::{
+    self, Account, AccountBalance, Category, CreateAccountRequest, CreateCategoryRequest,
+    CreateTransactionRequest, CreateTransferRequest, DatabaseError, Transaction, Transfer,
+    UpdateTransactionRequest, cents_to_dollars, dollars_to_cents,
+};
+use crate::validation;
+use chrono::Utc;
+use sqlx::SqlitePool;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Foreign key message SQLite reports, repeated by [`MemoryStorage`].
+const FOREIGN_KEY_FAILED: &str = "FOREIGN KEY constraint failed";
+
+/// Storage for accounts, categories, transactions and transfers.
+///
+/// Business rules such as balances and transfers are written against this trait
+/// rather than SQL, so they behave the same on [`SqliteStorage`], which the app
+/// uses, and on [`MemoryStorage`] in unit tests. Reads only return active
+/// records: archived accounts and categories are left out unless asked for, and
+/// trashed transactions always are.
+///
+/// Both implementations validate requests and report a reference to a missing
+/// or archived record with the same `DatabaseError` variant.
+pub trait Storage: Send + Sync {
+    /// An active account by ID
+    fn account(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = Result<Option<Account>, DatabaseError>> + Send;
+
+    /// Active or archived accounts, by display order then name
+    fn accounts(
+        &self,
+        archived: bool,
+    ) -> impl Future<Output = Result<Vec<Account>, DatabaseError>> + Send;
+
+    /// Adds an account and returns its ID
+    fn insert_account(
+        &self,
+        request: &CreateAccountRequest,
+    ) -> impl Future<Output = Result<i64, DatabaseError>> + Send;
+
+    /// Replaces the fields of an active account
+    fn update_account(
+        &self,
+        id: i64,
+        request: &CreateAccountRequest,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// Archives an account, keeping its transactions
+    fn archive_account(&self, id: i64) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// Balances of the active accounts, or of just `account_id`, in account
+    /// order, summed in cents from the transactions outside the trash
+    fn balances(
+        &self,
+        account_id: Option<i64>,
+    ) -> impl Future<Output = Result<Vec<AccountBalance>, DatabaseError>> + Send;
+
+    /// An active category by ID
+    fn category(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = Result<Option<Category>, DatabaseError>> + Send;
+
+    /// Active or archived categories, by display order then name
+    fn categories(
+        &self,
+        archived: bool,
+    ) -> impl Future<Output = Result<Vec<Category>, DatabaseError>> + Send;
+
+    /// Adds a category under an optional active parent and returns its ID
+    fn insert_category(
+        &self,
+        request: &CreateCategoryRequest,
+    ) -> impl Future<Output = Result<i64, DatabaseError>> + Send;
+
+    /// Moves an active category, with its subcategories, under an active
+    /// parent other than itself or one of its descendants, or to the top level
+    fn move_category(
+        &self,
+        id: i64,
+        parent_id: Option<i64>,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// Archives a category; system categories are left as they are
+    fn archive_category(&self, id: i64) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// A transaction by ID, unless it is in the trash
+    fn transaction(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = Result<Option<Transaction>, DatabaseError>> + Send;
+
+    /// Every transaction of an account outside the trash, newest first
+    fn account_transactions(
+        &self,
+        account_id: i64,
+    ) -> impl Future<Output = Result<Vec<Transaction>, DatabaseError>> + Send;
+
+    /// Adds a transaction to an active account and returns its ID
+    fn insert_transaction(
+        &self,
+        request: &CreateTransactionRequest,
+    ) -> impl Future<Output = Result<i64, DatabaseError>> + Send;
+
+    /// Replaces the fields of a transaction outside the trash
+    fn update_transaction(
+        &self,
+        id: i64,
+        request: &UpdateTransactionRequest,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// Moves a transaction to the trash
+    fn delete_transaction(&self, id: i64)
+    -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// Every transfer, oldest first
+    fn transfers(&self) -> impl Future<Output = Result<Vec<Transfer>, DatabaseError>> + Send;
+
+    /// Adds both sides of an internal transfer and links them, all or nothing,
+    /// and returns the transfer's ID
+    fn insert_transfer(
+        &self,
+        withdrawal: &CreateTransactionRequest,
+        deposit: &CreateTransactionRequest,
+    ) -> impl Future<Output = Result<i64, DatabaseError>> + Send;
+}
+
+/// [`Storage`] on the app's SQLite database.
+///
+/// Every call goes through the matching `database` function, so changes are
+/// recorded in the change log and can be undone like any other edit.
+#[derive(Clone)]
+pub struct SqliteStorage {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+impl Storage for SqliteStorage {
+    async fn account(&self, id: i64) -> Result<Option<Account>, DatabaseError> {
+        Ok(database::get_account(&self.pool, id).await?)
+    }
+
+    async fn accounts(&self, archived: bool) -> Result<Vec<Account>, DatabaseError> {
+        let accounts = if archived {
+            database::get_archived_accounts(&self.pool).await?
+        } else {
+            database::get_accounts(&self.pool).await?
+        };
+        Ok(accounts)
+    }
+
+    async fn insert_account(&self, request: &CreateAccountRequest) -> Result<i64, DatabaseError> {
+        database::insert_account(&self.pool, request).await
+    }
+
+    async fn update_account(
+        &self,
+        id: i64,
+        request: &CreateAccountRequest,
+    ) -> Result<(), DatabaseError> {
+        database::update_account(&self.pool, id, request).await
+    }
+
+    async fn archive_account(&self, id: i64) -> Result<(), DatabaseError> {
+        database::archive_account(&self.pool, id).await
+    }
+
+    async fn balances(
+        &self,
+        account_id: Option<i64>,
+    ) -> Result<Vec<AccountBalance>, DatabaseError> {
+        Ok(database::get_account_balances(&self.pool, account_id).await?)
+    }
+
+    async fn category(&self, id: i64) -> Result<Option<Category>, DatabaseError> {
+        Ok(database::get_category(&self.pool, id).await?)
+    }
+
+    async fn categories(&self, archived: bool) -> Result<Vec<Category>, DatabaseError> {
+        let categories = if archived {
+            database::get_archived_categories(&self.pool).await?
+        } else {
+            database::get_categories(&self.pool).await?
+        };
+        Ok(categories)
+    }
+
+    async fn insert_category(&self, request: &CreateCategoryRequest) -> Result<i64, DatabaseError> {
+        database::insert_category(&self.pool, request).await
+    }
+
+    async fn move_category(&self, id: i64, parent_id: Option<i64>) -> Result<(), DatabaseError> {
+        database::move_category(&self.pool, id, parent_id).await
+    }
+
+    async fn archive_category(&self, id: i64) -> Result<(), DatabaseError> {
+        database::archive_category(&self.pool, id).await
+    }
+
+    async fn transaction(&self, id: i64) -> Result<Option<Transaction>, DatabaseError> {
+        Ok(database::get_transaction(&self.pool, id).await?)
+    }
+
+    async fn account_transactions(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<Transaction>, DatabaseError> {
+        Ok(database::get_account_transactions(&self.pool, account_id).await?)
+    }
+
+    async fn insert_transaction(
+        &self,
+        request: &CreateTransactionRequest,
+    ) -> Result<i64, DatabaseError> {
+        database::insert_transaction(&self.pool, request).await
+    }
+
+    async fn update_transaction(
+        &self,
+        id: i64,
+        request: &UpdateTransactionRequest,
+    ) -> Result<(), DatabaseError> {
+        database::update_transaction(&self.pool, id, request).await
+    }
+
+    async fn delete_transaction(&self, id: i64) -> Result<(), DatabaseError> {
+        database::delete_transaction(&self.pool, id).await
+    }
+
+    async fn transfers(&self) -> Result<Vec<Transfer>, DatabaseError> {
+        Ok(database::get_transfers(&self.pool).await?)
+    }
+
+    async fn insert_transfer(
+        &self,
+        withdrawal: &CreateTransactionRequest,
+        deposit: &CreateTransactionRequest,
+    ) -> Result<i64, DatabaseError> {
+        database::insert_transfer(&self.pool, withdrawal, deposit).await
+    }
+}
+
+/// [`Storage`] kept in memory, for fast unit tests of business rules.
+///
+/// Follows the same rules as the SQLite schema: requests are validated, IDs
+/// count up from 1, transactions can't reference a missing or archived account
+/// or category, and categorizing a transaction updates the category's usage.
+/// A new store holds only the "Uncategorized" system category, like a new
+/// database. Tag IDs on transactions are kept as given, since tags aren't part
+/// of this storage. Nothing is recorded for undo.
+pub struct MemoryStorage {
+    data: Mutex<MemoryData>,
+}
+
+#[derive(Default)]
+struct MemoryData {
+    accounts: BTreeMap<i64, Account>,
+    categories: BTreeMap<i64, Category>,
+    transactions: BTreeMap<i64, Transaction>,
+    trash: BTreeSet<i64>,
+    transfers: BTreeMap<i64, Transfer>,
+}
+
+/// The ID SQLite would give the next row of a table with these keys.
+fn next_id<T>(rows: &BTreeMap<i64, T>) -> i64 {
+    rows.keys().next_back().map_or(1, |id| id + 1)
+}
+
+/// Rounds dollars to whole cents, the way they come back out of SQLite.
+fn round_to_cents(dollars: f64) -> f64 {
+    cents_to_dollars(dollars_to_cents(dollars))
+}
+
+fn sorted_ids(ids: &[i64]) -> Vec<i64> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+impl MemoryData {
+    /// Checks the account and category of a transaction the way the schema's
+    /// foreign keys and triggers do.
+    fn check_references(
+        &self,
+        account_id: Option<i64>,
+        category_id: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        if let Some(account_id) = account_id {
+            match self.accounts.get(&account_id) {
+                None => {
+                    return Err(DatabaseError::ForeignKeyViolation(
+                        FOREIGN_KEY_FAILED.to_string(),
+                    ));
+                }
+                Some(account) if account.archived => {
+                    return Err(DatabaseError::InvariantViolation(
+                        "transactions can't be added to an archived account".to_string(),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(category_id) = category_id {
+            match self.categories.get(&category_id) {
+                None => {
+                    return Err(DatabaseError::ForeignKeyViolation(
+                        FOREIGN_KEY_FAILED.to_string(),
+                    ));
+                }
+                Some(category) if category.archived => {
+                    return Err(DatabaseError::InvariantViolation(
+                        "transactions can't be assigned to an archived category".to_string(),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `parent_id` can be the parent of `category_id`, which is
+    /// `None` for a new category, the way `database::move_category` does: the
+    /// parent must be active, and neither the category itself nor one of its
+    /// descendants, so the hierarchy never contains a cycle.
+    fn check_category_parent(
+        &self,
+        category_id: Option<i64>,
+        parent_id: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        let Some(parent_id) = parent_id else {
+            return Ok(());
+        };
+
+        if category_id == Some(parent_id) {
+            return Err(DatabaseError::Invalid(
+                "a category can't be its own parent".to_string(),
+            ));
+        }
+        if self
+            .categories
+            .get(&parent_id)
+            .is_none_or(|parent| parent.archived)
+        {
+            return Err(DatabaseError::NotFound {
+                entity: "category",
+                id: parent_id,
+            });
+        }
+
+        // Walk up from the new parent; reaching the category means it is a descendant
+        let mut ancestor = Some(parent_id);
+        while let Some(id) = ancestor {
+            if category_id == Some(id) {
+                return Err(DatabaseError::Invalid(
+                    "a category can't be moved under one of its own subcategories".to_string(),
+                ));
+            }
+            ancestor = self
+                .categories
+                .get(&id)
+                .and_then(|category| category.parent_category_id);
+        }
+        Ok(())
+    }
+
+    /// Adds a transaction whose references have been checked.
+    fn insert_transaction(&mut self, request: &CreateTransactionRequest) -> i64 {
+        let id = next_id(&self.transactions);
+        let transaction = Transaction {
+            id,
+            account_id: request.account_id,
+            date: request.date,
+            amount: round_to_cents(request.amount),
+            description: request.description.clone(),
+            category_id: request.category_id,
+            pending: request.pending,
+            transaction_type: "expense".to_string(),
+            created_at: Utc::now(),
+            reconciled: false,
+            import_id: None,
+            source: Some("manual".to_string()),
+            payee: request.payee.clone(),
+            original_description: None,
+            memo: request.memo.clone(),
+            spending_kind: request.spending_kind,
+            tag_ids: sorted_ids(request.tag_ids.as_deref().unwrap_or_default()),
+        };
+        self.transactions.insert(id, transaction);
+        self.recount_category_use(request.category_id);
+        id
+    }
+
+    /// Recounts a category's usage from the transactions not in the trash,
+    /// like the usage triggers.
+    fn recount_category_use(&mut self, category_id: Option<i64>) {
+        let Some(category_id) = category_id else {
+            return;
+        };
+        let (usage_count, last_used_date) = self
+            .transactions
+            .values()
+            .filter(|transaction| {
+                transaction.category_id == Some(category_id)
+                    && !self.trash.contains(&transaction.id)
+            })
+            .fold((0, None), |(count, last), transaction| {
+                (count + 1, last.max(Some(transaction.date)))
+            });
+        if let Some(category) = self.categories.get_mut(&category_id) {
+            category.usage_count = usage_count;
+            category.last_used_date = last_used_date;
+        }
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        let mut data = MemoryData::default();
+        data.categories.insert(
+            1,
+            Category {
+                id: 1,
+                name: "Uncategorized".to_string(),
+                archived: false,
+                created_at: Utc::now(),
+                display_order: Some(0),
+                parent_category_id: None,
+                default_discretionary: None,
+                default_fixed: None,
+                last_used_date: None,
+                usage_count: 0,
+                is_system_category: true,
+            },
+        );
+        Self {
+            data: Mutex::new(data),
+        }
+    }
+
+    fn data(&self) -> MutexGuard<'_, MemoryData> {
+        self.data.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Storage for MemoryStorage {
+    async fn account(&self, id: i64) -> Result<Option<Account>, DatabaseError> {
+        Ok(self
+            .data()
+            .accounts
+            .get(&id)
+            .filter(|account| !account.archived)
+            .cloned())
+    }
+
+    async fn accounts(&self, archived: bool) -> Result<Vec<Account>, DatabaseError> {
+        let mut accounts: Vec<Account> = self
+            .data()
+            .accounts
+            .values()
+            .filter(|account| account.archived == archived)
+            .cloned()
+            .collect();
+        accounts.sort_by(|a, b| (a.display_order, &a.name).cmp(&(b.display_order, &b.name)));
+        Ok(accounts)
+    }
+
+    async fn insert_account(&self, request: &CreateAccountRequest) -> Result<i64, DatabaseError> {
+        validation::validate_account(request).map_err(DatabaseError::Validation)?;
+
+        let mut data = self.data();
+        let id = next_id(&data.accounts);
+        let now = Utc::now();
+        data.accounts.insert(
+            id,
+            Account {
+                id,
+                name: request.name.clone(),
+                account_type: request.account_type.clone(),
+                created_at: now,
+                updated_at: now,
+                current_balance: request.current_balance.map(round_to_cents),
+                institution: request.institution.clone(),
+                display_order: request.display_order,
+                archived: false,
+                include_in_net_worth: request.include_in_net_worth.unwrap_or(true),
+                account_number_last4: request.account_number_last4.clone(),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn update_account(
+        &self,
+        id: i64,
+        request: &CreateAccountRequest,
+    ) -> Result<(), DatabaseError> {
+        validation::validate_account(request).map_err(DatabaseError::Validation)?;
+
+        let mut data = self.data();
+        if let Some(account) = data
+            .accounts
+            .get_mut(&id)
+            .filter(|account| !account.archived)
+        {
+            account.name = request.name.clone();
+            account.account_type = request.account_type.clone();
+            account.institution = request.institution.clone();
+            account.current_balance = request.current_balance.map(round_to_cents);
+            account.display_order = request.display_order;
+            account.include_in_net_worth = request.include_in_net_worth.unwrap_or(true);
+            account.account_number_last4 = request.account_number_last4.clone();
+            account.updated_at = Utc::now();
+        }
+        Ok(())
+    }
+
+    async fn archive_account(&self, id: i64) -> Result<(), DatabaseError> {
+        let mut data = self.data();
+        if let Some(account) = data
+            .accounts
+            .get_mut(&id)
+            .filter(|account| !account.archived)
+        {
+            account.archived = true;
+            account.updated_at = Utc::now();
+        }
+        Ok(())
+    }
+
+    async fn balances(
+        &self,
+        account_id: Option<i64>,
+    ) -> Result<Vec<AccountBalance>, DatabaseError> {
+        let data = self.data();
+        let mut accounts: Vec<&Account> = data
+            .accounts
+            .values()
+            .filter(|account| !account.archived && account_id.is_none_or(|id| id == account.id))
+            .collect();
+        accounts.sort_by(|a, b| (a.display_order, &a.name).cmp(&(b.display_order, &b.name)));
+
+        let mut balances: BTreeMap<i64, [i64; 3]> = accounts
+            .iter()
+            .map(|account| (account.id, [0; 3]))
+            .collect();
+        for transaction in data.transactions.values() {
+            if data.trash.contains(&transaction.id) {
+                continue;
+            }
+            if let Some([balance, pending, reconciled]) = balances.get_mut(&transaction.account_id)
+            {
+                let cents = dollars_to_cents(transaction.amount);
+                *balance += cents;
+                *pending += if transaction.pending { cents } else { 0 };
+                *reconciled += if transaction.reconciled { cents } else { 0 };
+            }
+        }
+
+        Ok(accounts
+            .iter()
+            .map(|account| {
+                let [balance, pending, reconciled] = balances[&account.id];
+                AccountBalance {
+                    account_id: account.id,
+                    balance: cents_to_dollars(balance),
+                    pending: cents_to_dollars(pending),
+                    reconciled: cents_to_dollars(reconciled),
+                }
+            })
+            .collect())
+    }
+
+    async fn category(&self, id: i64) -> Result<Option<Category>, DatabaseError> {
+        Ok(self
+            .data()
+            .categories
+            .get(&id)
+            .filter(|category| !category.archived)
+            .cloned())
+    }
+
+    async fn categories(&self, archived: bool) -> Result<Vec<Category>, DatabaseError> {
+        let mut categories: Vec<Category> = self
+            .data()
+            .categories
+            .values()
+            .filter(|category| category.archived == archived)
+            .cloned()
+            .collect();
+        categories.sort_by(|a, b| (a.display_order, &a.name).cmp(&(b.display_order, &b.name)));
+        Ok(categories)
+    }
+
+    async fn insert_category(&self, request: &CreateCategoryRequest) -> Result<i64, DatabaseError> {
+        validation::validate_category(request).map_err(DatabaseError::Validation)?;
+
+        let mut data = self.data();
+        data.check_category_parent(None, request.parent_category_id)?;
+
+        let id = next_id(&data.categories);
+        data.categories.insert(
+            id,
+            Category {
+                id,
+                name: request.name.clone(),
+                archived: false,
+                created_at: Utc::now(),
+                display_order: request.display_order,
+                parent_category_id: request.parent_category_id,
+                default_discretionary: request.default_discretionary,
+                default_fixed: request.default_fixed,
+                last_used_date: None,
+                usage_count: 0,
+                is_system_category: false,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn move_category(&self, id: i64, parent_id: Option<i64>) -> Result<(), DatabaseError> {
+        let mut data = self.data();
+        let Some(category) = data
+            .categories
+            .get(&id)
+            .filter(|category| !category.archived)
+        else {
+            return Err(DatabaseError::NotFound {
+                entity: "category",
+                id,
+            });
+        };
+        if category.is_system_category {
+            return Err(DatabaseError::Invalid(
+                "system categories can't be moved".to_string(),
+            ));
+        }
+        data.check_category_parent(Some(id), parent_id)?;
+
+        if let Some(category) = data.categories.get_mut(&id) {
+            category.parent_category_id = parent_id;
+        }
+        Ok(())
+    }
+
+    async fn archive_category(&self, id: i64) -> Result<(), DatabaseError> {
+        if let Some(category) = self.data().categories.get_mut(&id)
+            && !category.is_system_category
+        {
+            category.archived = true;
+        }
+        Ok(())
+    }
+
+    async fn transaction(&self, id: i64) -> Result<Option<Transaction>, DatabaseError> {
+        let data = self.data();
+        if data.trash.contains(&id) {
+            return Ok(None);
+        }
+        Ok(data.transactions.get(&id).cloned())
+    }
+
+    async fn account_transactions(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<Transaction>, DatabaseError> {
+        let data = self.data();
+        let mut transactions: Vec<Transaction> = data
+            .transactions
+            .values()
+            .filter(|transaction| {
+                transaction.account_id == account_id && !data.trash.contains(&transaction.id)
+            })
+            .cloned()
+            .collect();
+        transactions.sort_by_key(|transaction| Reverse((transaction.date, transaction.id)));
+        Ok(transactions)
+    }
+
+    async fn insert_transaction(
+        &self,
+        request: &CreateTransactionRequest,
+    ) -> Result<i64, DatabaseError> {
+        validation::validate_transaction(request).map_err(DatabaseError::Validation)?;
+
+        let mut data = self.data();
+        data.check_references(Some(request.account_id), request.category_id)?;
+        Ok(data.insert_transaction(request))
+    }
+
+    async fn update_transaction(
+        &self,
+        id: i64,
+        request: &UpdateTransactionRequest,
+    ) -> Result<(), DatabaseError> {
+        validation::validate_transaction_update(request).map_err(DatabaseError::Validation)?;
+
+        let mut data = self.data();
+        let Some(mut transaction) = data
+            .transactions
+            .get(&id)
+            .filter(|_| !data.trash.contains(&id))
+            .cloned()
+        else {
+            return Ok(());
+        };
+
+        let old_category_id = transaction.category_id;
+        if request.category_id != old_category_id {
+            data.check_references(None, request.category_id)?;
+        }
+
+        transaction.date = request.date;
+        transaction.amount = round_to_cents(request.amount);
+        transaction.description = request.description.clone();
+        transaction.payee = request.payee.clone();
+        transaction.memo = request.memo.clone();
+        transaction.category_id = request.category_id;
+        transaction.pending = request.pending;
+        transaction.spending_kind = request.spending_kind;
+        if let Some(tag_ids) = &request.tag_ids {
+            transaction.tag_ids = sorted_ids(tag_ids);
+        }
+
+        data.transactions.insert(id, transaction);
+        data.recount_category_use(old_category_id);
+        data.recount_category_use(request.category_id);
+        Ok(())
+    }
+
+    async fn delete_transaction(&self, id: i64) -> Result<(), DatabaseError> {
+        let mut data = self.data();
+        if let Some(transaction) = data.transactions.get(&id) {
+            let category_id = transaction.category_id;
+            data.trash.insert(id);
+            data.recount_category_use(category_id);
+        }
+        Ok(())
+    }
+
+    async fn transfers(&self) -> Result<Vec<Transfer>, DatabaseError> {
+        Ok(self.data().transfers.values().cloned().collect())
+    }
+
+    async fn insert_transfer(
+        &self,
+        withdrawal: &CreateTransactionRequest,
+        deposit: &CreateTransactionRequest,
+    ) -> Result<i64, DatabaseError> {
+        validation::validate_transaction(withdrawal).map_err(DatabaseError::Validation)?;
+        validation::validate_transaction(deposit).map_err(DatabaseError::Validation)?;
+
+        // Both sides are checked before either is added, so a failure leaves nothing behind
+        let mut data = self.data();
+        data.check_references(Some(withdrawal.account_id), withdrawal.category_id)?;
+        data.check_references(Some(deposit.account_id), deposit.category_id)?;
+
+        let from_transaction_id = data.insert_transaction(withdrawal);
+        let to_transaction_id = data.insert_transaction(deposit);
+        let id = next_id(&data.transfers);
+        data.transfers.insert(
+            id,
+            Transfer {
+                id,
+                from_transaction_id,
+                to_transaction_id,
+                transfer_type: Some("internal".to_string()),
+                auto_created: false,
+                created_at: Utc::now(),
+            },
+        );
+        Ok(id)
+    }
+}
+
+/// Works out the balance of an active account from its transactions.
+///
+/// Amounts are summed in cents, so the result is exact. The account's
+/// `current_balance` is what the user entered and isn't used.
+///
+/// # Arguments
+///
+/// * `storage` - Where the account and its transactions are kept
+/// * `account_id` - The account to total
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(AccountBalance)` - Total, pending and reconciled balance
+/// - `Err(DatabaseError::NotFound)` - The account doesn't exist or is archived
+/// - `Err(DatabaseError)` - Storage error
+///
+/// # Examples
+///
+/// ```rust
+/// let balance = account_balance(&SqliteStorage::new(pool), 1).await?;
+/// println!("{:.2} ({:.2} pending)", balance.balance, balance.pending);
+/// ```
+pub async fn account_balance<S: Storage>(
+    storage: &S,
+    account_id: i64,
+) -> Result<AccountBalance, DatabaseError> {
+    storage
+        .balances(Some(account_id))
+        .await?
+        .pop()
+        .ok_or(DatabaseError::NotFound {
+            entity: "account",
+            id: account_id,
+        })
+}
+
+/// Works out the balance of every active account, in account order.
+///
+/// # Arguments
+///
+/// * `storage` - Where the accounts and their transactions are kept
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<AccountBalance>)` - One balance per active account
+/// - `Err(DatabaseError)` - Storage error
+pub async fn account_balances<S: Storage>(
+    storage: &S,
+) -> Result<Vec<AccountBalance>, DatabaseError> {
+    storage.balances(None).await
+}
+
+async fn active_account<S: Storage>(storage: &S, id: i64) -> Result<Account, DatabaseError> {
+    storage.account(id).await?.ok_or(DatabaseError::NotFound {
+        entity: "account",
+        id,
+    })
+}
+
+/// Moves money between two active accounts.
+///
+/// Adds a withdrawal from one account and a matching deposit to the other, each
+/// with the other account as its payee, and links them as a transfer. Either
+/// everything is added or, if any step fails, nothing is.
+///
+/// # Arguments
+///
+/// * `storage` - Where the accounts and transactions are kept
+/// * `request` - The accounts, date and positive amount to move
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The ID of the new transfer
+/// - `Err(DatabaseError::Validation)` - Invalid fields, such as a non-positive
+///   amount or the same account on both sides
+/// - `Err(DatabaseError::NotFound)` - Either account doesn't exist or is archived
+/// - `Err(DatabaseError)` - Storage error
+///
+/// # Examples
+///
+/// ```rust
+/// let request = CreateTransferRequest {
+///     from_account_id: checking_id,
+///     to_account_id: savings_id,
+///     date: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+///     amount: 250.00,
+///     description: Some("Monthly savings".to_string()),
+///     memo: None,
+///     cleared: true,
+/// };
+/// let transfer_id = add_transfer(&storage, &request).await?;
+/// ```
+pub async fn add_transfer<S: Storage>(
+    storage: &S,
+    request: &CreateTransferRequest,
+) -> Result<i64, DatabaseError> {
+    validation::validate_transfer(request).map_err(DatabaseError::Validation)?;
+    let from = active_account(storage, request.from_account_id).await?;
+    let to = active_account(storage, request.to_account_id).await?;
+
+    let side = |account: &Account, other: &Account, amount: f64| CreateTransactionRequest {
+        account_id: account.id,
+        date: request.date,
+        amount,
+        description: request.description.clone(),
+        payee: Some(other.name.clone()),
+        memo: request.memo.clone(),
+        category_id: None,
+        pending: false,
+        cleared: request.cleared,
+        tag_ids: None,
+        spending_kind: None,
+    };
+
+    storage
+        .insert_transfer(
+            &side(&from, &to, -request.amount),
+            &side(&to, &from, request.amount),
+        )
+        .await
+}
@@ -0,0 +1,181 @@
+use super::database_tests::create_test_schema;
+use crate::backup::*;
+use crate::database::*;
+use sqlx::SqlitePool;
+use sqlx::sqlite::SqliteConnectOptions;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Creates an empty scratch directory for a test's database and backups
+pub(super) fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tally-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+async fn open_pool(path: &Path) -> SqlitePool {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .foreign_keys(true);
+    SqlitePool::connect_with(options).await.unwrap()
+}
+
+fn account(name: &str) -> CreateAccountRequest {
+    CreateAccountRequest {
+        name: name.to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    }
+}
+
+async fn account_names(pool: &SqlitePool) -> Vec<String> {
+    get_accounts(pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|account| account.name)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_backup_rotation_and_restore() {
+    let dir = test_dir("backup-restore");
+    let database = dir.join("tally.db");
+
+    let pool = open_pool(&database).await;
+    create_test_schema(&pool).await;
+    insert_account(&pool, &account("Checking")).await.unwrap();
+    let config = BackupConfig {
+        dir: dir.join("backups"),
+        retention: 3,
+    };
+    assert!(list_backups(&config).unwrap().is_empty());
+
+    let first = backup_on_startup(&pool, &config).await.unwrap().unwrap();
+    assert_eq!(first.reason, BackupReason::Startup);
+    assert!(first.size_bytes > 0);
+
+    insert_account(&pool, &account("Savings")).await.unwrap();
+
+    // Only the most recent backups are kept
+    for _ in 0..2 {
+        create_backup(&pool, &config, BackupReason::Manual)
+            .await
+            .unwrap();
+    }
+    let newest = create_backup(&pool, &config, BackupReason::BulkDelete)
+        .await
+        .unwrap();
+    let backups = list_backups(&config).unwrap();
+    assert_eq!(backups.len(), 3);
+    assert_eq!(backups[0].file_name, newest.file_name);
+    assert!(
+        backups
+            .iter()
+            .all(|backup| backup.file_name != first.file_name)
+    );
+    let oldest = backups.last().unwrap().clone();
+
+    // Invalid backups are refused and leave the database untouched
+    fs::write(
+        config.dir.join("tally-20200101T000000000000Z-manual.db"),
+        b"not a database",
+    )
+    .unwrap();
+    let error = restore_backup(
+        &pool,
+        &database,
+        &config,
+        "tally-20200101T000000000000Z-manual.db",
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(error, BackupError::Invalid(_)), "{error}");
+
+    let empty = config.dir.join("tally-20200101T000000000001Z-manual.db");
+    open_pool(&empty).await.close().await;
+    let error = restore_backup(
+        &pool,
+        &database,
+        &config,
+        "tally-20200101T000000000001Z-manual.db",
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid backup: missing the accounts table"
+    );
+
+    let error = restore_backup(&pool, &database, &config, "../tally.db", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, BackupError::Invalid(_)));
+    assert_eq!(account_names(&pool).await, ["Checking", "Savings"]);
+
+    // Restoring swaps the file in, after backing up what it replaces
+    create_backup(&pool, &config, BackupReason::Manual)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM accounts WHERE name = 'Savings'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let latest = list_backups(&config).unwrap()[0].file_name.clone();
+    let previous = restore_backup(&pool, &database, &config, &latest, None)
+        .await
+        .unwrap();
+    assert_eq!(previous.reason, BackupReason::Restore);
+    assert!(pool.is_closed());
+
+    let pool = open_pool(&database).await;
+    assert_eq!(account_names(&pool).await, ["Checking", "Savings"]);
+    pool.close().await;
+
+    // The backup taken before the restore holds the replaced database
+    validate_backup(&config.dir.join(&previous.file_name), None)
+        .await
+        .unwrap();
+    assert!(
+        list_backups(&config)
+            .unwrap()
+            .iter()
+            .all(|backup| backup.file_name != oldest.file_name)
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_no_startup_backup_for_new_database() {
+    let dir = test_dir("backup-new");
+    let pool = open_pool(&dir.join("tally.db")).await;
+    let config = BackupConfig {
+        dir: dir.join("backups"),
+        retention: 3,
+    };
+
+    assert!(backup_on_startup(&pool, &config).await.unwrap().is_none());
+    assert!(list_backups(&config).unwrap().is_empty());
+
+    pool.close().await;
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_parse_retention() {
+    assert_eq!(parse_retention("3"), Some(3));
+    assert_eq!(parse_retention(" 25\n"), Some(25));
+    // Keeping no backups would delete the one just taken
+    for value in ["0", "-1", "ten", ""] {
+        assert_eq!(parse_retention(value), None, "{value}");
+    }
+}
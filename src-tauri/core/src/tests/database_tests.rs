@@ -0,0 +1,2313 @@
+use crate::database::*;
+use chrono::{NaiveDate, TimeZone, Utc};
+use sqlx::SqlitePool;
+
+// Helper function to create a test database with all tables
+pub(super) async fn create_test_pool() -> SqlitePool {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_test_schema(&pool).await;
+    pool
+}
+
+// Creates all tables in an existing database, such as a file-backed one
+pub(super) async fn create_test_schema(pool: &SqlitePool) {
+    create_accounts_table(pool).await.unwrap();
+    create_categories_table(pool).await.unwrap();
+    create_transactions_table(pool).await.unwrap();
+    create_transactions_fts_table(pool).await.unwrap();
+    create_transfers_table(pool).await.unwrap();
+    create_tags_table(pool).await.unwrap();
+    create_attachments_table(pool).await.unwrap();
+    create_change_log_table(pool).await.unwrap();
+    seed_default_categories(pool).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_insert_account() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+
+    // Set up database schema
+    create_accounts_table(&pool).await.unwrap();
+
+    // Create test account request
+    let request = CreateAccountRequest {
+        name: "Test Checking Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: Some("Test Bank".to_string()),
+        current_balance: Some(1000.50),
+        display_order: Some(1),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("1234".to_string()),
+    };
+
+    // Insert account and verify we get an ID back
+    let account_id = insert_account(&pool, &request).await.unwrap();
+    assert!(account_id > 0);
+
+    // Verify the account was inserted correctly by fetching it
+    let account = get_account(&pool, account_id).await.unwrap().unwrap();
+
+    assert_eq!(account.name, "Test Checking Account");
+    assert_eq!(account.account_type, "checking");
+    assert_eq!(account.institution, Some("Test Bank".to_string()));
+    assert_eq!(account.current_balance, Some(1000.50));
+    assert_eq!(account.display_order, Some(1));
+    assert_eq!(account.include_in_net_worth, true);
+    assert_eq!(account.account_number_last4, Some("1234".to_string()));
+    assert_eq!(account.archived, false);
+}
+
+#[tokio::test]
+async fn test_get_account() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+
+    // Set up database schema
+    create_accounts_table(&pool).await.unwrap();
+
+    // Insert a test account first
+    let request = CreateAccountRequest {
+        name: "Savings Account".to_string(),
+        account_type: "savings".to_string(),
+        institution: Some("Credit Union".to_string()),
+        current_balance: Some(2500.75),
+        display_order: Some(2),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("5678".to_string()),
+    };
+    let account_id = insert_account(&pool, &request).await.unwrap();
+
+    // Test getting the account by ID
+    let account = get_account(&pool, account_id).await.unwrap().unwrap();
+
+    assert_eq!(account.id, account_id);
+    assert_eq!(account.name, "Savings Account");
+    assert_eq!(account.account_type, "savings");
+    assert_eq!(account.institution, Some("Credit Union".to_string()));
+    assert_eq!(account.current_balance, Some(2500.75));
+    assert_eq!(account.display_order, Some(2));
+    assert_eq!(account.include_in_net_worth, true);
+    assert_eq!(account.account_number_last4, Some("5678".to_string()));
+    assert_eq!(account.archived, false);
+
+    // Test getting a non-existent account
+    let non_existent = get_account(&pool, 99999).await.unwrap();
+    assert!(non_existent.is_none());
+}
+
+#[tokio::test]
+async fn test_get_accounts() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+
+    // Set up database schema
+    create_accounts_table(&pool).await.unwrap();
+
+    // Initially should have no accounts
+    let accounts = get_accounts(&pool).await.unwrap();
+    assert_eq!(accounts.len(), 0);
+
+    // Insert multiple test accounts
+    let request1 = CreateAccountRequest {
+        name: "Checking Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: Some("Bank A".to_string()),
+        current_balance: Some(1000.00),
+        display_order: Some(1),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("1111".to_string()),
+    };
+
+    let request2 = CreateAccountRequest {
+        name: "Savings Account".to_string(),
+        account_type: "savings".to_string(),
+        institution: Some("Bank B".to_string()),
+        current_balance: Some(5000.00),
+        display_order: Some(2),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("2222".to_string()),
+    };
+
+    let request3 = CreateAccountRequest {
+        name: "Credit Card".to_string(),
+        account_type: "credit_card".to_string(),
+        institution: Some("Bank C".to_string()),
+        current_balance: Some(-250.50),
+        display_order: Some(3),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("3333".to_string()),
+    };
+
+    insert_account(&pool, &request1).await.unwrap();
+    insert_account(&pool, &request2).await.unwrap();
+    insert_account(&pool, &request3).await.unwrap();
+
+    // Test getting all accounts
+    let accounts = get_accounts(&pool).await.unwrap();
+    assert_eq!(accounts.len(), 3);
+
+    // Verify accounts are ordered by display_order, then name
+    assert_eq!(accounts[0].name, "Checking Account");
+    assert_eq!(accounts[0].display_order, Some(1));
+
+    assert_eq!(accounts[1].name, "Savings Account");
+    assert_eq!(accounts[1].display_order, Some(2));
+
+    assert_eq!(accounts[2].name, "Credit Card");
+    assert_eq!(accounts[2].display_order, Some(3));
+
+    // Verify all accounts are non-archived
+    for account in &accounts {
+        assert_eq!(account.archived, false);
+    }
+}
+
+#[tokio::test]
+async fn test_update_account() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+
+    // Set up database schema
+    create_accounts_table(&pool).await.unwrap();
+
+    // Insert a test account first
+    let original_request = CreateAccountRequest {
+        name: "Original Name".to_string(),
+        account_type: "checking".to_string(),
+        institution: Some("Original Bank".to_string()),
+        current_balance: Some(1000.00),
+        display_order: Some(1),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("1234".to_string()),
+    };
+    let account_id = insert_account(&pool, &original_request).await.unwrap();
+
+    // Update the account with new values
+    let update_request = CreateAccountRequest {
+        name: "Updated Account Name".to_string(),
+        account_type: "savings".to_string(),
+        institution: Some("New Bank".to_string()),
+        current_balance: Some(2500.50),
+        display_order: Some(3),
+        include_in_net_worth: Some(false),
+        account_number_last4: Some("9876".to_string()),
+    };
+
+    // Perform the update
+    update_account(&pool, account_id, &update_request)
+        .await
+        .unwrap();
+
+    // Fetch the updated account and verify changes
+    let updated_account = get_account(&pool, account_id).await.unwrap().unwrap();
+
+    assert_eq!(updated_account.id, account_id); // ID should remain the same
+    assert_eq!(updated_account.name, "Updated Account Name");
+    assert_eq!(updated_account.account_type, "savings");
+    assert_eq!(updated_account.institution, Some("New Bank".to_string()));
+    assert_eq!(updated_account.current_balance, Some(2500.50));
+    assert_eq!(updated_account.display_order, Some(3));
+    assert_eq!(updated_account.include_in_net_worth, false);
+    assert_eq!(
+        updated_account.account_number_last4,
+        Some("9876".to_string())
+    );
+    assert_eq!(updated_account.archived, false); // Should still be false
+
+    // Test updating a non-existent account (should not error but have no effect)
+    let result = update_account(&pool, 99999, &update_request).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_archive_account() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+
+    // Set up database schema
+    create_accounts_table(&pool).await.unwrap();
+
+    // Insert test accounts
+    let request1 = CreateAccountRequest {
+        name: "Account to Archive".to_string(),
+        account_type: "checking".to_string(),
+        institution: Some("Test Bank".to_string()),
+        current_balance: Some(1000.00),
+        display_order: Some(1),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("1111".to_string()),
+    };
+
+    let request2 = CreateAccountRequest {
+        name: "Account to Keep".to_string(),
+        account_type: "savings".to_string(),
+        institution: Some("Test Bank".to_string()),
+        current_balance: Some(2000.00),
+        display_order: Some(2),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("2222".to_string()),
+    };
+
+    let account_id_1 = insert_account(&pool, &request1).await.unwrap();
+    let account_id_2 = insert_account(&pool, &request2).await.unwrap();
+
+    // Verify both accounts exist in get_accounts (non-archived)
+    let accounts_before = get_accounts(&pool).await.unwrap();
+    assert_eq!(accounts_before.len(), 2);
+
+    // Archive the first account
+    archive_account(&pool, account_id_1).await.unwrap();
+
+    // Verify get_accounts now only returns the non-archived account
+    let accounts_after = get_accounts(&pool).await.unwrap();
+    assert_eq!(accounts_after.len(), 1);
+    assert_eq!(accounts_after[0].name, "Account to Keep");
+
+    // Verify get_account returns None for archived account
+    let archived_account = get_account(&pool, account_id_1).await.unwrap();
+    assert!(archived_account.is_none());
+
+    // Verify the non-archived account is still accessible
+    let active_account = get_account(&pool, account_id_2).await.unwrap().unwrap();
+    assert_eq!(active_account.name, "Account to Keep");
+
+    // Test archiving a non-existent account (should not error but have no effect)
+    let result = archive_account(&pool, 99999).await;
+    assert!(result.is_ok());
+
+    // Test archiving an already archived account (should not error but have no effect)
+    let result = archive_account(&pool, account_id_1).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_insert_category() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+    
+    // Set up database schema
+    create_categories_table(&pool).await.unwrap();
+    
+    // Create test category request
+    let request = CreateCategoryRequest {
+        name: "Test Groceries".to_string(),
+        display_order: Some(10),
+        parent_category_id: None,
+        default_discretionary: Some(true),
+        default_fixed: Some(false),
+    };
+
+    // Insert category and verify we get an ID back
+    let category_id = insert_category(&pool, &request).await.unwrap();
+    assert!(category_id > 0);
+
+    // Verify the category was inserted correctly by fetching it
+    let category = get_category(&pool, category_id).await.unwrap().unwrap();
+    
+    assert_eq!(category.name, "Test Groceries");
+    assert_eq!(category.display_order, Some(10));
+    assert_eq!(category.parent_category_id, None);
+    assert_eq!(category.default_discretionary, Some(true));
+    assert_eq!(category.default_fixed, Some(false));
+    assert_eq!(category.archived, false);
+    assert_eq!(category.is_system_category, false);
+}
+
+#[tokio::test]
+async fn test_get_category() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+    
+    // Set up database schema
+    create_categories_table(&pool).await.unwrap();
+    seed_default_categories(&pool).await.unwrap();
+    
+    // Insert a test category first
+    let request = CreateCategoryRequest {
+        name: "Entertainment".to_string(),
+        display_order: Some(20),
+        parent_category_id: None,
+        default_discretionary: Some(true),
+        default_fixed: Some(false),
+    };
+    let category_id = insert_category(&pool, &request).await.unwrap();
+
+    // Test getting the category by ID
+    let category = get_category(&pool, category_id).await.unwrap().unwrap();
+    
+    assert_eq!(category.id, category_id);
+    assert_eq!(category.name, "Entertainment");
+    assert_eq!(category.display_order, Some(20));
+    assert_eq!(category.parent_category_id, None);
+    assert_eq!(category.default_discretionary, Some(true));
+    assert_eq!(category.default_fixed, Some(false));
+    assert_eq!(category.archived, false);
+    assert_eq!(category.is_system_category, false);
+
+    // Test getting a non-existent category
+    let non_existent = get_category(&pool, 99999).await.unwrap();
+    assert!(non_existent.is_none());
+
+    // Test getting the seeded system category
+    let system_categories = get_categories(&pool).await.unwrap();
+    let uncategorized = system_categories.iter().find(|c| c.name == "Uncategorized").unwrap();
+    let system_category = get_category(&pool, uncategorized.id).await.unwrap().unwrap();
+    assert_eq!(system_category.name, "Uncategorized");
+    assert_eq!(system_category.is_system_category, true);
+}
+
+#[tokio::test]
+async fn test_get_categories() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+    
+    // Set up database schema
+    create_categories_table(&pool).await.unwrap();
+    seed_default_categories(&pool).await.unwrap();
+    
+    // Initially should have one seeded category (Uncategorized)
+    let categories = get_categories(&pool).await.unwrap();
+    assert_eq!(categories.len(), 1);
+    assert_eq!(categories[0].name, "Uncategorized");
+    assert_eq!(categories[0].is_system_category, true);
+    
+    // Insert multiple test categories
+    let request1 = CreateCategoryRequest {
+        name: "Food".to_string(),
+        display_order: Some(1),
+        parent_category_id: None,
+        default_discretionary: Some(false),
+        default_fixed: Some(false),
+    };
+    
+    let request2 = CreateCategoryRequest {
+        name: "Transportation".to_string(),
+        display_order: Some(2),
+        parent_category_id: None,
+        default_discretionary: Some(false),
+        default_fixed: Some(true),
+    };
+
+    let request3 = CreateCategoryRequest {
+        name: "Entertainment".to_string(),
+        display_order: Some(3),
+        parent_category_id: None,
+        default_discretionary: Some(true),
+        default_fixed: Some(false),
+    };
+
+    insert_category(&pool, &request1).await.unwrap();
+    insert_category(&pool, &request2).await.unwrap();
+    insert_category(&pool, &request3).await.unwrap();
+
+    // Test getting all categories
+    let categories = get_categories(&pool).await.unwrap();
+    assert_eq!(categories.len(), 4); // 3 user + 1 system category
+    
+    // Verify categories are ordered by display_order, then name
+    // Uncategorized has display_order 0, so it should be first
+    assert_eq!(categories[0].name, "Uncategorized");
+    assert_eq!(categories[0].display_order, Some(0));
+    assert_eq!(categories[0].is_system_category, true);
+    
+    assert_eq!(categories[1].name, "Food");
+    assert_eq!(categories[1].display_order, Some(1));
+    
+    assert_eq!(categories[2].name, "Transportation");
+    assert_eq!(categories[2].display_order, Some(2));
+    
+    assert_eq!(categories[3].name, "Entertainment");
+    assert_eq!(categories[3].display_order, Some(3));
+
+    // Verify all categories are non-archived
+    for category in &categories {
+        assert_eq!(category.archived, false);
+    }
+}
+
+#[tokio::test]
+async fn test_update_category() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+    
+    // Set up database schema
+    create_categories_table(&pool).await.unwrap();
+    seed_default_categories(&pool).await.unwrap();
+    
+    // Insert a test category first
+    let original_request = CreateCategoryRequest {
+        name: "Original Category".to_string(),
+        display_order: Some(5),
+        parent_category_id: None,
+        default_discretionary: Some(false),
+        default_fixed: Some(false),
+    };
+    let category_id = insert_category(&pool, &original_request).await.unwrap();
+
+    // Update the category with new values
+    let update_request = CreateCategoryRequest {
+        name: "Updated Category Name".to_string(),
+        display_order: Some(15),
+        parent_category_id: None,
+        default_discretionary: Some(true),
+        default_fixed: Some(true),
+    };
+    
+    // Perform the update
+    update_category(&pool, category_id, &update_request).await.unwrap();
+
+    // Fetch the updated category and verify changes
+    let updated_category = get_category(&pool, category_id).await.unwrap().unwrap();
+    
+    assert_eq!(updated_category.id, category_id); // ID should remain the same
+    assert_eq!(updated_category.name, "Updated Category Name");
+    assert_eq!(updated_category.display_order, Some(15));
+    assert_eq!(updated_category.parent_category_id, None);
+    assert_eq!(updated_category.default_discretionary, Some(true));
+    assert_eq!(updated_category.default_fixed, Some(true));
+    assert_eq!(updated_category.archived, false); // Should still be false
+    assert_eq!(updated_category.is_system_category, false); // Should remain false
+
+    // Test that system categories cannot be updated
+    let categories = get_categories(&pool).await.unwrap();
+    let system_category = categories.iter().find(|c| c.is_system_category).unwrap();
+    let system_update = CreateCategoryRequest {
+        name: "Should Not Update".to_string(),
+        display_order: Some(99),
+        parent_category_id: None,
+        default_discretionary: Some(false),
+        default_fixed: Some(false),
+    };
+    
+    // This should succeed but have no effect (system category protection)
+    update_category(&pool, system_category.id, &system_update).await.unwrap();
+    
+    // Verify system category was not changed
+    let unchanged_system = get_category(&pool, system_category.id).await.unwrap().unwrap();
+    assert_eq!(unchanged_system.name, "Uncategorized");
+    assert_eq!(unchanged_system.display_order, Some(0));
+
+    // Test updating a non-existent category (should not error but have no effect)
+    let result = update_category(&pool, 99999, &update_request).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_archive_category() {
+    // Create in-memory database for testing
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+    
+    // Set up database schema
+    create_categories_table(&pool).await.unwrap();
+    seed_default_categories(&pool).await.unwrap();
+    
+    // Insert test categories
+    let request1 = CreateCategoryRequest {
+        name: "Category to Archive".to_string(),
+        display_order: Some(10),
+        parent_category_id: None,
+        default_discretionary: Some(true),
+        default_fixed: Some(false),
+    };
+    
+    let request2 = CreateCategoryRequest {
+        name: "Category to Keep".to_string(),
+        display_order: Some(20),
+        parent_category_id: None,
+        default_discretionary: Some(false),
+        default_fixed: Some(true),
+    };
+    
+    let category_id_1 = insert_category(&pool, &request1).await.unwrap();
+    let category_id_2 = insert_category(&pool, &request2).await.unwrap();
+
+    // Verify we have 3 categories (2 user + 1 system)
+    let categories_before = get_categories(&pool).await.unwrap();
+    assert_eq!(categories_before.len(), 3);
+
+    // Archive the first user category
+    archive_category(&pool, category_id_1).await.unwrap();
+
+    // Verify get_categories now only returns 2 categories (1 user + 1 system)
+    let categories_after = get_categories(&pool).await.unwrap();
+    assert_eq!(categories_after.len(), 2);
+    
+    // Verify the archived category is not in the list
+    assert!(!categories_after.iter().any(|c| c.name == "Category to Archive"));
+    
+    // Verify the non-archived category is still there
+    assert!(categories_after.iter().any(|c| c.name == "Category to Keep"));
+
+    // Verify get_category returns None for archived category
+    let archived_category = get_category(&pool, category_id_1).await.unwrap();
+    assert!(archived_category.is_none());
+
+    // Verify the non-archived category is still accessible
+    let active_category = get_category(&pool, category_id_2).await.unwrap().unwrap();
+    assert_eq!(active_category.name, "Category to Keep");
+
+    // Test that system categories cannot be archived
+    let system_categories = get_categories(&pool).await.unwrap();
+    let system_category = system_categories.iter().find(|c| c.is_system_category).unwrap();
+    
+    // This should succeed but have no effect (system category protection)
+    archive_category(&pool, system_category.id).await.unwrap();
+    
+    // Verify system category is still there
+    let categories_final = get_categories(&pool).await.unwrap();
+    assert!(categories_final.iter().any(|c| c.is_system_category && c.name == "Uncategorized"));
+
+    // Test archiving a non-existent category (should not error but have no effect)
+    let result = archive_category(&pool, 99999).await;
+    assert!(result.is_ok());
+
+    // Test archiving an already archived category (should not error but have no effect)
+    let result = archive_category(&pool, category_id_1).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_insert_transaction() {
+    let pool = create_test_pool().await;
+
+    // First, create an account for the transaction
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: Some("Test Bank".to_string()),
+        current_balance: Some(1000.0),
+        display_order: Some(1),
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("1234".to_string()),
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    // Create a category for the transaction
+    let category_request = CreateCategoryRequest {
+        name: "Test Category".to_string(),
+        display_order: Some(1),
+        parent_category_id: None,
+        default_discretionary: Some(true),
+        default_fixed: Some(false),
+    };
+    let category_id = insert_category(&pool, &category_request).await.unwrap();
+
+    // Create a transaction
+    let transaction_request = CreateTransactionRequest {
+        account_id,
+        date: "2024-01-15".parse().unwrap(),
+        amount: 25.50,
+        description: Some("Test transaction".to_string()),
+        payee: Some("Test Payee".to_string()),
+        memo: Some("Test memo".to_string()),
+        category_id: Some(category_id),
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+
+    let transaction_id = insert_transaction(&pool, &transaction_request).await.unwrap();
+    assert!(transaction_id > 0);
+
+    // Verify the transaction was inserted correctly
+    let transaction = get_transaction(&pool, transaction_id).await.unwrap();
+    assert!(transaction.is_some());
+    let transaction = transaction.unwrap();
+
+    assert_eq!(transaction.account_id, account_id);
+    assert_eq!(transaction.date.to_string(), "2024-01-15");
+    assert_eq!(transaction.amount, 25.50);
+    assert_eq!(transaction.description, Some("Test transaction".to_string()));
+    assert_eq!(transaction.payee, Some("Test Payee".to_string()));
+    assert_eq!(transaction.memo, Some("Test memo".to_string()));
+    assert_eq!(transaction.category_id, Some(category_id));
+    assert_eq!(transaction.pending, false);
+    assert_eq!(transaction.transaction_type, "expense");
+}
+
+#[tokio::test]
+async fn test_get_transactions() {
+    let pool = create_test_pool().await;
+
+    // Create an account
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: Some(1000.0),
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    // Create multiple transactions for the account
+    let transaction1_request = CreateTransactionRequest {
+        account_id,
+        date: "2024-01-15".parse().unwrap(),
+        amount: 25.50,
+        description: Some("First transaction".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let transaction2_request = CreateTransactionRequest {
+        account_id,
+        date: "2024-01-16".parse().unwrap(),
+        amount: 50.00,
+        description: Some("Second transaction".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: true,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+
+    let _tx1_id = insert_transaction(&pool, &transaction1_request).await.unwrap();
+    let _tx2_id = insert_transaction(&pool, &transaction2_request).await.unwrap();
+
+    // Test retrieving transactions
+    let page = get_transactions(&pool, account_id, 10, None).await.unwrap();
+    let transactions = page.transactions;
+    assert_eq!(transactions.len(), 2);
+    assert!(page.next_cursor.is_none());
+
+    // Should be ordered by date DESC, so second transaction comes first
+    assert_eq!(transactions[0].description, Some("Second transaction".to_string()));
+    assert_eq!(transactions[0].amount, 50.00);
+    assert_eq!(transactions[0].pending, true);
+
+    assert_eq!(transactions[1].description, Some("First transaction".to_string()));
+    assert_eq!(transactions[1].amount, 25.50);
+    assert_eq!(transactions[1].pending, false);
+
+    // Test pagination
+    let first_page = get_transactions(&pool, account_id, 1, None).await.unwrap();
+    assert_eq!(first_page.transactions.len(), 1);
+    assert_eq!(first_page.transactions[0].description, Some("Second transaction".to_string()));
+
+    let cursor = TransactionCursor::decode(first_page.next_cursor.as_deref().unwrap()).unwrap();
+    let second_page = get_transactions(&pool, account_id, 1, Some(&cursor)).await.unwrap();
+    assert_eq!(second_page.transactions.len(), 1);
+    assert_eq!(second_page.transactions[0].description, Some("First transaction".to_string()));
+    assert!(second_page.next_cursor.is_none());
+}
+
+#[tokio::test]
+async fn test_get_transactions_keyset_pagination() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Paging Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    // Several transactions share a date so the id tie-breaker matters
+    let dates = ["2024-03-01", "2024-03-02", "2024-03-02", "2024-03-02", "2024-03-03"];
+    for (i, date) in dates.iter().enumerate() {
+        let request = CreateTransactionRequest {
+            account_id,
+            date: date.parse().unwrap(),
+            amount: (i + 1) as f64,
+            description: Some(format!("Transaction {i}")),
+            payee: None,
+            memo: None,
+            category_id: None,
+            pending: false,
+            cleared: false,
+            tag_ids: None,
+            spending_kind: None,
+        };
+        insert_transaction(&pool, &request).await.unwrap();
+    }
+
+    let first_page = get_transactions(&pool, account_id, 2, None).await.unwrap();
+    let mut seen: Vec<i64> = first_page.transactions.iter().map(|t| t.id).collect();
+
+    // A new, newest transaction arrives while the user is scrolling
+    let late_request = CreateTransactionRequest {
+        account_id,
+        date: "2024-03-04".parse().unwrap(),
+        amount: 99.0,
+        description: Some("Late arrival".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    insert_transaction(&pool, &late_request).await.unwrap();
+
+    let mut cursor = first_page.next_cursor;
+    while let Some(encoded) = cursor {
+        let decoded = TransactionCursor::decode(&encoded).unwrap();
+        let page = get_transactions(&pool, account_id, 2, Some(&decoded)).await.unwrap();
+        seen.extend(page.transactions.iter().map(|t| t.id));
+        cursor = page.next_cursor;
+    }
+
+    // Every original row exactly once, in date DESC, id DESC order
+    assert_eq!(seen.len(), dates.len());
+    let all = get_transactions(&pool, account_id, 100, None).await.unwrap();
+    let expected: Vec<i64> = all
+        .transactions
+        .iter()
+        .filter(|t| t.description.as_deref() != Some("Late arrival"))
+        .map(|t| t.id)
+        .collect();
+    assert_eq!(seen, expected);
+}
+
+#[test]
+fn test_transaction_cursor_round_trip() {
+    let cursor = TransactionCursor {
+        date: "2024-01-15".parse().unwrap(),
+        id: 42,
+    };
+    let encoded = cursor.encode();
+    assert!(encoded.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_eq!(TransactionCursor::decode(&encoded), Some(cursor));
+
+    assert_eq!(TransactionCursor::decode("not a cursor"), None);
+    assert_eq!(TransactionCursor::decode("abc"), None);
+    assert_eq!(TransactionCursor::decode(""), None);
+}
+
+#[tokio::test]
+async fn test_seed_default_categories() {
+    let pool = create_test_pool().await;
+
+    // The create_test_pool already calls seed_default_categories, so let's verify it worked
+    let categories = get_categories(&pool).await.unwrap();
+    assert_eq!(categories.len(), 1);
+    
+    let uncategorized = &categories[0];
+    assert_eq!(uncategorized.name, "Uncategorized");
+    assert_eq!(uncategorized.is_system_category, true);
+    assert_eq!(uncategorized.archived, false);
+    assert_eq!(uncategorized.display_order, Some(0));
+
+    // Test that calling seed again doesn't create a duplicate
+    seed_default_categories(&pool).await.unwrap();
+    
+    let categories_after_second_call = get_categories(&pool).await.unwrap();
+    assert_eq!(categories_after_second_call.len(), 1);
+    assert_eq!(categories_after_second_call[0].id, uncategorized.id);
+
+    // Test on fresh database (without using create_test_pool)
+    let fresh_pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_categories_table(&fresh_pool).await.unwrap();
+    
+    // Should have no categories initially
+    let empty_categories = get_categories(&fresh_pool).await.unwrap();
+    assert_eq!(empty_categories.len(), 0);
+    
+    // Seed should create the Uncategorized category
+    seed_default_categories(&fresh_pool).await.unwrap();
+    
+    let seeded_categories = get_categories(&fresh_pool).await.unwrap();
+    assert_eq!(seeded_categories.len(), 1);
+    assert_eq!(seeded_categories[0].name, "Uncategorized");
+    assert_eq!(seeded_categories[0].is_system_category, true);
+}
+
+#[test]
+fn test_build_fts_query() {
+    assert_eq!(build_fts_query("coffee"), Some("\"coffee\"".to_string()));
+    assert_eq!(build_fts_query("groc*"), Some("\"groc\"*".to_string()));
+    assert_eq!(
+        build_fts_query("\"whole foods\" market"),
+        Some("\"whole foods\" \"market\"".to_string())
+    );
+
+    // FTS5 operators and punctuation are treated as plain text
+    assert_eq!(build_fts_query("AT&T OR"), Some("\"AT&T\" \"OR\"".to_string()));
+
+    // Nothing searchable
+    assert_eq!(build_fts_query("   "), None);
+    assert_eq!(build_fts_query("\"\" *"), None);
+}
+
+#[tokio::test]
+async fn test_search_transactions() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: Some(1000.0),
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let grocery_request = CreateTransactionRequest {
+        account_id,
+        date: "2024-01-15".parse().unwrap(),
+        amount: 82.40,
+        description: Some("Weekly groceries".to_string()),
+        payee: Some("Whole Foods Market".to_string()),
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let coffee_request = CreateTransactionRequest {
+        account_id,
+        date: "2024-01-16".parse().unwrap(),
+        amount: 4.75,
+        description: Some("Morning coffee".to_string()),
+        payee: Some("Blue Bottle".to_string()),
+        memo: Some("Grocery run afterwards".to_string()),
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let grocery_id = insert_transaction(&pool, &grocery_request).await.unwrap();
+    let coffee_id = insert_transaction(&pool, &coffee_request).await.unwrap();
+
+    let search = |query: &str| TransactionSearchRequest {
+        query: query.to_string(),
+        account_id: None,
+        tag_ids: None,
+        limit: None,
+    };
+
+    // Prefix query matches both, description match ranks above memo match
+    let results = search_transactions(&pool, &search("groc*")).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].transaction.id, grocery_id);
+    assert_eq!(results[1].transaction.id, coffee_id);
+    assert!(results[0].snippet.contains("<mark>groceries</mark>"));
+
+    // Phrase query requires the words to be adjacent
+    let results = search_transactions(&pool, &search("\"whole foods\"")).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].transaction.payee, Some("Whole Foods Market".to_string()));
+    let results = search_transactions(&pool, &search("\"foods whole\"")).await.unwrap();
+    assert!(results.is_empty());
+
+    // Account filter
+    let mut filtered = search("coffee");
+    filtered.account_id = Some(account_id + 1);
+    assert!(search_transactions(&pool, &filtered).await.unwrap().is_empty());
+
+    // Updates are reflected in the index
+    let update_request = UpdateTransactionRequest {
+        date: "2024-01-16".parse().unwrap(),
+        amount: 4.75,
+        description: Some("Afternoon tea".to_string()),
+        payee: Some("Blue Bottle".to_string()),
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    update_transaction(&pool, coffee_id, &update_request).await.unwrap();
+    assert!(search_transactions(&pool, &search("coffee")).await.unwrap().is_empty());
+    assert_eq!(search_transactions(&pool, &search("tea")).await.unwrap().len(), 1);
+
+    // Deleted transactions drop out of the index
+    delete_transaction(&pool, grocery_id).await.unwrap();
+    assert!(search_transactions(&pool, &search("groceries")).await.unwrap().is_empty());
+
+    // Empty queries return nothing rather than erroring
+    assert!(search_transactions(&pool, &search("  ")).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_create_transactions_fts_table_indexes_existing_rows() {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    create_accounts_table(&pool).await.unwrap();
+    create_categories_table(&pool).await.unwrap();
+    create_transactions_table(&pool).await.unwrap();
+    create_tags_table(&pool).await.unwrap();
+    create_change_log_table(&pool).await.unwrap();
+
+    let account_request = CreateAccountRequest {
+        name: "Legacy Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    // Written before the search index exists, as in an upgraded database
+    let transaction_request = CreateTransactionRequest {
+        account_id,
+        date: "2023-06-01".parse().unwrap(),
+        amount: 12.00,
+        description: Some("Hardware store".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    insert_transaction(&pool, &transaction_request).await.unwrap();
+
+    create_transactions_fts_table(&pool).await.unwrap();
+    // Creating again must not duplicate index entries
+    create_transactions_fts_table(&pool).await.unwrap();
+
+    let request = TransactionSearchRequest {
+        query: "hardware".to_string(),
+        account_id: Some(account_id),
+        tag_ids: None,
+        limit: Some(10),
+    };
+    let results = search_transactions(&pool, &request).await.unwrap();
+    assert_eq!(results.len(), 1);
+}
+
+#[tokio::test]
+async fn test_tags_crud() {
+    let pool = create_test_pool().await;
+
+    let vacation_id = insert_tag(&pool, &CreateTagRequest { name: "vacation-2026".to_string() })
+        .await
+        .unwrap();
+    let tax_id = insert_tag(&pool, &CreateTagRequest { name: " tax-deductible ".to_string() })
+        .await
+        .unwrap();
+
+    // Names are trimmed and ordered alphabetically
+    let tags = get_tags(&pool).await.unwrap();
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[0].name, "tax-deductible");
+    assert_eq!(tags[1].name, "vacation-2026");
+
+    // Names are unique regardless of case
+    let duplicate = insert_tag(&pool, &CreateTagRequest { name: "Vacation-2026".to_string() }).await;
+    assert!(duplicate.is_err());
+
+    update_tag(&pool, tax_id, &CreateTagRequest { name: "reimbursable".to_string() })
+        .await
+        .unwrap();
+    let tags = get_tags(&pool).await.unwrap();
+    assert_eq!(tags[0].id, tax_id);
+    assert_eq!(tags[0].name, "reimbursable");
+
+    delete_tag(&pool, vacation_id).await.unwrap();
+    let tags = get_tags(&pool).await.unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].id, tax_id);
+}
+
+#[tokio::test]
+async fn test_transaction_tags() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let vacation_id = insert_tag(&pool, &CreateTagRequest { name: "vacation-2026".to_string() })
+        .await
+        .unwrap();
+    let tax_id = insert_tag(&pool, &CreateTagRequest { name: "tax-deductible".to_string() })
+        .await
+        .unwrap();
+
+    let hotel_request = CreateTransactionRequest {
+        account_id,
+        date: "2026-07-01".parse().unwrap(),
+        amount: 240.00,
+        description: Some("Hotel".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: Some(vec![tax_id, vacation_id]),
+        spending_kind: None,
+    };
+    let hotel_id = insert_transaction(&pool, &hotel_request).await.unwrap();
+
+    let dinner_request = CreateTransactionRequest {
+        account_id,
+        date: "2026-07-02".parse().unwrap(),
+        amount: 60.00,
+        description: Some("Dinner".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: Some(vec![vacation_id]),
+        spending_kind: None,
+    };
+    let dinner_id = insert_transaction(&pool, &dinner_request).await.unwrap();
+
+    let hotel = get_transaction(&pool, hotel_id).await.unwrap().unwrap();
+    let mut expected = vec![tax_id, vacation_id];
+    expected.sort();
+    assert_eq!(hotel.tag_ids, expected);
+
+    // Updating without tag_ids leaves tags alone
+    let mut update_request = UpdateTransactionRequest {
+        date: "2026-07-02".parse().unwrap(),
+        amount: 65.00,
+        description: Some("Dinner".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    update_transaction(&pool, dinner_id, &update_request).await.unwrap();
+    let dinner = get_transaction(&pool, dinner_id).await.unwrap().unwrap();
+    assert_eq!(dinner.tag_ids, vec![vacation_id]);
+
+    // Updating with tag_ids replaces the set
+    update_request.tag_ids = Some(vec![tax_id]);
+    update_transaction(&pool, dinner_id, &update_request).await.unwrap();
+    let dinner = get_transaction(&pool, dinner_id).await.unwrap().unwrap();
+    assert_eq!(dinner.tag_ids, vec![tax_id]);
+
+    // Tag filter requires every listed tag
+    let search = |tag_ids: Vec<i64>| TransactionSearchRequest {
+        query: String::new(),
+        account_id: None,
+        tag_ids: Some(tag_ids),
+        limit: None,
+    };
+    let results = search_transactions(&pool, &search(vec![tax_id])).await.unwrap();
+    assert_eq!(results.len(), 2);
+    let results = search_transactions(&pool, &search(vec![tax_id, vacation_id])).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].transaction.id, hotel_id);
+
+    // Combined with text search
+    let mut text_and_tag = search(vec![tax_id]);
+    text_and_tag.query = "dinner".to_string();
+    let results = search_transactions(&pool, &text_and_tag).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].transaction.id, dinner_id);
+
+    // Bulk tag skips transactions that already have the tag and unknown IDs
+    let tagged = tag_transactions(&pool, &[hotel_id, dinner_id, 99999], vacation_id)
+        .await
+        .unwrap();
+    assert_eq!(tagged, 1);
+    let untagged = untag_transactions(&pool, &[hotel_id, dinner_id], tax_id)
+        .await
+        .unwrap();
+    assert_eq!(untagged, 2);
+    assert!(search_transactions(&pool, &search(vec![tax_id])).await.unwrap().is_empty());
+
+    // Deleting a transaction or a tag removes its associations
+    delete_transaction(&pool, hotel_id).await.unwrap();
+    delete_tag(&pool, vacation_id).await.unwrap();
+    let dinner = get_transaction(&pool, dinner_id).await.unwrap().unwrap();
+    assert!(dinner.tag_ids.is_empty());
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transaction_tags")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
+
+#[test]
+fn test_guess_mime_type() {
+    assert_eq!(guess_mime_type("receipt.pdf"), "application/pdf");
+    assert_eq!(guess_mime_type("IMG_0042.JPG"), "image/jpeg");
+    assert_eq!(guess_mime_type("scan.png"), "image/png");
+    assert_eq!(guess_mime_type("warranty"), "application/octet-stream");
+}
+
+#[tokio::test]
+async fn test_attachments() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let transaction_request = CreateTransactionRequest {
+        account_id,
+        date: "2026-03-14".parse().unwrap(),
+        amount: 1299.00,
+        description: Some("New laptop".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let transaction_id = insert_transaction(&pool, &transaction_request).await.unwrap();
+
+    let receipt = CreateAttachmentRequest {
+        transaction_id,
+        file_name: "receipt.pdf".to_string(),
+        mime_type: None,
+        data: b"%PDF-1.4 receipt".to_vec(),
+    };
+    let warranty = CreateAttachmentRequest {
+        transaction_id,
+        file_name: "warranty".to_string(),
+        mime_type: Some("text/plain".to_string()),
+        data: b"abc".to_vec(),
+    };
+    let receipt_id = insert_attachment(&pool, &receipt).await.unwrap();
+    let warranty_id = insert_attachment(&pool, &warranty).await.unwrap();
+
+    let attachments = get_attachments(&pool, transaction_id).await.unwrap();
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[0].id, receipt_id);
+    assert_eq!(attachments[0].file_name, "receipt.pdf");
+    assert_eq!(attachments[0].mime_type, "application/pdf");
+    assert_eq!(attachments[0].size_bytes, 16);
+    assert_eq!(attachments[1].mime_type, "text/plain");
+    // SHA-256 of "abc"
+    assert_eq!(
+        attachments[1].sha256,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+
+    let data = get_attachment_data(&pool, receipt_id).await.unwrap().unwrap();
+    assert_eq!(data, b"%PDF-1.4 receipt".to_vec());
+    assert!(get_attachment_data(&pool, 99999).await.unwrap().is_none());
+
+    // Attaching to a missing transaction fails
+    let orphan = CreateAttachmentRequest {
+        transaction_id: 99999,
+        ..receipt.clone()
+    };
+    assert!(insert_attachment(&pool, &orphan).await.is_err());
+
+    delete_attachment(&pool, warranty_id).await.unwrap();
+    assert_eq!(get_attachments(&pool, transaction_id).await.unwrap().len(), 1);
+
+    // Trashing the transaction keeps its attachments, purging it removes them
+    delete_transaction(&pool, transaction_id).await.unwrap();
+    assert_eq!(get_attachments(&pool, transaction_id).await.unwrap().len(), 1);
+
+    purge_trash(&pool, 0, ChangeSource::Manual).await.unwrap();
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM attachments")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
+
+#[tokio::test]
+async fn test_change_history() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: Some(100.0),
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let transaction_request = CreateTransactionRequest {
+        account_id,
+        date: "2026-07-01".parse().unwrap(),
+        amount: -42.50,
+        description: Some("Grocery Store".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let transaction_id = insert_transaction(&pool, &transaction_request).await.unwrap();
+
+    let update_request = UpdateTransactionRequest {
+        date: "2026-07-01".parse().unwrap(),
+        amount: -45.00,
+        description: Some("Grocery Store".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    update_transaction(&pool, transaction_id, &update_request).await.unwrap();
+    delete_transaction(&pool, transaction_id).await.unwrap();
+
+    let history = get_change_history(&pool, EntityType::Transaction, transaction_id).await.unwrap();
+    let actions: Vec<&str> = history.iter().map(|entry| entry.action.as_str()).collect();
+    assert_eq!(actions, vec!["create", "update", "delete"]);
+    assert!(history.iter().all(|entry| entry.source == "manual"));
+
+    // Snapshots hold the full record before and after, amounts in cents
+    assert!(history[0].before.is_none());
+    assert_eq!(history[0].after.as_ref().unwrap()["amount"], -4250);
+    assert_eq!(history[1].before.as_ref().unwrap()["amount"], -4250);
+    assert_eq!(history[1].after.as_ref().unwrap()["amount"], -4500);
+    assert_eq!(history[2].before.as_ref().unwrap()["description"], "Grocery Store");
+    assert!(history[2].before.as_ref().unwrap()["deleted_at"].is_null());
+    assert!(history[2].after.as_ref().unwrap()["deleted_at"].is_string());
+
+    // Archiving an already archived account changes nothing and isn't logged
+    archive_account(&pool, account_id).await.unwrap();
+    archive_account(&pool, account_id).await.unwrap();
+    let history = get_change_history(&pool, EntityType::Account, account_id).await.unwrap();
+    let actions: Vec<&str> = history.iter().map(|entry| entry.action.as_str()).collect();
+    assert_eq!(actions, vec!["create", "archive"]);
+
+    // The log is append-only
+    assert!(sqlx::query("UPDATE change_log SET source = 'import'").execute(&pool).await.is_err());
+    assert!(sqlx::query("DELETE FROM change_log").execute(&pool).await.is_err());
+}
+
+#[tokio::test]
+async fn test_undo_redo() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+    let tag_id = insert_tag(&pool, &CreateTagRequest { name: "groceries".to_string() })
+        .await
+        .unwrap();
+
+    let transaction_request = CreateTransactionRequest {
+        account_id,
+        date: "2026-07-01".parse().unwrap(),
+        amount: -42.50,
+        description: Some("Grocery Store".to_string()),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: false,
+        tag_ids: Some(vec![tag_id]),
+        spending_kind: None,
+    };
+    let transaction_id = insert_transaction(&pool, &transaction_request).await.unwrap();
+
+    // Undo a delete: the transaction comes back with its tags
+    delete_transaction(&pool, transaction_id).await.unwrap();
+    assert!(get_transaction(&pool, transaction_id).await.unwrap().is_none());
+
+    let undone = undo(&pool, 1).await.unwrap();
+    assert_eq!(undone.len(), 1);
+    assert_eq!(undone[0].label, "Delete transaction");
+    let restored = get_transaction(&pool, transaction_id).await.unwrap().unwrap();
+    assert_eq!(restored.amount, -42.50);
+    assert_eq!(restored.tag_ids, vec![tag_id]);
+
+    // Redo deletes it again, then undo once more
+    redo(&pool, 1).await.unwrap();
+    assert!(get_transaction(&pool, transaction_id).await.unwrap().is_none());
+    undo(&pool, 1).await.unwrap();
+
+    // Undo several operations at once, newest first
+    update_account(
+        &pool,
+        account_id,
+        &CreateAccountRequest { name: "Renamed".to_string(), ..account_request.clone() },
+    )
+    .await
+    .unwrap();
+    archive_account(&pool, account_id).await.unwrap();
+
+    let undone = undo(&pool, 2).await.unwrap();
+    let labels: Vec<&str> = undone.iter().map(|op| op.label.as_str()).collect();
+    assert_eq!(labels, vec!["Archive account", "Update account"]);
+    let account = get_account(&pool, account_id).await.unwrap().unwrap();
+    assert_eq!(account.name, "Test Account");
+    assert!(!account.archived);
+
+    let stack = get_undo_stack(&pool).await.unwrap();
+    assert_eq!(stack.redo.len(), 2);
+    assert_eq!(stack.redo[0].label, "Update account");
+    assert_eq!(stack.undo[0].label, "Add transaction");
+
+    // A new change clears the redo stack
+    update_tag(&pool, tag_id, &CreateTagRequest { name: "food".to_string() })
+        .await
+        .unwrap();
+    let stack = get_undo_stack(&pool).await.unwrap();
+    assert!(stack.redo.is_empty());
+    assert!(redo(&pool, 1).await.unwrap().is_empty());
+
+    // A transfer is one operation: both sides and the link go and come back together
+    let savings_id = insert_account(&pool, &CreateAccountRequest { name: "Savings".to_string(), ..account_request.clone() }).await.unwrap();
+    let withdrawal = CreateTransactionRequest { amount: -100.00, tag_ids: None, ..transaction_request.clone() };
+    let deposit = CreateTransactionRequest { account_id: savings_id, amount: 100.00, ..withdrawal.clone() };
+    let transfer_id = insert_transfer(&pool, &withdrawal, &deposit).await.unwrap();
+    let transfer = get_transfers(&pool).await.unwrap().pop().unwrap();
+    assert_eq!(transfer.id, transfer_id);
+
+    let undone = undo(&pool, 1).await.unwrap();
+    assert_eq!(undone[0].label, "Add transfer");
+    assert!(get_transfers(&pool).await.unwrap().is_empty());
+    assert!(get_transaction(&pool, transfer.from_transaction_id).await.unwrap().is_none());
+    assert!(get_transaction(&pool, transfer.to_transaction_id).await.unwrap().is_none());
+
+    redo(&pool, 1).await.unwrap();
+    assert_eq!(get_transfers(&pool).await.unwrap().len(), 1);
+    assert_eq!(get_transaction(&pool, transfer.to_transaction_id).await.unwrap().unwrap().amount, 100.00);
+
+    // A transfer that fails halfway adds nothing
+    let error = insert_transfer(&pool, &withdrawal, &CreateTransactionRequest { account_id: 99, ..deposit.clone() }).await.unwrap_err();
+    assert!(matches!(error, DatabaseError::ForeignKeyViolation(_)), "{error}");
+    assert_eq!(get_transfers(&pool).await.unwrap().len(), 1);
+    assert_eq!(get_account_transactions(&pool, account_id).await.unwrap().len(), 2);
+
+    // Undoing everything removes the records that were added
+    undo(&pool, 10).await.unwrap();
+    assert!(get_transaction(&pool, transaction_id).await.unwrap().is_none());
+    assert!(get_account(&pool, account_id).await.unwrap().is_none());
+    assert!(get_tags(&pool).await.unwrap().is_empty());
+    assert!(get_transfers(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_trash() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let mut ids = Vec::new();
+    for (date, description) in [("2026-07-01", "Coffee Shop"), ("2026-07-02", "Coffee Beans")] {
+        let request = CreateTransactionRequest {
+            account_id,
+            date: date.parse().unwrap(),
+            amount: -5.00,
+            description: Some(description.to_string()),
+            payee: None,
+            memo: None,
+            category_id: None,
+            pending: false,
+            cleared: false,
+            tag_ids: None,
+            spending_kind: None,
+        };
+        ids.push(insert_transaction(&pool, &request).await.unwrap());
+    }
+
+    sqlx::query("INSERT INTO transfers (from_transaction_id, to_transaction_id) VALUES (?, ?)")
+        .bind(ids[0])
+        .bind(ids[1])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Trashed transactions are hidden from every read path
+    delete_transaction(&pool, ids[0]).await.unwrap();
+    assert!(get_transaction(&pool, ids[0]).await.unwrap().is_none());
+    let page = get_transactions(&pool, account_id, 10, None).await.unwrap();
+    assert_eq!(page.transactions.len(), 1);
+    let search = TransactionSearchRequest {
+        query: "coffee".to_string(),
+        account_id: None,
+        tag_ids: None,
+        limit: None,
+    };
+    assert_eq!(search_transactions(&pool, &search).await.unwrap().len(), 1);
+
+    let trash = get_trash(&pool).await.unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].transaction.id, ids[0]);
+
+    restore_transaction(&pool, ids[0]).await.unwrap();
+    assert!(get_transaction(&pool, ids[0]).await.unwrap().is_some());
+    assert!(get_trash(&pool).await.unwrap().is_empty());
+
+    // Only transactions older than the retention period are purged
+    delete_transaction(&pool, ids[0]).await.unwrap();
+    assert_eq!(purge_trash(&pool, 30, ChangeSource::Schedule).await.unwrap(), 0);
+    sqlx::query("UPDATE transactions SET deleted_at = datetime('now', '-31 days') WHERE id = ?")
+        .bind(ids[0])
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(purge_trash(&pool, 30, ChangeSource::Schedule).await.unwrap(), 1);
+    assert!(get_trash(&pool).await.unwrap().is_empty());
+
+    // The transfer link to the purged transaction goes with it
+    let transfers: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transfers")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(transfers, 0);
+    let history = get_change_history(&pool, EntityType::Transfer, 1).await.unwrap();
+    assert_eq!(history.last().unwrap().action, "purge");
+
+    let history = get_change_history(&pool, EntityType::Transaction, ids[0]).await.unwrap();
+    let last = history.last().unwrap();
+    assert_eq!(last.action, "purge");
+    assert_eq!(last.source, "schedule");
+    assert!(last.after.is_none());
+}
+
+#[tokio::test]
+async fn test_purge_cant_be_undone() {
+    let pool = create_test_pool().await;
+    let account_request = CreateAccountRequest {
+        name: "Checking".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: None,
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+    let transaction_id = insert_transaction(
+        &pool,
+        &CreateTransactionRequest {
+            account_id,
+            date: "2026-07-01".parse().unwrap(),
+            amount: -4.50,
+            description: Some("Coffee".to_string()),
+            payee: None,
+            memo: None,
+            category_id: None,
+            pending: false,
+            cleared: false,
+            tag_ids: None,
+            spending_kind: None,
+        },
+    )
+    .await
+    .unwrap();
+    delete_transaction(&pool, transaction_id).await.unwrap();
+    purge_trash(&pool, 0, ChangeSource::Manual).await.unwrap();
+
+    // The operations that added and deleted the transaction are gone, so
+    // undo moves on to the account instead of bringing the transaction back
+    let undone = undo(&pool, 1).await.unwrap();
+    assert_eq!(undone[0].label, "Add account");
+    assert!(get_transaction(&pool, transaction_id).await.unwrap().is_none());
+    assert!(get_trash(&pool).await.unwrap().is_empty());
+
+    redo(&pool, 1).await.unwrap();
+    assert!(get_transaction(&pool, transaction_id).await.unwrap().is_none());
+    assert!(get_undo_stack(&pool).await.unwrap().redo.is_empty());
+}
+
+#[tokio::test]
+async fn test_bulk_update_transactions() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Checking".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let checking_id = insert_account(&pool, &account_request).await.unwrap();
+    let savings_id = insert_account(
+        &pool,
+        &CreateAccountRequest { name: "Savings".to_string(), ..account_request.clone() },
+    )
+    .await
+    .unwrap();
+
+    let category_request = CreateCategoryRequest {
+        name: "Shopping".to_string(),
+        display_order: None,
+        parent_category_id: None,
+        default_discretionary: None,
+        default_fixed: None,
+    };
+    let shopping_id = insert_category(&pool, &category_request).await.unwrap();
+
+    let mut ids = Vec::new();
+    for description in ["Amazon Order", "Amazon Prime", "Gas Station"] {
+        let request = CreateTransactionRequest {
+            account_id: checking_id,
+            date: "2026-07-01".parse().unwrap(),
+            amount: -20.00,
+            description: Some(description.to_string()),
+            payee: None,
+            memo: None,
+            category_id: None,
+            pending: false,
+            cleared: false,
+            tag_ids: None,
+            spending_kind: None,
+        };
+        ids.push(insert_transaction(&pool, &request).await.unwrap());
+    }
+
+    let amazon = BulkTarget::Filter(TransactionSearchRequest {
+        query: "amazon".to_string(),
+        account_id: None,
+        tag_ids: None,
+        limit: None,
+    });
+
+    // A filter targets every match; unchanged rows aren't counted a second time
+    let set_category = BulkChange::SetCategory { category_id: Some(shopping_id) };
+    assert_eq!(bulk_update_transactions(&pool, &amazon, &set_category).await.unwrap(), 2);
+    assert_eq!(bulk_update_transactions(&pool, &amazon, &set_category).await.unwrap(), 0);
+    let gas = get_transaction(&pool, ids[2]).await.unwrap().unwrap();
+    assert_eq!(gas.category_id, None);
+
+    let set_payee = BulkChange::SetPayee { payee: Some("Amazon".to_string()) };
+    assert_eq!(bulk_update_transactions(&pool, &amazon, &set_payee).await.unwrap(), 2);
+    let order = get_transaction(&pool, ids[0]).await.unwrap().unwrap();
+    assert_eq!(order.payee.as_deref(), Some("Amazon"));
+
+    let everything = BulkTarget::Ids(ids.clone());
+    let cleared = BulkChange::SetCleared { cleared: true };
+    assert_eq!(bulk_update_transactions(&pool, &everything, &cleared).await.unwrap(), 3);
+
+    // Moving is all or nothing: an unknown account changes no rows
+    let missing_account = BulkChange::MoveToAccount { account_id: 999 };
+    assert!(bulk_update_transactions(&pool, &everything, &missing_account).await.is_err());
+    let move_to_savings = BulkChange::MoveToAccount { account_id: savings_id };
+    assert_eq!(bulk_update_transactions(&pool, &amazon, &move_to_savings).await.unwrap(), 2);
+    let page = get_transactions(&pool, savings_id, 10, None).await.unwrap();
+    assert_eq!(page.transactions.len(), 2);
+
+    // A bulk command is a single undo step
+    let undone = undo(&pool, 1).await.unwrap();
+    assert_eq!(undone[0].label, "Move transactions");
+    let page = get_transactions(&pool, checking_id, 10, None).await.unwrap();
+    assert_eq!(page.transactions.len(), 3);
+
+    assert_eq!(bulk_update_transactions(&pool, &everything, &BulkChange::Delete).await.unwrap(), 3);
+    assert_eq!(get_trash(&pool).await.unwrap().len(), 3);
+    assert_eq!(bulk_update_transactions(&pool, &everything, &cleared).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_merge_categories() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Checking".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let category = |name: &str, parent_category_id: Option<i64>| CreateCategoryRequest {
+        name: name.to_string(),
+        display_order: None,
+        parent_category_id,
+        default_discretionary: None,
+        default_fixed: None,
+    };
+    let dining_id = insert_category(&pool, &category("Dining Out", None)).await.unwrap();
+    let takeout_id = insert_category(&pool, &category("Takeout", Some(dining_id))).await.unwrap();
+    let restaurants_id = insert_category(&pool, &category("Restaurants", None)).await.unwrap();
+
+    let transaction_request = CreateTransactionRequest {
+        account_id,
+        date: "2026-07-01".parse().unwrap(),
+        amount: -30.00,
+        description: Some("Bistro".to_string()),
+        payee: None,
+        memo: None,
+        category_id: Some(dining_id),
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let transaction_id = insert_transaction(&pool, &transaction_request).await.unwrap();
+
+    // Invalid merges change nothing
+    assert!(matches!(
+        merge_categories(&pool, dining_id, dining_id).await,
+        Err(DatabaseError::Invalid(_))
+    ));
+    assert!(matches!(
+        merge_categories(&pool, dining_id, takeout_id).await,
+        Err(DatabaseError::Invalid(_))
+    ));
+    assert!(matches!(
+        merge_categories(&pool, dining_id, 999).await,
+        Err(DatabaseError::NotFound { entity: "category", id: 999 })
+    ));
+
+    assert_eq!(merge_categories(&pool, dining_id, restaurants_id).await.unwrap(), 1);
+
+    let transaction = get_transaction(&pool, transaction_id).await.unwrap().unwrap();
+    assert_eq!(transaction.category_id, Some(restaurants_id));
+    let takeout = get_category(&pool, takeout_id).await.unwrap().unwrap();
+    assert_eq!(takeout.parent_category_id, Some(restaurants_id));
+    assert!(get_category(&pool, dining_id).await.unwrap().is_none());
+
+    // The merge is undone as one step
+    undo(&pool, 1).await.unwrap();
+    let transaction = get_transaction(&pool, transaction_id).await.unwrap().unwrap();
+    assert_eq!(transaction.category_id, Some(dining_id));
+    assert!(get_category(&pool, dining_id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_category_tree() {
+    let pool = create_test_pool().await;
+
+    let category = |name: &str, parent_category_id: Option<i64>| CreateCategoryRequest {
+        name: name.to_string(),
+        display_order: None,
+        parent_category_id,
+        default_discretionary: None,
+        default_fixed: None,
+    };
+    let food_id = insert_category(&pool, &category("Food", None)).await.unwrap();
+    let groceries_id = insert_category(&pool, &category("Groceries", Some(food_id))).await.unwrap();
+    let produce_id = insert_category(&pool, &category("Produce", Some(groceries_id))).await.unwrap();
+    let travel_id = insert_category(&pool, &category("Travel", None)).await.unwrap();
+
+    let tree = get_category_tree(&pool).await.unwrap();
+    let paths: Vec<(&str, u32)> = tree.iter().map(|node| (node.path.as_str(), node.depth)).collect();
+    assert_eq!(
+        paths,
+        vec![
+            ("Food", 0),
+            ("Food > Groceries", 1),
+            ("Food > Groceries > Produce", 2),
+            ("Travel", 0),
+            ("Uncategorized", 0),
+        ]
+    );
+
+    // Parents must exist, be active and not create a cycle
+    assert!(matches!(
+        insert_category(&pool, &category("Orphan", Some(999))).await,
+        Err(DatabaseError::NotFound { .. })
+    ));
+    assert!(matches!(
+        update_category(&pool, food_id, &category("Food", Some(food_id))).await,
+        Err(DatabaseError::Invalid(_))
+    ));
+    assert!(matches!(
+        update_category(&pool, food_id, &category("Food", Some(produce_id))).await,
+        Err(DatabaseError::Invalid(_))
+    ));
+    archive_category(&pool, travel_id).await.unwrap();
+    assert!(matches!(
+        move_category(&pool, groceries_id, Some(travel_id)).await,
+        Err(DatabaseError::NotFound { .. })
+    ));
+
+    // Moving a category takes its subtree along
+    move_category(&pool, groceries_id, None).await.unwrap();
+    let tree = get_category_tree(&pool).await.unwrap();
+    let produce = tree.iter().find(|node| node.category.id == produce_id).unwrap();
+    assert_eq!(produce.path, "Groceries > Produce");
+    assert_eq!(produce.depth, 1);
+
+    // A child keeps its parent when the parent is archived and can still be edited
+    archive_category(&pool, groceries_id).await.unwrap();
+    update_category(&pool, produce_id, &category("Vegetables", Some(groceries_id))).await.unwrap();
+    let produce = get_category(&pool, produce_id).await.unwrap().unwrap();
+    assert_eq!(produce.name, "Vegetables");
+    assert_eq!(produce.parent_category_id, Some(groceries_id));
+    assert!(matches!(
+        update_category(&pool, food_id, &category("Food", Some(groceries_id))).await,
+        Err(DatabaseError::NotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_restore_archived() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Old Savings".to_string(),
+        account_type: "savings".to_string(),
+        institution: None,
+        current_balance: Some(10.0),
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+    let category_request = CreateCategoryRequest {
+        name: "Hobbies".to_string(),
+        display_order: None,
+        parent_category_id: None,
+        default_discretionary: None,
+        default_fixed: None,
+    };
+    let category_id = insert_category(&pool, &category_request).await.unwrap();
+
+    archive_account(&pool, account_id).await.unwrap();
+    archive_category(&pool, category_id).await.unwrap();
+    assert!(get_accounts(&pool).await.unwrap().is_empty());
+
+    let archived_accounts = get_archived_accounts(&pool).await.unwrap();
+    assert_eq!(archived_accounts.len(), 1);
+    assert_eq!(archived_accounts[0].id, account_id);
+    assert!(archived_accounts[0].archived);
+    let archived_categories = get_archived_categories(&pool).await.unwrap();
+    assert_eq!(archived_categories.len(), 1);
+    assert_eq!(archived_categories[0].id, category_id);
+
+    restore_account(&pool, account_id).await.unwrap();
+    restore_category(&pool, category_id).await.unwrap();
+    assert!(get_archived_accounts(&pool).await.unwrap().is_empty());
+    assert!(get_archived_categories(&pool).await.unwrap().is_empty());
+    assert_eq!(get_accounts(&pool).await.unwrap()[0].id, account_id);
+    assert!(get_category(&pool, category_id).await.unwrap().is_some());
+
+    let history = get_change_history(&pool, EntityType::Account, account_id).await.unwrap();
+    let actions: Vec<&str> = history.iter().map(|entry| entry.action.as_str()).collect();
+    assert_eq!(actions, vec!["create", "archive", "restore"]);
+}
+
+#[tokio::test]
+async fn test_redo_skips_discarded_operations() {
+    let pool = create_test_pool().await;
+    let account = |name: &str| CreateAccountRequest {
+        name: name.to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: None,
+        account_number_last4: None,
+    };
+
+    // The undone tag is discarded by the next change, whose operation must not
+    // inherit the tag's changes
+    insert_account(&pool, &account("Checking")).await.unwrap();
+    insert_tag(&pool, &CreateTagRequest { name: "Scratch".to_string() }).await.unwrap();
+    undo(&pool, 1).await.unwrap();
+    let savings_id = insert_account(&pool, &account("Savings")).await.unwrap();
+    undo(&pool, 1).await.unwrap();
+
+    let redone = redo(&pool, 1).await.unwrap();
+    assert_eq!(redone.len(), 1);
+    assert_eq!(redone[0].label, "Add account");
+    assert!(get_account(&pool, savings_id).await.unwrap().is_some());
+    assert!(get_tags(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_operations_from_older_versions() {
+    // The undo history as older versions kept it, with reusable operation IDs:
+    // operation 2 was discarded but its change is still in the log
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    for sql in [
+        "CREATE TABLE change_log (id INTEGER PRIMARY KEY, entity_type TEXT NOT NULL, entity_id INTEGER NOT NULL, action TEXT NOT NULL, source TEXT NOT NULL, changed_at DATETIME DEFAULT CURRENT_TIMESTAMP, before_json TEXT, after_json TEXT, operation_id INTEGER)",
+        "CREATE TABLE operations (id INTEGER PRIMARY KEY, label TEXT NOT NULL, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, undone BOOLEAN NOT NULL DEFAULT FALSE)",
+        "INSERT INTO operations (id, label) VALUES (1, 'Add tag')",
+        "INSERT INTO change_log (entity_type, entity_id, action, source, operation_id) VALUES ('tag', 1, 'create', 'manual', 1), ('tag', 2, 'create', 'manual', 2)",
+    ] {
+        sqlx::query(sql).execute(&pool).await.unwrap();
+    }
+    create_schema(&pool).await.unwrap();
+
+    let stack = get_undo_stack(&pool).await.unwrap();
+    let ids: Vec<i64> = stack.undo.iter().map(|op| op.id).collect();
+    assert_eq!(ids, vec![1]);
+
+    // New operations start above every ID the change log has seen
+    insert_tag(&pool, &CreateTagRequest { name: "Scratch".to_string() }).await.unwrap();
+    let stack = get_undo_stack(&pool).await.unwrap();
+    assert_eq!(stack.undo[0].id, 3);
+}
+
+#[tokio::test]
+async fn test_checks_on_older_tables() {
+    // Tables as older versions created them, before their CHECK constraints
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    for table in [
+        "CREATE TABLE accounts (id INTEGER PRIMARY KEY, name TEXT NOT NULL, type TEXT NOT NULL, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, updated_at DATETIME DEFAULT CURRENT_TIMESTAMP, current_balance INTEGER, institution TEXT, display_order INTEGER, archived BOOLEAN DEFAULT FALSE, include_in_net_worth BOOLEAN DEFAULT TRUE, account_number_last4 TEXT)",
+        "CREATE TABLE transactions (id INTEGER PRIMARY KEY, account_id INTEGER NOT NULL REFERENCES accounts(id), date DATE NOT NULL, amount INTEGER NOT NULL, description TEXT, category_id INTEGER REFERENCES categories(id), pending BOOLEAN DEFAULT FALSE, transaction_type TEXT NOT NULL, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, cleared BOOLEAN DEFAULT FALSE, reconciled BOOLEAN DEFAULT FALSE, import_id TEXT, source TEXT, payee TEXT, original_description TEXT, memo TEXT)",
+        "CREATE TABLE categories (id INTEGER PRIMARY KEY, name TEXT NOT NULL, archived BOOLEAN DEFAULT FALSE, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, display_order INTEGER, parent_category_id INTEGER REFERENCES categories(id), default_discretionary BOOLEAN, default_fixed BOOLEAN, last_used_date DATE, is_system_category BOOLEAN DEFAULT FALSE)",
+        "CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE COLLATE NOCASE, created_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+    ] {
+        sqlx::query(table).execute(&pool).await.unwrap();
+    }
+    // Dates were free text back then
+    sqlx::query("INSERT INTO accounts (id, name, type) VALUES (1, 'Checking', 'checking')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO transactions (id, account_id, date, amount, transaction_type) VALUES (1, 1, '2026-06-30 00:00:00', -500, 'expense'), (2, 1, '06/30/2026', -700, 'expense')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    create_schema(&pool).await.unwrap();
+
+    // Dates SQLite can read are rewritten, others fail to load instead of panicking
+    let transaction = get_transaction(&pool, 1).await.unwrap().unwrap();
+    assert_eq!(transaction.date, "2026-06-30".parse::<NaiveDate>().unwrap());
+    assert!(get_transaction(&pool, 2).await.is_err());
+
+    // The constraints the tables lack are enforced all the same
+    let execute = |sql: &'static str| {
+        let pool = pool.clone();
+        async move { sqlx::query(sql).execute(&pool).await.map_err(DatabaseError::from) }
+    };
+    for sql in [
+        "INSERT INTO accounts (name, type) VALUES ('  ', 'checking')",
+        "INSERT INTO transactions (account_id, date, amount, transaction_type) VALUES (1, '07/01/2026', -1000, 'expense')",
+        "INSERT INTO transactions (account_id, date, amount, transaction_type) VALUES (1, '2026-07-01', -10.5, 'expense')",
+        "UPDATE categories SET parent_category_id = id",
+        "INSERT INTO tags (name) VALUES ('')",
+    ] {
+        let error = execute(sql).await.unwrap_err();
+        assert!(matches!(error, DatabaseError::CheckViolation(_)), "{sql}: {error}");
+    }
+    execute("INSERT INTO transactions (account_id, date, amount, transaction_type) VALUES (1, '2026-07-01', -1000, 'expense')")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_integrity_errors() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Checking".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+    let category_request = CreateCategoryRequest {
+        name: "Hobbies".to_string(),
+        display_order: None,
+        parent_category_id: None,
+        default_discretionary: None,
+        default_fixed: None,
+    };
+    let category_id = insert_category(&pool, &category_request).await.unwrap();
+
+    let transaction = |account_id: i64, category_id: Option<i64>, date: &str| CreateTransactionRequest {
+        account_id,
+        date: date.parse().unwrap(),
+        amount: -10.00,
+        description: None,
+        payee: None,
+        memo: None,
+        category_id,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+
+    assert!(matches!(
+        insert_transaction(&pool, &transaction(999, None, "2026-07-01")).await,
+        Err(DatabaseError::ForeignKeyViolation(_))
+    ));
+    let bad_date = sqlx::query("INSERT INTO transactions (account_id, date, amount) VALUES (?, '07/01/2026', -1000)")
+        .bind(account_id)
+        .execute(&pool)
+        .await
+        .map_err(DatabaseError::from);
+    assert!(matches!(bad_date, Err(DatabaseError::CheckViolation(_))));
+    assert!(matches!(
+        insert_tag(&pool, &CreateTagRequest { name: "   ".to_string() }).await,
+        Err(DatabaseError::CheckViolation(_))
+    ));
+    insert_tag(&pool, &CreateTagRequest { name: "work".to_string() }).await.unwrap();
+    assert!(matches!(
+        insert_tag(&pool, &CreateTagRequest { name: "Work".to_string() }).await,
+        Err(DatabaseError::UniqueViolation(_))
+    ));
+
+    let transaction_id = insert_transaction(&pool, &transaction(account_id, None, "2026-07-01"))
+        .await
+        .unwrap();
+
+    archive_category(&pool, category_id).await.unwrap();
+    assert!(matches!(
+        insert_transaction(&pool, &transaction(account_id, Some(category_id), "2026-07-02")).await,
+        Err(DatabaseError::InvariantViolation(_))
+    ));
+
+    // Existing transactions of an archived account can still be edited, but no
+    // new ones can be added
+    archive_account(&pool, account_id).await.unwrap();
+    let error = insert_transaction(&pool, &transaction(account_id, None, "2026-07-02"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, DatabaseError::InvariantViolation(_)));
+    assert_eq!(error.to_string(), "transactions can't be added to an archived account");
+
+    let update_request = UpdateTransactionRequest {
+        date: "2026-07-01".parse().unwrap(),
+        amount: -12.00,
+        description: None,
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    update_transaction(&pool, transaction_id, &update_request).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_validation_errors() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "  ".to_string(),
+        account_type: "piggy bank".to_string(),
+        institution: None,
+        current_balance: Some(f64::NAN),
+        display_order: None,
+        include_in_net_worth: None,
+        account_number_last4: Some("abcdefg".to_string()),
+    };
+
+    // Every bad field is reported at once, and nothing is written
+    let Err(DatabaseError::Validation(errors)) = insert_account(&pool, &account_request).await else {
+        panic!("expected a validation error");
+    };
+    let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+    assert_eq!(fields, ["name", "account_type", "current_balance", "account_number_last4"]);
+    assert!(get_accounts(&pool).await.unwrap().is_empty());
+
+    let category_request = CreateCategoryRequest {
+        name: String::new(),
+        display_order: None,
+        parent_category_id: None,
+        default_discretionary: None,
+        default_fixed: None,
+    };
+    let error = insert_category(&pool, &category_request).await.unwrap_err();
+    assert_eq!(error.to_string(), "invalid request: name is required");
+
+    let account_id = insert_account(
+        &pool,
+        &CreateAccountRequest {
+            name: "Checking".to_string(),
+            account_type: "checking".to_string(),
+            institution: None,
+            current_balance: None,
+            display_order: None,
+            include_in_net_worth: None,
+            account_number_last4: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let transaction_request = CreateTransactionRequest {
+        account_id,
+        date: "2024-02-29".parse().unwrap(),
+        amount: f64::INFINITY,
+        description: Some("x".repeat(501)),
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let Err(DatabaseError::Validation(errors)) = insert_transaction(&pool, &transaction_request).await else {
+        panic!("expected a validation error");
+    };
+    let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+    assert_eq!(fields, ["amount", "description"]);
+
+    let transaction_id = insert_transaction(
+        &pool,
+        &CreateTransactionRequest {
+            amount: -5.00,
+            description: None,
+            ..transaction_request
+        },
+    )
+    .await
+    .unwrap();
+
+    let update_request = UpdateTransactionRequest {
+        date: "2024-02-29".parse().unwrap(),
+        amount: -5.00,
+        description: None,
+        payee: None,
+        memo: Some("x".repeat(501)),
+        category_id: None,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let error = update_transaction(&pool, transaction_id, &update_request).await.unwrap_err();
+    assert_eq!(error.to_string(), "invalid request: memo must be at most 500 characters");
+}
+
+#[tokio::test]
+async fn test_typed_dates() {
+    let pool = create_test_pool().await;
+
+    // Dates that aren't real calendar days are reported as an invalid `date` field
+    let request = |date: &str| {
+        serde_json::json!({
+            "account_id": 1,
+            "date": date,
+            "amount": -5.0,
+            "description": null,
+            "payee": null,
+            "memo": null,
+            "category_id": null,
+            "pending": false,
+            "cleared": false,
+            "tag_ids": null,
+        })
+    };
+    for date in ["yesterday", "2023-02-29", "07/01/2026", "2026-07-01T00:00:00Z", "+10000-01-01"] {
+        let parsed: CreateTransactionRequest = serde_json::from_value(request(date)).unwrap();
+        let Err(DatabaseError::Validation(errors)) = insert_transaction(&pool, &parsed).await else {
+            panic!("expected a validation error for {date}");
+        };
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, ["date"], "{date}");
+    }
+    let parsed: CreateTransactionRequest = serde_json::from_value(request("2024-02-29")).unwrap();
+    assert_eq!(parsed.date, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+
+    let account_id = insert_account(
+        &pool,
+        &CreateAccountRequest {
+            name: "Checking".to_string(),
+            account_type: "checking".to_string(),
+            institution: None,
+            current_balance: None,
+            display_order: None,
+            include_in_net_worth: None,
+            account_number_last4: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    // SQLite writes CURRENT_TIMESTAMP in UTC without a zone; it is read back as UTC
+    sqlx::query("UPDATE accounts SET created_at = '2026-07-01 23:30:00' WHERE id = ?")
+        .bind(account_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let account = get_account(&pool, account_id).await.unwrap().unwrap();
+    assert_eq!(account.created_at, Utc.with_ymd_and_hms(2026, 7, 1, 23, 30, 0).unwrap());
+    assert!(account.updated_at <= Utc::now());
+
+    // Timestamps serialize as ISO-8601 with an explicit UTC offset, dates as plain days
+    let json = serde_json::to_value(&account).unwrap();
+    assert_eq!(json["created_at"], "2026-07-01T23:30:00Z");
+
+    for (date, id) in [("2026-07-01", 1), ("2026-06-30", 2), ("2025-12-31", 3)] {
+        let request = CreateTransactionRequest {
+            account_id,
+            date: date.parse().unwrap(),
+            amount: -id as f64,
+            ..parsed.clone()
+        };
+        insert_transaction(&pool, &request).await.unwrap();
+    }
+    let page = get_transactions(&pool, account_id, 10, None).await.unwrap();
+    let dates: Vec<String> = page.transactions.iter().map(|t| t.date.to_string()).collect();
+    assert_eq!(dates, ["2026-07-01", "2026-06-30", "2025-12-31"]);
+    assert_eq!(serde_json::to_value(&page.transactions[0]).unwrap()["date"], "2026-07-01");
+}
+
+#[tokio::test]
+async fn test_category_suggestions() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Checking".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let category = |name: &str| CreateCategoryRequest {
+        name: name.to_string(),
+        display_order: None,
+        parent_category_id: None,
+        default_discretionary: None,
+        default_fixed: None,
+    };
+    let groceries_id = insert_category(&pool, &category("Groceries")).await.unwrap();
+    let dining_id = insert_category(&pool, &category("Dining")).await.unwrap();
+    let fuel_id = insert_category(&pool, &category("Fuel")).await.unwrap();
+    let gifts_id = insert_category(&pool, &category("Gifts")).await.unwrap();
+
+    let transaction = |date: &str, payee: &str, category_id: Option<i64>| CreateTransactionRequest {
+        account_id,
+        date: date.parse().unwrap(),
+        amount: -20.00,
+        description: None,
+        payee: Some(payee.to_string()),
+        memo: None,
+        category_id,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    for (date, payee, category_id) in [
+        ("2026-06-05", "Safeway", Some(groceries_id)),
+        ("2026-06-01", "SAFEWAY", Some(groceries_id)),
+        ("2026-06-20", "Safeway", Some(dining_id)),
+        ("2026-06-25", "Shell", Some(fuel_id)),
+        ("2026-06-28", "Shell", None),
+    ] {
+        insert_transaction(&pool, &transaction(date, payee, category_id)).await.unwrap();
+    }
+
+    // Categorizing a transaction records the usage on its category
+    let groceries = get_category(&pool, groceries_id).await.unwrap().unwrap();
+    assert_eq!(groceries.usage_count, 2);
+    assert_eq!(groceries.last_used_date, Some("2026-06-05".parse().unwrap()));
+    let gifts = get_category(&pool, gifts_id).await.unwrap().unwrap();
+    assert_eq!((gifts.usage_count, gifts.last_used_date), (0, None));
+
+    // The payee's own categories come first, then recency; unused ones are left out
+    let ranked = |suggestions: Vec<CategorySuggestion>| -> Vec<(i64, i64)> {
+        suggestions
+            .iter()
+            .map(|suggestion| (suggestion.category.id, suggestion.payee_uses))
+            .collect()
+    };
+    let suggestions = suggest_categories(&pool, Some("  safeway "), 5).await.unwrap();
+    assert_eq!(ranked(suggestions), [(groceries_id, 2), (dining_id, 1), (fuel_id, 0)]);
+
+    let suggestions = suggest_categories(&pool, None, 5).await.unwrap();
+    assert_eq!(ranked(suggestions), [(fuel_id, 0), (dining_id, 0), (groceries_id, 0)]);
+
+    let suggestions = suggest_categories(&pool, Some("Shell"), 1).await.unwrap();
+    assert_eq!(ranked(suggestions), [(fuel_id, 1)]);
+
+    // Bulk recategorizing counts too
+    bulk_update_transactions(
+        &pool,
+        &BulkTarget::Filter(TransactionSearchRequest {
+            query: "Shell".to_string(),
+            account_id: None,
+            tag_ids: None,
+            limit: None,
+        }),
+        &BulkChange::SetCategory { category_id: Some(gifts_id) },
+    )
+    .await
+    .unwrap();
+    let gifts = get_category(&pool, gifts_id).await.unwrap().unwrap();
+    assert_eq!(gifts.usage_count, 2);
+    assert_eq!(gifts.last_used_date, Some("2026-06-28".parse().unwrap()));
+    let fuel = get_category(&pool, fuel_id).await.unwrap().unwrap();
+    assert_eq!((fuel.usage_count, fuel.last_used_date), (0, None));
+
+    // Trashing, restoring and undoing move the usage with the transaction
+    let latest: i64 = sqlx::query_scalar("SELECT id FROM transactions WHERE date = '2026-06-05'").fetch_one(&pool).await.unwrap();
+    delete_transaction(&pool, latest).await.unwrap();
+    let groceries = get_category(&pool, groceries_id).await.unwrap().unwrap();
+    assert_eq!((groceries.usage_count, groceries.last_used_date), (1, Some("2026-06-01".parse().unwrap())));
+    restore_transaction(&pool, latest).await.unwrap();
+    let groceries = get_category(&pool, groceries_id).await.unwrap().unwrap();
+    assert_eq!((groceries.usage_count, groceries.last_used_date), (2, Some("2026-06-05".parse().unwrap())));
+    undo(&pool, 1).await.unwrap();
+    let groceries = get_category(&pool, groceries_id).await.unwrap().unwrap();
+    assert_eq!((groceries.usage_count, groceries.last_used_date), (1, Some("2026-06-01".parse().unwrap())));
+    redo(&pool, 1).await.unwrap();
+
+    // Undoing an edit of the category doesn't roll its usage back
+    update_category(&pool, gifts_id, &category("Presents")).await.unwrap();
+    undo(&pool, 1).await.unwrap();
+    let gifts = get_category(&pool, gifts_id).await.unwrap().unwrap();
+    assert_eq!(gifts.name, "Gifts");
+    assert_eq!(gifts.usage_count, 2);
+}
@@ -0,0 +1,155 @@
+use super::backup_tests::test_dir;
+use super::database_tests::create_test_schema;
+use crate::backup::*;
+use crate::database::*;
+use crate::encryption::*;
+use sqlx::SqlitePool;
+use std::path::Path;
+
+async fn search(pool: &SqlitePool, query: &str) -> Vec<String> {
+    let request = TransactionSearchRequest {
+        query: query.to_string(),
+        account_id: None,
+        tag_ids: None,
+        limit: None,
+    };
+    search_transactions(pool, &request)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter_map(|result| result.transaction.payee)
+        .collect()
+}
+
+async fn unlock_error(database: &Path, passphrase: Option<&str>) -> EncryptionError {
+    unlock(database, passphrase).await.unwrap_err()
+}
+
+#[tokio::test]
+async fn test_encryption_lifecycle() {
+    let dir = test_dir("encryption");
+    let database = dir.join("tally.db");
+    let config = BackupConfig {
+        dir: dir.join("backups"),
+        retention: 5,
+    };
+
+    let pool = unlock(&database, None).await.unwrap();
+    create_test_schema(&pool).await;
+    let account_request = CreateAccountRequest {
+        name: "Checking".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+    let category_request = CreateCategoryRequest {
+        name: "Groceries".to_string(),
+        display_order: None,
+        parent_category_id: None,
+        default_discretionary: None,
+        default_fixed: None,
+    };
+    let category_id = insert_category(&pool, &category_request).await.unwrap();
+    let transaction_request = CreateTransactionRequest {
+        account_id,
+        date: "2026-03-14".parse().unwrap(),
+        amount: -42.50,
+        description: Some("Weekly shop".to_string()),
+        payee: Some("Safeway".to_string()),
+        memo: None,
+        category_id: Some(category_id),
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    insert_transaction(&pool, &transaction_request)
+        .await
+        .unwrap();
+    assert!(!is_encrypted(&database).unwrap());
+
+    // Enabling needs a passphrase and replaces the file with an encrypted copy
+    let error = enable_encryption(&pool, &database, "").await.unwrap_err();
+    assert!(matches!(error, EncryptionError::Invalid(_)), "{error}");
+    enable_encryption(&pool, &database, "correct horse")
+        .await
+        .unwrap();
+    assert!(pool.is_closed());
+    assert!(is_encrypted(&database).unwrap());
+
+    // The database stays locked without the right passphrase
+    assert!(matches!(
+        unlock_error(&database, None).await,
+        EncryptionError::WrongPassphrase
+    ));
+    assert!(matches!(
+        unlock_error(&database, Some("battery staple")).await,
+        EncryptionError::WrongPassphrase
+    ));
+
+    let pool = unlock(&database, Some("correct horse")).await.unwrap();
+    assert_eq!(search(&pool, "weekly").await, ["Safeway"]);
+    assert_eq!(
+        get_category(&pool, category_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .usage_count,
+        1
+    );
+    let error = enable_encryption(&pool, &database, "again")
+        .await
+        .unwrap_err();
+    assert!(matches!(error, EncryptionError::Invalid(_)), "{error}");
+
+    // Backups of an encrypted database need the passphrase to be restored
+    let backup = create_backup(&pool, &config, BackupReason::Manual)
+        .await
+        .unwrap();
+    assert!(backup.encrypted);
+    assert!(list_backups(&config).unwrap()[0].encrypted);
+    let backup_path = config.dir.join(&backup.file_name);
+    assert!(validate_backup(&backup_path, None).await.is_err());
+    validate_backup(&backup_path, Some("correct horse"))
+        .await
+        .unwrap();
+
+    // Changing the passphrase checks the current one first
+    let error = change_passphrase(&pool, &database, "wrong", "battery staple")
+        .await
+        .unwrap_err();
+    assert!(matches!(error, EncryptionError::WrongPassphrase));
+    assert!(!pool.is_closed());
+    change_passphrase(&pool, &database, "correct horse", "battery staple")
+        .await
+        .unwrap();
+    assert!(matches!(
+        unlock_error(&database, Some("correct horse")).await,
+        EncryptionError::WrongPassphrase
+    ));
+
+    // Disabling leaves an ordinary database with the same data
+    let pool = unlock(&database, Some("battery staple")).await.unwrap();
+    let error = disable_encryption(&pool, &database, "correct horse")
+        .await
+        .unwrap_err();
+    assert!(matches!(error, EncryptionError::WrongPassphrase));
+    disable_encryption(&pool, &database, "battery staple")
+        .await
+        .unwrap();
+    assert!(!is_encrypted(&database).unwrap());
+
+    let pool = unlock(&database, None).await.unwrap();
+    assert_eq!(search(&pool, "safeway").await, ["Safeway"]);
+    let error = disable_encryption(&pool, &database, "battery staple")
+        .await
+        .unwrap_err();
+    assert!(matches!(error, EncryptionError::Invalid(_)), "{error}");
+    pool.close().await;
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,187 @@
+use super::database_tests::create_test_pool;
+use super::export_tests::create_sample_data;
+use crate::database::*;
+use crate::events::*;
+use crate::export::{export_data, import_data};
+
+#[tokio::test]
+async fn test_capture_changes() {
+    let pool = create_test_pool().await;
+    let account_id = insert_account(
+        &pool,
+        &CreateAccountRequest {
+            name: "Checking".to_string(),
+            account_type: "checking".to_string(),
+            institution: None,
+            current_balance: None,
+            display_order: None,
+            include_in_net_worth: None,
+            account_number_last4: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Reading changes nothing
+    let (_, events) = capture(get_accounts(&pool)).await;
+    assert!(events.is_empty());
+
+    let transaction = |amount: f64| CreateTransactionRequest {
+        account_id,
+        date: "2026-10-02".parse().unwrap(),
+        amount,
+        description: None,
+        payee: None,
+        memo: None,
+        category_id: None,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let ((first, second), events) = capture(async {
+        let first = insert_transaction(&pool, &transaction(-10.00))
+            .await
+            .unwrap();
+        let second = insert_transaction(&pool, &transaction(-20.00))
+            .await
+            .unwrap();
+        delete_transaction(&pool, first).await.unwrap();
+        archive_account(&pool, account_id).await.unwrap();
+        (first, second)
+    })
+    .await;
+    assert_eq!(
+        events,
+        [
+            ChangeEvent::new(
+                ChangedRecord::Transaction,
+                ChangeAction::Create,
+                vec![first, second]
+            ),
+            ChangeEvent::new(
+                ChangedRecord::Transaction,
+                ChangeAction::Delete,
+                vec![first]
+            ),
+            ChangeEvent::new(
+                ChangedRecord::Account,
+                ChangeAction::Archive,
+                vec![account_id]
+            ),
+        ]
+    );
+    assert_eq!(events[0].name(), "transaction-changed");
+    assert_eq!(
+        serde_json::to_value(&events[2]).unwrap(),
+        serde_json::json!({ "record": "account", "kind": "archive", "ids": [account_id] })
+    );
+
+    // Undo is reported for the records it puts back
+    let (result, events) = capture(undo(&pool, 1)).await;
+    result.unwrap();
+    assert_eq!(
+        events,
+        [ChangeEvent::new(
+            ChangedRecord::Account,
+            ChangeAction::Undo,
+            vec![account_id]
+        )]
+    );
+
+    // Attachments aren't in the change log, but are reported all the same
+    let request = CreateAttachmentRequest {
+        transaction_id: second,
+        file_name: "receipt.txt".to_string(),
+        mime_type: None,
+        data: b"Coffee 4.50".to_vec(),
+    };
+    let (attachment_id, events) = capture(insert_attachment(&pool, &request)).await;
+    let attachment_id = attachment_id.unwrap();
+    assert_eq!(
+        events,
+        [ChangeEvent::new(
+            ChangedRecord::Attachment,
+            ChangeAction::Create,
+            vec![attachment_id]
+        )]
+    );
+    let (_, events) = capture(delete_attachment(&pool, attachment_id)).await;
+    assert_eq!(
+        events,
+        [ChangeEvent::new(
+            ChangedRecord::Attachment,
+            ChangeAction::Delete,
+            vec![attachment_id]
+        )]
+    );
+}
+
+#[tokio::test]
+async fn test_capture_ignores_other_tasks() {
+    let pool = create_test_pool().await;
+    let request = |name: &str| CreateTagRequest {
+        name: name.to_string(),
+    };
+
+    // A change made on another task during the capture isn't reported
+    let (tag_id, events) = capture(async {
+        let other = tokio::spawn({
+            let pool = pool.clone();
+            let request = request("Other");
+            async move { insert_tag(&pool, &request).await.unwrap() }
+        });
+        other.await.unwrap();
+        insert_tag(&pool, &request("Mine")).await.unwrap()
+    })
+    .await;
+    assert_eq!(
+        events,
+        [ChangeEvent::new(
+            ChangedRecord::Tag,
+            ChangeAction::Create,
+            vec![tag_id]
+        )]
+    );
+    assert_eq!(get_tags(&pool).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_imports_report_created_records() {
+    let source = create_test_pool().await;
+    create_sample_data(&source).await;
+    let export = export_data(&source).await.unwrap();
+
+    let target = create_test_pool().await;
+    let (result, events) = capture(import_data(&target, &export)).await;
+    result.unwrap();
+
+    // The import is in the change log: the seeded system category is
+    // replaced by the exported one, then everything else is added
+    let kinds: Vec<(&str, ChangeAction)> = events
+        .iter()
+        .map(|event| (event.name(), event.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("category-changed", ChangeAction::Delete),
+            ("account-changed", ChangeAction::Create),
+            ("category-changed", ChangeAction::Create),
+            ("tag-changed", ChangeAction::Create),
+            ("transaction-changed", ChangeAction::Create),
+            ("account-changed", ChangeAction::Archive),
+            ("category-changed", ChangeAction::Archive),
+        ]
+    );
+    assert_eq!(events[1].ids.len(), 2);
+    assert_eq!(events[4].ids.len(), 5);
+
+    let categories: Vec<i64> = export
+        .data
+        .categories
+        .iter()
+        .map(|category| category.id)
+        .collect();
+    assert_eq!(events[2].ids, categories);
+}
@@ -0,0 +1,351 @@
+use super::backup_tests::test_dir;
+use super::database_tests::create_test_pool;
+use crate::database::*;
+use crate::export::*;
+use flate2::read::DeflateDecoder;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+
+// Fills a database with one of everything an export covers
+pub(super) async fn create_sample_data(pool: &SqlitePool) {
+    let account = |name: &str| CreateAccountRequest {
+        name: name.to_string(),
+        account_type: "checking".to_string(),
+        institution: Some("First Bank".to_string()),
+        current_balance: Some(1250.75),
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: Some("1234".to_string()),
+    };
+    let checking_id = insert_account(pool, &account("Checking")).await.unwrap();
+    let savings_id = insert_account(pool, &account("Savings")).await.unwrap();
+
+    let category = |name: &str, parent_category_id: Option<i64>| CreateCategoryRequest {
+        name: name.to_string(),
+        display_order: None,
+        parent_category_id,
+        default_discretionary: None,
+        default_fixed: Some(true),
+    };
+    let food_id = insert_category(pool, &category("Food", None))
+        .await
+        .unwrap();
+    let groceries_id = insert_category(pool, &category("Groceries", Some(food_id)))
+        .await
+        .unwrap();
+    let old_id = insert_category(pool, &category("Old, unused", None))
+        .await
+        .unwrap();
+
+    let tag_id = insert_tag(
+        pool,
+        &CreateTagRequest {
+            name: "vacation".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let transaction = |account_id: i64, amount: f64, payee: &str, category_id: Option<i64>| {
+        CreateTransactionRequest {
+            account_id,
+            date: "2026-04-02".parse().unwrap(),
+            amount,
+            description: Some(format!("Paid \"{payee}\", in full")),
+            payee: Some(payee.to_string()),
+            memo: None,
+            category_id,
+            pending: false,
+            cleared: true,
+            tag_ids: None,
+            spending_kind: Some(SpendingKind::Discretionary),
+        }
+    };
+    let groceries = insert_transaction(
+        pool,
+        &transaction(checking_id, -42.50, "Safeway", Some(groceries_id)),
+    )
+    .await
+    .unwrap();
+    insert_transaction(
+        pool,
+        &transaction(checking_id, -5.00, "Kiosk", Some(old_id)),
+    )
+    .await
+    .unwrap();
+    let from = insert_transaction(pool, &transaction(checking_id, -100.00, "Savings", None))
+        .await
+        .unwrap();
+    let to = insert_transaction(pool, &transaction(savings_id, 100.00, "Checking", None))
+        .await
+        .unwrap();
+    let trashed = insert_transaction(pool, &transaction(checking_id, -1.00, "Oops", None))
+        .await
+        .unwrap();
+
+    tag_transactions(pool, &[groceries], tag_id).await.unwrap();
+    delete_transaction(pool, trashed).await.unwrap();
+    sqlx::query(
+        "INSERT INTO transfers (from_transaction_id, to_transaction_id, transfer_type) VALUES (?, ?, 'internal')",
+    )
+    .bind(from)
+    .bind(to)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    archive_category(pool, old_id).await.unwrap();
+    archive_account(pool, savings_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_export_import_round_trip() {
+    let source = create_test_pool().await;
+    create_sample_data(&source).await;
+
+    let export = export_data(&source).await.unwrap();
+    assert_eq!(export.format, EXPORT_FORMAT);
+    assert_eq!(export.version, EXPORT_VERSION);
+    assert_eq!(
+        export.data.counts(),
+        DataCounts {
+            accounts: 2,
+            categories: 4,
+            tags: 1,
+            transactions: 5,
+            transfers: 1,
+        }
+    );
+
+    let target = create_test_pool().await;
+    let counts = import_data(&target, &export).await.unwrap();
+    assert_eq!(counts, export.data.counts());
+
+    // Exporting the imported data gives back exactly what went in
+    let reexport = export_data(&target).await.unwrap();
+    assert_eq!(
+        serde_json::to_value(&reexport.data).unwrap(),
+        serde_json::to_value(&export.data).unwrap()
+    );
+    assert_eq!(reexport.sha256, export.sha256);
+
+    // Derived data is rebuilt from the imported transactions
+    let search = TransactionSearchRequest {
+        query: "safeway".to_string(),
+        account_id: None,
+        tag_ids: None,
+        limit: None,
+    };
+    assert_eq!(
+        search_transactions(&target, &search).await.unwrap().len(),
+        1
+    );
+    let groceries = export
+        .data
+        .categories
+        .iter()
+        .find(|category| category.name == "Groceries")
+        .unwrap();
+    let imported = get_category(&target, groceries.id).await.unwrap().unwrap();
+    assert_eq!(imported.usage_count, 1);
+    assert_eq!(imported.last_used_date, Some("2026-04-02".parse().unwrap()));
+
+    // Only an empty database can be imported into
+    let error = import_data(&target, &export).await.unwrap_err();
+    assert!(matches!(error, ExportError::Invalid(_)), "{error}");
+}
+
+#[tokio::test]
+async fn test_import_rejects_invalid_exports() {
+    let source = create_test_pool().await;
+    create_sample_data(&source).await;
+    let export = export_data(&source).await.unwrap();
+    let target = create_test_pool().await;
+
+    let mut edited = export.clone();
+    edited.data.transactions[0].amount = -1;
+    let error = import_data(&target, &edited).await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid export: checksum mismatch, the file is damaged or was edited"
+    );
+
+    let mut newer = export.clone();
+    newer.version = EXPORT_VERSION + 1;
+    let error = import_data(&target, &newer).await.unwrap_err();
+    assert!(matches!(error, ExportError::Invalid(_)), "{error}");
+
+    // A dangling reference rolls the whole import back
+    let mut dangling = export.clone();
+    dangling.data.transactions[0].category_id = Some(999);
+    dangling.sha256 = dangling.data.checksum().unwrap();
+    let error = import_data(&target, &dangling).await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid export: 1 records reference records that aren't in the export"
+    );
+    assert!(get_accounts(&target).await.unwrap().is_empty());
+    assert_eq!(get_categories(&target).await.unwrap().len(), 1);
+
+    import_data(&target, &export).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_import_is_one_undoable_operation() {
+    let source = create_test_pool().await;
+    create_sample_data(&source).await;
+    let export = export_data(&source).await.unwrap();
+
+    // An undone account leaves a redo that would reuse the imported ID 1
+    let target = create_test_pool().await;
+    let account = CreateAccountRequest {
+        name: "Scratch".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: None,
+        account_number_last4: None,
+    };
+    insert_account(&target, &account).await.unwrap();
+    undo(&target, 1).await.unwrap();
+
+    import_data(&target, &export).await.unwrap();
+    let stack = get_undo_stack(&target).await.unwrap();
+    let labels: Vec<&str> = stack.undo.iter().map(|op| op.label.as_str()).collect();
+    assert_eq!(labels, ["Import data"]);
+    assert!(stack.redo.is_empty());
+
+    let transaction_id = export.data.transactions[0].id;
+    let history = get_change_history(&target, EntityType::Transaction, transaction_id)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(
+        (history[0].action.as_str(), history[0].source.as_str()),
+        ("create", "import")
+    );
+
+    // Undoing the import empties the database again, and redoing brings it all back
+    undo(&target, 1).await.unwrap();
+    assert!(get_accounts(&target).await.unwrap().is_empty());
+    assert!(get_archived_accounts(&target).await.unwrap().is_empty());
+    assert!(get_transfers(&target).await.unwrap().is_empty());
+    let categories = get_categories(&target).await.unwrap();
+    assert_eq!(categories.len(), 1);
+    assert!(categories[0].is_system_category);
+
+    redo(&target, 1).await.unwrap();
+    let reexport = export_data(&target).await.unwrap();
+    assert_eq!(reexport.sha256, export.sha256);
+}
+
+#[tokio::test]
+async fn test_import_leaves_cleared_history_behind() {
+    let source = create_test_pool().await;
+    let account = CreateAccountRequest {
+        name: "Checking".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: None,
+        account_number_last4: None,
+    };
+    insert_account(&source, &account).await.unwrap();
+    let export = export_data(&source).await.unwrap();
+
+    // The cleared operation added a tag the export doesn't have
+    let target = create_test_pool().await;
+    let request = CreateTagRequest {
+        name: "Scratch".to_string(),
+    };
+    insert_tag(&target, &request).await.unwrap();
+    undo(&target, 1).await.unwrap();
+
+    import_data(&target, &export).await.unwrap();
+    undo(&target, 1).await.unwrap();
+    assert!(get_accounts(&target).await.unwrap().is_empty());
+    redo(&target, 1).await.unwrap();
+
+    assert_eq!(get_accounts(&target).await.unwrap().len(), 1);
+    assert!(get_tags(&target).await.unwrap().is_empty());
+}
+
+// Reads every file out of a zip archive by walking its local headers
+fn unzip(archive: &[u8]) -> HashMap<String, String> {
+    let u16_at = |at: usize| u16::from_le_bytes([archive[at], archive[at + 1]]) as usize;
+    let u32_at = |at: usize| u32::from_le_bytes(archive[at..at + 4].try_into().unwrap()) as usize;
+
+    let mut files = HashMap::new();
+    let mut at = 0;
+    while u32_at(at) == 0x04034b50 {
+        let crc = u32_at(at + 14) as u32;
+        let compressed_size = u32_at(at + 18);
+        let name_length = u16_at(at + 26);
+        let data_start = at + 30 + name_length + u16_at(at + 28);
+        let name = String::from_utf8(archive[at + 30..at + 30 + name_length].to_vec()).unwrap();
+
+        let mut contents = String::new();
+        DeflateDecoder::new(&archive[data_start..data_start + compressed_size])
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(crc32fast::hash(contents.as_bytes()), crc, "{name}");
+
+        files.insert(name, contents);
+        at = data_start + compressed_size;
+    }
+    files
+}
+
+#[tokio::test]
+async fn test_export_to_files() {
+    let dir = test_dir("export");
+    let pool = create_test_pool().await;
+    create_sample_data(&pool).await;
+
+    let zip_path = dir.join("tally.zip");
+    let counts = export_to_file(&pool, &zip_path, ExportFormat::CsvZip)
+        .await
+        .unwrap();
+    assert_eq!(counts.transactions, 5);
+
+    let files = unzip(&fs::read(&zip_path).unwrap());
+    let mut names: Vec<_> = files.keys().cloned().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "accounts.csv",
+            "categories.csv",
+            "tags.csv",
+            "transactions.csv",
+            "transfers.csv"
+        ]
+    );
+    assert!(files["accounts.csv"].contains(",1250.75,"));
+    assert!(files["categories.csv"].contains(",Food > Groceries,"));
+    let transactions: Vec<&str> = files["transactions.csv"].lines().collect();
+    assert_eq!(transactions.len(), 6);
+    assert!(transactions[0].starts_with("id,date,account_id,account,amount,"));
+    assert!(transactions[1].contains(r#",Checking,-42.50,Safeway,"Paid ""Safeway"", in full",,,"#));
+    assert!(transactions[1].contains(",Food > Groceries,vacation,discretionary,"));
+
+    // A JSON export can be imported from the file
+    let json_path = dir.join("tally.json");
+    export_to_file(&pool, &json_path, ExportFormat::Json)
+        .await
+        .unwrap();
+    let target = create_test_pool().await;
+    let imported = import_from_file(&target, &json_path).await.unwrap();
+    assert_eq!(imported, counts);
+
+    let error = import_from_file(&create_test_pool().await, &zip_path)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ExportError::Invalid(_)), "{error}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,472 @@
+use super::backup_tests::test_dir;
+use super::database_tests::create_test_pool;
+use super::export_tests::create_sample_data;
+use crate::database::*;
+use crate::export::*;
+use crate::ledger::*;
+use std::fs;
+
+#[tokio::test]
+async fn test_ledger_journal() {
+    let pool = create_test_pool().await;
+    create_sample_data(&pool).await;
+    let export = export_data(&pool).await.unwrap();
+
+    let journal = journal(&export, LedgerFormat::Ledger);
+    // Groceries, the kiosk purchase and the transfer; the trashed one is left out
+    assert_eq!(journal.entries, 3);
+    assert!(journal.text.contains("account Assets:Checking\n"));
+    assert!(journal.text.contains("account Expenses:Food:Groceries\n"));
+    assert!(journal.text.contains("account Expenses:Old, unused\n"));
+    assert!(journal.text.contains(concat!(
+        "2026-04-02 * Safeway | Paid \"Safeway\", in full\n",
+        "    ; tally_id: 1\n",
+        "    Expenses:Food:Groceries   42.50 USD\n",
+        "    Assets:Checking          -42.50 USD\n",
+    )));
+    assert!(journal.text.contains(concat!(
+        "2026-04-02 * Savings | Paid \"Savings\", in full\n",
+        "    ; transfer_id: 1\n",
+        "    Assets:Savings    100.00 USD\n",
+        "    Assets:Checking  -100.00 USD\n",
+    )));
+    assert!(!journal.text.contains("Oops"));
+    assert!(!journal.text.contains("Income:Uncategorized"));
+}
+
+#[tokio::test]
+async fn test_beancount_journal() {
+    let pool = create_test_pool().await;
+    create_sample_data(&pool).await;
+    let mut export = export_data(&pool).await.unwrap();
+
+    // An uncleared, pending refund with a memo and no category
+    let mut refund = export.data.transactions[0].clone();
+    refund.id = 100;
+    refund.amount = 1250;
+    refund.category_id = None;
+    refund.payee = None;
+    refund.memo = Some("Returned\nbroken eggs".to_string());
+    refund.cleared = false;
+    refund.pending = true;
+    export.data.transactions.push(refund);
+    // A reconciled purchase counts as cleared
+    export.data.transactions[1].cleared = false;
+    export.data.transactions[1].reconciled = true;
+
+    let journal = journal(&export, LedgerFormat::Beancount);
+    assert_eq!(journal.entries, 4);
+    assert!(
+        journal
+            .text
+            .contains("option \"operating_currency\" \"USD\"\n")
+    );
+    assert!(journal.text.contains(" open Assets:Checking USD\n"));
+    assert!(
+        journal
+            .text
+            .contains("2026-04-02 open Income:Uncategorized USD\n")
+    );
+    assert!(journal.text.contains(" open Expenses:Old-unused USD\n"));
+    assert!(journal.text.contains(concat!(
+        "2026-04-02 * \"Safeway\" \"Paid \\\"Safeway\\\", in full\"\n",
+        "  tally_id: 1\n",
+    )));
+    assert!(journal.text.contains(concat!(
+        "2026-04-02 * \"Kiosk\" \"Paid \\\"Kiosk\\\", in full\"\n",
+        "  tally_id: 2\n",
+        "  reconciled: TRUE\n",
+        "  Expenses:Old-unused   5.00 USD\n",
+        "  Assets:Checking      -5.00 USD\n",
+    )));
+    assert!(journal.text.contains(concat!(
+        "2026-04-02 ! \"Paid \\\"Safeway\\\", in full\"\n",
+        "  tally_id: 100\n",
+        "  memo: \"Returned broken eggs\"\n",
+        "  pending: TRUE\n",
+        "  Income:Uncategorized  -12.50 USD\n",
+        "  Assets:Checking        12.50 USD\n",
+    )));
+
+    // Transfer sides that don't cancel out are exported separately
+    export.data.transactions[3].amount = 9900;
+    let journal = crate::ledger::journal(&export, LedgerFormat::Beancount);
+    assert_eq!(journal.entries, 5);
+    assert!(!journal.text.contains("transfer_id"));
+    assert!(
+        journal
+            .text
+            .contains("  Expenses:Uncategorized   100.00 USD\n")
+    );
+}
+
+#[tokio::test]
+async fn test_export_ledger_file() {
+    let dir = test_dir("ledger");
+    let pool = create_test_pool().await;
+    create_sample_data(&pool).await;
+
+    let path = dir.join("tally.beancount");
+    let entries = export_ledger(&pool, &path, LedgerFormat::Beancount)
+        .await
+        .unwrap();
+    assert_eq!(entries, 3);
+    assert!(
+        fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("; Exported from Tally on ")
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+async fn import_round_trip(format: LedgerFormat) {
+    let source = create_test_pool().await;
+    create_sample_data(&source).await;
+    sqlx::query("UPDATE transactions SET memo = 'Receipt in the glovebox', reconciled = TRUE WHERE payee = 'Kiosk'")
+        .execute(&source)
+        .await
+        .unwrap();
+    let text = journal(&export_data(&source).await.unwrap(), format).text;
+
+    let target = create_test_pool().await;
+    let import = import_journal(&target, &text, format).await.unwrap();
+    assert_eq!(
+        import,
+        JournalImport {
+            accounts: 2,
+            categories: 3,
+            tags: 0,
+            transactions: 4,
+            transfers: 1,
+            skipped: vec![],
+        }
+    );
+
+    let data = export_data(&target).await.unwrap().data;
+    let account = |id: i64| {
+        let account = data.accounts.iter().find(|a| a.id == id).unwrap();
+        account.name.as_str()
+    };
+    let category = |name: &str| data.categories.iter().find(|c| c.name == name).unwrap();
+    assert_eq!(
+        category("Groceries").parent_category_id,
+        Some(category("Food").id)
+    );
+
+    let groceries = &data.transactions[0];
+    assert_eq!(account(groceries.account_id), "Checking");
+    assert_eq!(groceries.amount, -4250);
+    assert_eq!(groceries.category_id, Some(category("Groceries").id));
+    assert_eq!(groceries.payee.as_deref(), Some("Safeway"));
+    assert_eq!(
+        groceries.description.as_deref(),
+        Some("Paid \"Safeway\", in full")
+    );
+    assert!(groceries.cleared && !groceries.reconciled && !groceries.pending);
+
+    let kiosk = &data.transactions[1];
+    assert_eq!(kiosk.memo.as_deref(), Some("Receipt in the glovebox"));
+    assert!(kiosk.cleared && kiosk.reconciled);
+
+    let transfer = &data.transfers[0];
+    let from = data
+        .transactions
+        .iter()
+        .find(|t| t.id == transfer.from_transaction_id)
+        .unwrap();
+    let to = data
+        .transactions
+        .iter()
+        .find(|t| t.id == transfer.to_transaction_id)
+        .unwrap();
+    assert_eq!(
+        (account(from.account_id), from.amount),
+        ("Checking", -10000)
+    );
+    assert_eq!((account(to.account_id), to.amount), ("Savings", 10000));
+    assert_eq!(from.category_id, None);
+
+    // Importing again reuses the accounts and categories
+    let again = import_journal(&target, &text, format).await.unwrap();
+    assert_eq!((again.accounts, again.categories), (0, 0));
+    assert_eq!(again.transactions, 4);
+}
+
+#[tokio::test]
+async fn test_import_round_trip() {
+    import_round_trip(LedgerFormat::Ledger).await;
+    import_round_trip(LedgerFormat::Beancount).await;
+}
+
+const HLEDGER_JOURNAL: &str = r#"; personal.journal
+account assets:bank:checking
+account equity:opening balances
+
+2026-01-01 opening balances
+    assets:bank:checking        $1,500.00
+    equity:opening balances
+
+2026-01-03 * Trader Joe's | groceries  ; :food:weekly:
+    expenses:food:groceries      $62.18
+    assets:bank:checking
+
+2026-01-04 ! Paycheck
+    ; memo: January
+    assets:bank:checking        $2,000.00 = $3,437.82
+    income:salary
+
+2026-01-05 * Card payment
+    liabilities:visa             $300
+    assets:bank:checking
+
+2026-01-06 Dinner in Paris
+    expenses:dining               30.00 EUR
+    liabilities:visa
+
+2026-01-07 Split purchase
+    expenses:food                 $10.00
+    expenses:household            $5.00
+    liabilities:visa
+
+2026-01-08 Budget
+    (budget:food)                 $-100
+    assets:bank:checking          $0
+
+include other.journal
+
+2026-01-09 Typo
+    expenses:food                 $10.00
+    assets:bank:checking         -$9.00
+
+2026-01-10 Shares
+    assets:brokerage              10 AAPL @ $150
+    assets:bank:checking
+"#;
+
+#[tokio::test]
+async fn test_import_ledger_journal() {
+    let pool = create_test_pool().await;
+    let import = import_journal(&pool, HLEDGER_JOURNAL, LedgerFormat::Ledger)
+        .await
+        .unwrap();
+
+    let skipped: Vec<(usize, &str)> = import
+        .skipped
+        .iter()
+        .map(|skipped| (skipped.line, skipped.reason.as_str()))
+        .collect();
+    assert_eq!(
+        skipped,
+        [
+            (
+                5,
+                "`equity:opening balances` isn't an asset, liability, expense or income account"
+            ),
+            (22, "amounts in EUR aren't supported"),
+            (26, "entries with 3 postings aren't supported"),
+            (31, "virtual postings aren't supported"),
+            (35, "unsupported directive `include`"),
+            (37, "the postings are off balance by 1.00"),
+            (41, "prices and costs aren't supported"),
+        ]
+    );
+    assert_eq!(
+        (
+            import.accounts,
+            import.categories,
+            import.tags,
+            import.transactions,
+            import.transfers
+        ),
+        (2, 3, 2, 4, 1)
+    );
+
+    let data = export_data(&pool).await.unwrap().data;
+    let visa = data.accounts.iter().find(|a| a.name == "visa").unwrap();
+    assert_eq!(visa.account_type, "credit_card");
+    assert!(data.accounts.iter().any(|a| a.name == "bank:checking"));
+
+    let groceries = &data.transactions[0];
+    assert_eq!(groceries.amount, -6218);
+    assert_eq!(groceries.payee.as_deref(), Some("Trader Joe's"));
+    assert_eq!(groceries.description.as_deref(), Some("groceries"));
+    assert_eq!(groceries.tag_ids.len(), 2);
+    assert!(groceries.cleared);
+
+    let paycheck = &data.transactions[1];
+    assert_eq!(paycheck.amount, 200000);
+    assert_eq!(paycheck.memo.as_deref(), Some("January"));
+    assert!(paycheck.pending && !paycheck.cleared);
+    let salary = data.categories.iter().find(|c| c.name == "salary").unwrap();
+    assert_eq!(paycheck.category_id, Some(salary.id));
+
+    let from = &data.transactions[2];
+    assert_eq!(
+        (from.amount, from.transaction_type.as_str()),
+        (-30000, "transfer")
+    );
+    assert_eq!(data.transfers[0].from_transaction_id, from.id);
+}
+
+const BEANCOUNT_JOURNAL: &str = r#"option "title" "Books"
+2026-01-01 open Assets:Checking USD
+2026-01-01 open Expenses:Coffee
+2026-01-01 pad Assets:Checking Equity:Opening-Balances
+
+2026-01-02 * "Blue Bottle" "Latte \"to go\"" #treat
+  receipt: "yes"
+  Expenses:Coffee   5.50 USD ; oat milk
+  Assets:Checking
+
+2026-01-03 ! "Refill"
+  Expenses:Coffee   2 HOOL {1.00 USD}
+  Assets:Checking
+"#;
+
+#[tokio::test]
+async fn test_import_beancount_journal() {
+    let pool = create_test_pool().await;
+    let import = import_journal(&pool, BEANCOUNT_JOURNAL, LedgerFormat::Beancount)
+        .await
+        .unwrap();
+    assert_eq!(
+        import,
+        JournalImport {
+            accounts: 1,
+            categories: 1,
+            tags: 1,
+            transactions: 1,
+            transfers: 0,
+            skipped: vec![
+                SkippedLine {
+                    line: 4,
+                    reason: "unsupported directive `pad`".to_string(),
+                },
+                SkippedLine {
+                    line: 11,
+                    reason: "prices and costs aren't supported".to_string(),
+                },
+            ],
+        }
+    );
+
+    let data = export_data(&pool).await.unwrap().data;
+    let latte = &data.transactions[0];
+    assert_eq!(latte.amount, -550);
+    assert_eq!(latte.payee.as_deref(), Some("Blue Bottle"));
+    assert_eq!(latte.description.as_deref(), Some("Latte \"to go\""));
+    assert!(latte.cleared);
+    assert_eq!(data.tags[0].name, "treat");
+}
+
+#[tokio::test]
+async fn test_journal_import_is_one_undoable_operation() {
+    let pool = create_test_pool().await;
+    import_journal(&pool, HLEDGER_JOURNAL, LedgerFormat::Ledger)
+        .await
+        .unwrap();
+
+    let stack = get_undo_stack(&pool).await.unwrap();
+    let labels: Vec<&str> = stack.undo.iter().map(|op| op.label.as_str()).collect();
+    assert_eq!(labels, ["Import journal"]);
+
+    let data = export_data(&pool).await.unwrap().data;
+    let history = get_change_history(&pool, EntityType::Transaction, data.transactions[0].id)
+        .await
+        .unwrap();
+    assert_eq!(
+        (history[0].action.as_str(), history[0].source.as_str()),
+        ("create", "import")
+    );
+
+    // One undo takes everything the import created back out
+    undo(&pool, 1).await.unwrap();
+    let data = export_data(&pool).await.unwrap().data;
+    assert!(data.accounts.is_empty());
+    assert!(data.tags.is_empty());
+    assert!(data.transactions.is_empty());
+    assert!(data.transfers.is_empty());
+    assert!(data.categories.iter().all(|c| c.is_system_category));
+
+    redo(&pool, 1).await.unwrap();
+    let data = export_data(&pool).await.unwrap().data;
+    assert_eq!((data.transactions.len(), data.transfers.len()), (4, 1));
+}
+
+#[tokio::test]
+async fn test_import_skips_archived_matches() {
+    let pool = create_test_pool().await;
+    let checking = insert_account(
+        &pool,
+        &CreateAccountRequest {
+            name: "checking".to_string(),
+            account_type: "checking".to_string(),
+            institution: None,
+            current_balance: None,
+            display_order: None,
+            include_in_net_worth: None,
+            account_number_last4: None,
+        },
+    )
+    .await
+    .unwrap();
+    archive_account(&pool, checking).await.unwrap();
+    let housing = insert_category(
+        &pool,
+        &CreateCategoryRequest {
+            name: "housing".to_string(),
+            display_order: None,
+            parent_category_id: None,
+            default_discretionary: None,
+            default_fixed: None,
+        },
+    )
+    .await
+    .unwrap();
+    archive_category(&pool, housing).await.unwrap();
+
+    let journal = r#"account assets:checking
+2026-01-05 * Coffee
+    expenses:coffee      $3.00
+    assets:checking
+
+2026-01-06 * Rent
+    expenses:housing:rent  $900.00
+    assets:savings
+
+2026-01-07 * Move
+    assets:savings      $50.00
+    assets:checking
+
+2026-01-08 * Lunch
+    expenses:food      $12.00
+    assets:savings
+"#;
+    let import = import_journal(&pool, journal, LedgerFormat::Ledger)
+        .await
+        .unwrap();
+
+    let skipped: Vec<(usize, &str)> = import
+        .skipped
+        .iter()
+        .map(|skipped| (skipped.line, skipped.reason.as_str()))
+        .collect();
+    assert_eq!(
+        skipped,
+        [
+            (2, "the account `checking` is archived"),
+            (6, "the category `housing` is archived"),
+            (10, "the account `checking` is archived"),
+        ]
+    );
+    assert_eq!(
+        (import.accounts, import.categories, import.transactions),
+        (1, 1, 1)
+    );
+
+    // Archived records are neither reused nor duplicated
+    let accounts = get_accounts(&pool).await.unwrap();
+    let names: Vec<&str> = accounts.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ["savings"]);
+}
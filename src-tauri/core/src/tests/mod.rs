@@ -0,0 +1,8 @@
+mod backup_tests;
+mod database_tests;
+mod encryption_tests;
+mod events_tests;
+mod export_tests;
+mod ledger_tests;
+mod reports_tests;
+mod storage_tests;
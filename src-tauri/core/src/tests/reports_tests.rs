@@ -0,0 +1,214 @@
+use super::database_tests::create_test_pool;
+use crate::database::*;
+use crate::reports::*;
+
+#[tokio::test]
+async fn test_spending_report_grouping() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let travel_request = CreateCategoryRequest {
+        name: "Travel".to_string(),
+        display_order: None,
+        parent_category_id: None,
+        default_discretionary: Some(true),
+        default_fixed: Some(false),
+    };
+    let travel_id = insert_category(&pool, &travel_request).await.unwrap();
+
+    let vacation_id = insert_tag(&pool, &CreateTagRequest { name: "vacation-2026".to_string() })
+        .await
+        .unwrap();
+    let tax_id = insert_tag(&pool, &CreateTagRequest { name: "tax-deductible".to_string() })
+        .await
+        .unwrap();
+
+    let transactions = [
+        ("2026-07-01", 240.00, Some(travel_id), vec![vacation_id, tax_id]),
+        ("2026-07-02", 60.00, Some(travel_id), vec![vacation_id]),
+        ("2026-07-03", 15.50, None, vec![]),
+        // Outside the report range
+        ("2026-08-01", 1000.00, Some(travel_id), vec![vacation_id]),
+    ];
+    for (date, amount, category_id, tag_ids) in transactions {
+        let request = CreateTransactionRequest {
+            account_id,
+            date: date.parse().unwrap(),
+            amount,
+            description: None,
+            payee: None,
+            memo: None,
+            category_id,
+            pending: false,
+            cleared: true,
+            tag_ids: Some(tag_ids),
+            spending_kind: None,
+        };
+        insert_transaction(&pool, &request).await.unwrap();
+    }
+
+    let mut request = SpendingReportRequest {
+        start_date: "2026-07-01".parse().unwrap(),
+        end_date: "2026-07-31".parse().unwrap(),
+        account_id: None,
+        group_by: ReportGrouping::Category,
+        include_archived: false,
+    };
+
+    let by_category = get_spending_report(&pool, &request).await.unwrap();
+    assert_eq!(by_category.len(), 2);
+    assert_eq!(by_category[0].group_id, Some(travel_id));
+    assert_eq!(by_category[0].group_name, "Travel");
+    assert_eq!(by_category[0].total, 300.00);
+    assert_eq!(by_category[0].transaction_count, 2);
+    assert_eq!(by_category[1].group_id, None);
+    assert_eq!(by_category[1].group_name, "Uncategorized");
+    assert_eq!(by_category[1].total, 15.50);
+
+    request.group_by = ReportGrouping::Tag;
+    let by_tag = get_spending_report(&pool, &request).await.unwrap();
+    assert_eq!(by_tag.len(), 3);
+    assert_eq!(by_tag[0].group_name, "vacation-2026");
+    assert_eq!(by_tag[0].total, 300.00);
+    assert_eq!(by_tag[0].transaction_count, 2);
+    assert_eq!(by_tag[1].group_name, "tax-deductible");
+    assert_eq!(by_tag[1].total, 240.00);
+    assert_eq!(by_tag[2].group_id, None);
+    assert_eq!(by_tag[2].group_name, "Untagged");
+    assert_eq!(by_tag[2].total, 15.50);
+
+    // Account filter
+    request.account_id = Some(account_id + 1);
+    assert!(get_spending_report(&pool, &request).await.unwrap().is_empty());
+
+    // Archived accounts only count when asked for, or when selected directly
+    archive_account(&pool, account_id).await.unwrap();
+    request.account_id = None;
+    assert!(get_spending_report(&pool, &request).await.unwrap().is_empty());
+    request.include_archived = true;
+    assert_eq!(get_spending_report(&pool, &request).await.unwrap().len(), 3);
+    request.include_archived = false;
+    request.account_id = Some(account_id);
+    assert_eq!(get_spending_report(&pool, &request).await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_spending_split_report() {
+    let pool = create_test_pool().await;
+
+    let account_request = CreateAccountRequest {
+        name: "Test Account".to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: None,
+        display_order: None,
+        include_in_net_worth: Some(true),
+        account_number_last4: None,
+    };
+    let account_id = insert_account(&pool, &account_request).await.unwrap();
+
+    let category = |name: &str, fixed: Option<bool>, discretionary: Option<bool>| CreateCategoryRequest {
+        name: name.to_string(),
+        display_order: None,
+        parent_category_id: None,
+        default_discretionary: discretionary,
+        default_fixed: fixed,
+    };
+    let rent_id = insert_category(&pool, &category("Rent", Some(true), Some(false))).await.unwrap();
+    let dining_id = insert_category(&pool, &category("Dining", Some(false), Some(true))).await.unwrap();
+    let misc_id = insert_category(&pool, &category("Misc", None, None)).await.unwrap();
+
+    let transactions = [
+        ("2026-06-01", 1500.00, Some(rent_id), None),
+        ("2026-06-12", 80.25, Some(dining_id), None),
+        // A meal plan is committed even though dining usually isn't
+        ("2026-06-15", 20.00, Some(dining_id), Some(SpendingKind::Fixed)),
+        ("2026-06-20", 10.00, Some(misc_id), None),
+        ("2026-06-21", 5.00, None, None),
+        ("2026-07-01", 1500.00, Some(rent_id), None),
+        ("2026-07-09", 30.00, Some(misc_id), Some(SpendingKind::Discretionary)),
+        // Outside the report range
+        ("2026-08-01", 1500.00, Some(rent_id), None),
+    ];
+    let mut ids = Vec::new();
+    for (date, amount, category_id, spending_kind) in transactions {
+        let request = CreateTransactionRequest {
+            account_id,
+            date: date.parse().unwrap(),
+            amount,
+            description: None,
+            payee: None,
+            memo: None,
+            category_id,
+            pending: false,
+            cleared: true,
+            tag_ids: None,
+            spending_kind,
+        };
+        ids.push(insert_transaction(&pool, &request).await.unwrap());
+    }
+
+    let transaction = get_transaction(&pool, ids[2]).await.unwrap().unwrap();
+    assert_eq!(transaction.spending_kind, Some(SpendingKind::Fixed));
+    let transaction = get_transaction(&pool, ids[1]).await.unwrap().unwrap();
+    assert_eq!(transaction.spending_kind, None);
+
+    let request = SpendingSplitRequest {
+        start_date: "2026-06-01".parse().unwrap(),
+        end_date: "2026-07-31".parse().unwrap(),
+        account_id: None,
+        include_archived: false,
+    };
+    let rows = get_spending_split_report(&pool, &request).await.unwrap();
+    assert_eq!(
+        rows,
+        [
+            SpendingSplitRow {
+                month: "2026-06-01".parse().unwrap(),
+                fixed: 1520.00,
+                discretionary: 80.25,
+                unclassified: 15.00,
+                total: 1615.25,
+            },
+            SpendingSplitRow {
+                month: "2026-07-01".parse().unwrap(),
+                fixed: 1500.00,
+                discretionary: 30.00,
+                unclassified: 0.00,
+                total: 1530.00,
+            },
+        ]
+    );
+
+    // Clearing the override falls back to the category, and trashed
+    // transactions don't count
+    let update_request = UpdateTransactionRequest {
+        date: "2026-06-15".parse().unwrap(),
+        amount: 20.00,
+        description: None,
+        payee: None,
+        memo: None,
+        category_id: Some(dining_id),
+        pending: false,
+        cleared: true,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    update_transaction(&pool, ids[2], &update_request).await.unwrap();
+    delete_transaction(&pool, ids[0]).await.unwrap();
+
+    let rows = get_spending_split_report(&pool, &request).await.unwrap();
+    assert_eq!(rows[0].fixed, 0.00);
+    assert_eq!(rows[0].discretionary, 100.25);
+    assert_eq!(rows[0].total, 115.25);
+}
@@ -0,0 +1,432 @@
+use super::database_tests::create_test_pool;
+use crate::database::*;
+use crate::storage::*;
+
+fn account(name: &str, display_order: Option<i32>) -> CreateAccountRequest {
+    CreateAccountRequest {
+        name: name.to_string(),
+        account_type: "checking".to_string(),
+        institution: None,
+        current_balance: Some(1250.75),
+        display_order,
+        include_in_net_worth: None,
+        account_number_last4: None,
+    }
+}
+
+fn category(name: &str, parent_category_id: Option<i64>) -> CreateCategoryRequest {
+    CreateCategoryRequest {
+        name: name.to_string(),
+        display_order: None,
+        parent_category_id,
+        default_discretionary: None,
+        default_fixed: None,
+    }
+}
+
+fn transaction(
+    account_id: i64,
+    date: &str,
+    amount: f64,
+    category_id: Option<i64>,
+) -> CreateTransactionRequest {
+    CreateTransactionRequest {
+        account_id,
+        date: date.parse().unwrap(),
+        amount,
+        description: None,
+        payee: None,
+        memo: None,
+        category_id,
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    }
+}
+
+fn transfer(from_account_id: i64, to_account_id: i64, amount: f64) -> CreateTransferRequest {
+    CreateTransferRequest {
+        from_account_id,
+        to_account_id,
+        date: "2026-10-01".parse().unwrap(),
+        amount,
+        description: Some("Monthly savings".to_string()),
+        memo: None,
+        cleared: true,
+    }
+}
+
+// Runs the same checks on every backend, so the in-memory one can stand in for SQLite
+async fn check_storage_rules<S: Storage>(storage: &S) {
+    let savings_id = storage
+        .insert_account(&account("Savings", None))
+        .await
+        .unwrap();
+    let checking_id = storage
+        .insert_account(&account("Checking", None))
+        .await
+        .unwrap();
+    let first_id = storage
+        .insert_account(&account("Zzz", Some(1)))
+        .await
+        .unwrap();
+    assert_eq!((savings_id, checking_id, first_id), (1, 2, 3));
+
+    let names: Vec<String> = storage
+        .accounts(false)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|account| account.name)
+        .collect();
+    assert_eq!(names, ["Checking", "Savings", "Zzz"]);
+    let checking = storage.account(checking_id).await.unwrap().unwrap();
+    assert_eq!(checking.current_balance, Some(1250.75));
+    assert!(checking.include_in_net_worth);
+
+    let error = storage
+        .insert_account(&account(" ", None))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, DatabaseError::Validation(_)), "{error}");
+
+    // Only the system category exists to begin with
+    let categories = storage.categories(false).await.unwrap();
+    assert_eq!(categories.len(), 1);
+    assert!(categories[0].is_system_category);
+
+    let food_id = storage
+        .insert_category(&category("Food", None))
+        .await
+        .unwrap();
+    let error = storage
+        .insert_category(&category("Orphan", Some(99)))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            error,
+            DatabaseError::NotFound {
+                entity: "category",
+                id: 99
+            }
+        ),
+        "{error}"
+    );
+
+    let groceries = storage
+        .insert_transaction(&transaction(
+            checking_id,
+            "2026-10-02",
+            -42.50,
+            Some(food_id),
+        ))
+        .await
+        .unwrap();
+    storage
+        .insert_transaction(&transaction(
+            checking_id,
+            "2026-10-05",
+            -7.25,
+            Some(food_id),
+        ))
+        .await
+        .unwrap();
+    let food = storage.category(food_id).await.unwrap().unwrap();
+    assert_eq!(food.usage_count, 2);
+    assert_eq!(food.last_used_date, Some("2026-10-05".parse().unwrap()));
+
+    let saved = storage.transaction(groceries).await.unwrap().unwrap();
+    assert_eq!(saved.amount, -42.50);
+    assert_eq!(saved.transaction_type, "expense");
+    assert_eq!(saved.source.as_deref(), Some("manual"));
+
+    // References are checked the way the schema's foreign keys and triggers do
+    let error = storage
+        .insert_transaction(&transaction(99, "2026-10-02", -1.00, None))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::ForeignKeyViolation(_)),
+        "{error}"
+    );
+
+    storage.archive_account(first_id).await.unwrap();
+    assert!(storage.account(first_id).await.unwrap().is_none());
+    assert_eq!(storage.accounts(true).await.unwrap().len(), 1);
+    let error = storage
+        .insert_transaction(&transaction(first_id, "2026-10-02", -1.00, None))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::InvariantViolation(_)),
+        "{error}"
+    );
+
+    let old_id = storage
+        .insert_category(&category("Old", None))
+        .await
+        .unwrap();
+    storage.archive_category(old_id).await.unwrap();
+    let error = storage
+        .insert_transaction(&transaction(checking_id, "2026-10-02", -1.00, Some(old_id)))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::InvariantViolation(_)),
+        "{error}"
+    );
+    let update = UpdateTransactionRequest {
+        date: "2026-10-02".parse().unwrap(),
+        amount: -40.00,
+        description: None,
+        payee: Some("Safeway".to_string()),
+        memo: None,
+        category_id: Some(old_id),
+        pending: false,
+        cleared: false,
+        tag_ids: None,
+        spending_kind: None,
+    };
+    let error = storage
+        .update_transaction(groceries, &update)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::InvariantViolation(_)),
+        "{error}"
+    );
+
+    storage
+        .update_transaction(
+            groceries,
+            &UpdateTransactionRequest {
+                category_id: Some(food_id),
+                ..update
+            },
+        )
+        .await
+        .unwrap();
+    let saved = storage.transaction(groceries).await.unwrap().unwrap();
+    assert_eq!(
+        (saved.amount, saved.payee.as_deref()),
+        (-40.00, Some("Safeway"))
+    );
+
+    // Trashed transactions are left out of reads
+    storage.delete_transaction(groceries).await.unwrap();
+    assert!(storage.transaction(groceries).await.unwrap().is_none());
+    let remaining = storage.account_transactions(checking_id).await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].amount, -7.25);
+    let food = storage.category(food_id).await.unwrap().unwrap();
+    assert_eq!(food.usage_count, 1);
+    assert_eq!(food.last_used_date, Some("2026-10-05".parse().unwrap()));
+
+    // A transfer is added all or nothing: a bad deposit leaves no withdrawal
+    let error = storage
+        .insert_transfer(
+            &transaction(checking_id, "2026-10-06", -10.00, None),
+            &transaction(99, "2026-10-06", 10.00, None),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::ForeignKeyViolation(_)),
+        "{error}"
+    );
+    let error = storage
+        .insert_transfer(
+            &transaction(checking_id, "2026-10-06", -10.00, None),
+            &transaction(savings_id, "2026-10-06", 10.00, Some(old_id)),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::InvariantViolation(_)),
+        "{error}"
+    );
+    assert_eq!(
+        storage
+            .account_transactions(checking_id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(storage.transfers().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_storage_rules() {
+    check_storage_rules(&MemoryStorage::new()).await;
+    check_storage_rules(&SqliteStorage::new(create_test_pool().await)).await;
+}
+
+async fn check_category_hierarchy<S: Storage>(storage: &S) {
+    let food_id = storage
+        .insert_category(&category("Food", None))
+        .await
+        .unwrap();
+    let groceries_id = storage
+        .insert_category(&category("Groceries", Some(food_id)))
+        .await
+        .unwrap();
+    let produce_id = storage
+        .insert_category(&category("Produce", Some(groceries_id)))
+        .await
+        .unwrap();
+    let old_id = storage
+        .insert_category(&category("Old", None))
+        .await
+        .unwrap();
+    storage.archive_category(old_id).await.unwrap();
+
+    // A category can't go under itself, its own subtree or an archived parent
+    for parent_id in [food_id, produce_id] {
+        let error = storage
+            .move_category(food_id, Some(parent_id))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DatabaseError::Invalid(_)), "{error}");
+    }
+    let error = storage
+        .insert_category(&category("Leftovers", Some(old_id)))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::NotFound { entity: "category", id } if id == old_id),
+        "{error}"
+    );
+    let error = storage
+        .move_category(groceries_id, Some(old_id))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, DatabaseError::NotFound { .. }), "{error}");
+    let error = storage.move_category(1, Some(food_id)).await.unwrap_err();
+    assert!(matches!(error, DatabaseError::Invalid(_)), "{error}");
+
+    // Moving a subtree takes its subcategories along
+    storage.move_category(groceries_id, None).await.unwrap();
+    storage
+        .move_category(food_id, Some(produce_id))
+        .await
+        .unwrap();
+    let food = storage.category(food_id).await.unwrap().unwrap();
+    assert_eq!(food.parent_category_id, Some(produce_id));
+    let produce = storage.category(produce_id).await.unwrap().unwrap();
+    assert_eq!(produce.parent_category_id, Some(groceries_id));
+}
+
+#[tokio::test]
+async fn test_category_hierarchy() {
+    check_category_hierarchy(&MemoryStorage::new()).await;
+    check_category_hierarchy(&SqliteStorage::new(create_test_pool().await)).await;
+}
+
+async fn check_balances_and_transfers<S: Storage>(storage: &S) {
+    let checking_id = storage
+        .insert_account(&account("Checking", None))
+        .await
+        .unwrap();
+    let savings_id = storage
+        .insert_account(&account("Savings", None))
+        .await
+        .unwrap();
+
+    storage
+        .insert_transaction(&transaction(checking_id, "2026-10-01", 1000.10, None))
+        .await
+        .unwrap();
+    storage
+        .insert_transaction(&CreateTransactionRequest {
+            pending: true,
+            ..transaction(checking_id, "2026-10-03", -0.30, None)
+        })
+        .await
+        .unwrap();
+    let trashed = storage
+        .insert_transaction(&transaction(checking_id, "2026-10-04", -500.00, None))
+        .await
+        .unwrap();
+    storage.delete_transaction(trashed).await.unwrap();
+
+    let transfer_id = add_transfer(storage, &transfer(checking_id, savings_id, 250.20))
+        .await
+        .unwrap();
+    let transfers = storage.transfers().await.unwrap();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].id, transfer_id);
+    assert_eq!(transfers[0].transfer_type.as_deref(), Some("internal"));
+
+    let withdrawal = storage
+        .transaction(transfers[0].from_transaction_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(withdrawal.account_id, checking_id);
+    assert_eq!(withdrawal.amount, -250.20);
+    assert_eq!(withdrawal.payee.as_deref(), Some("Savings"));
+    let deposit = storage
+        .transaction(transfers[0].to_transaction_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(deposit.account_id, savings_id);
+    assert_eq!(deposit.amount, 250.20);
+    assert_eq!(deposit.payee.as_deref(), Some("Checking"));
+
+    // Summed in cents, so there is no floating point drift
+    let checking = account_balance(storage, checking_id).await.unwrap();
+    assert_eq!(
+        checking,
+        AccountBalance {
+            account_id: checking_id,
+            balance: 749.60,
+            pending: -0.30,
+            reconciled: 0.0,
+        }
+    );
+    let balances = account_balances(storage).await.unwrap();
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[0], checking);
+    assert_eq!(balances[1].balance, 250.20);
+
+    // Bad transfers are rejected before anything is added
+    let error = add_transfer(storage, &transfer(checking_id, checking_id, -5.00))
+        .await
+        .unwrap_err();
+    let DatabaseError::Validation(fields) = error else {
+        panic!("expected a validation error, got {error}");
+    };
+    let fields: Vec<&str> = fields.iter().map(|field| field.field.as_str()).collect();
+    assert_eq!(fields, ["to_account_id", "amount"]);
+
+    storage.archive_account(savings_id).await.unwrap();
+    let error = add_transfer(storage, &transfer(checking_id, savings_id, 5.00))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, DatabaseError::NotFound { entity: "account", id } if id == savings_id),
+        "{error}"
+    );
+    let error = account_balance(storage, savings_id).await.unwrap_err();
+    assert!(matches!(error, DatabaseError::NotFound { .. }), "{error}");
+
+    assert_eq!(
+        storage
+            .account_transactions(checking_id)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+    assert_eq!(storage.transfers().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_balances_and_transfers() {
+    check_balances_and_transfers(&MemoryStorage::new()).await;
+    check_balances_and_transfers(&SqliteStorage::new(create_test_pool().await)).await;
+}
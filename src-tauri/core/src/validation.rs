@@ -0,0 +1,215 @@
+use crate::database::{
+    CreateAccountRequest, CreateCategoryRequest, CreateTransactionRequest, CreateTransferRequest,
+    UpdateTransactionRequest,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Account types offered by the account form.
+pub const ACCOUNT_TYPES: [&str; 5] = [
+    "checking",
+    "savings",
+    "credit_card",
+    "investment",
+    "balance_only",
+];
+
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_TEXT_LENGTH: usize = 500;
+
+/// Largest amount, in dollars, that still fits in integer cents with room to sum.
+const MAX_AMOUNT: f64 = 1e13;
+
+/// Range of dates SQLite's date functions can store and compare.
+const MIN_DATE: NaiveDate = NaiveDate::from_ymd_opt(1, 1, 1).unwrap();
+const MAX_DATE: NaiveDate = NaiveDate::from_ymd_opt(9999, 12, 31).unwrap();
+
+/// A problem with one field of a request, keyed by the field's serialized name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Collects every field error of a request instead of stopping at the first.
+#[derive(Default)]
+struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    fn error(&mut self, field: &str, message: &str) {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    fn name(&mut self, field: &str, value: &str) {
+        if value.trim().is_empty() {
+            self.error(field, "is required");
+        } else if value.chars().count() > MAX_NAME_LENGTH {
+            self.error(field, "must be at most 100 characters");
+        }
+    }
+
+    fn optional_text(&mut self, field: &str, value: Option<&str>) {
+        if value.is_some_and(|value| value.chars().count() > MAX_TEXT_LENGTH) {
+            self.error(field, "must be at most 500 characters");
+        }
+    }
+
+    fn amount(&mut self, field: &str, value: f64) {
+        if !value.is_finite() || value.abs() >= MAX_AMOUNT {
+            self.error(field, "must be a valid amount");
+        }
+    }
+
+    fn date(&mut self, field: &str, value: NaiveDate) {
+        if !(MIN_DATE..=MAX_DATE).contains(&value) {
+            self.error(field, "must be a date in YYYY-MM-DD format");
+        }
+    }
+
+    fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Deserializes the date of a request, keeping text that isn't a date for
+/// validation to report.
+///
+/// Failing to deserialize would reject the whole request with no field to
+/// point the form at. Instead such text becomes a date before [`MIN_DATE`],
+/// which validation reports as an invalid `date` field like any other.
+///
+/// # Examples
+///
+/// ```rust
+/// #[serde(deserialize_with = "validation::deserialize_date")]
+/// pub date: NaiveDate,
+/// ```
+pub fn deserialize_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    Ok(text.parse().unwrap_or(NaiveDate::MIN))
+}
+
+/// Checks an account request before it is inserted or used for an update.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Every field is valid
+/// - `Err(Vec<FieldError>)` - One entry per invalid field, in field order
+pub fn validate_account(request: &CreateAccountRequest) -> Result<(), Vec<FieldError>> {
+    let mut validator = Validator::default();
+
+    validator.name("name", &request.name);
+    if !ACCOUNT_TYPES.contains(&request.account_type.as_str()) {
+        validator.error(
+            "account_type",
+            "must be one of checking, savings, credit_card, investment, balance_only",
+        );
+    }
+    validator.optional_text("institution", request.institution.as_deref());
+    if let Some(balance) = request.current_balance {
+        validator.amount("current_balance", balance);
+    }
+    if let Some(last4) = &request.account_number_last4
+        && (last4.len() != 4 || !last4.chars().all(|c| c.is_ascii_digit()))
+    {
+        validator.error("account_number_last4", "must be exactly 4 digits");
+    }
+
+    validator.finish()
+}
+
+/// Checks a category request before it is inserted or used for an update.
+///
+/// Whether the parent category exists is checked against the database when the
+/// request is applied.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Every field is valid
+/// - `Err(Vec<FieldError>)` - One entry per invalid field, in field order
+pub fn validate_category(request: &CreateCategoryRequest) -> Result<(), Vec<FieldError>> {
+    let mut validator = Validator::default();
+
+    validator.name("name", &request.name);
+
+    validator.finish()
+}
+
+/// Checks a new transaction before it is inserted.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Every field is valid
+/// - `Err(Vec<FieldError>)` - One entry per invalid field, in field order
+pub fn validate_transaction(request: &CreateTransactionRequest) -> Result<(), Vec<FieldError>> {
+    let mut validator = Validator::default();
+
+    validator.date("date", request.date);
+    validator.amount("amount", request.amount);
+    validator.optional_text("description", request.description.as_deref());
+    validator.optional_text("payee", request.payee.as_deref());
+    validator.optional_text("memo", request.memo.as_deref());
+
+    validator.finish()
+}
+
+/// Checks a transaction update before it is applied.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Every field is valid
+/// - `Err(Vec<FieldError>)` - One entry per invalid field, in field order
+pub fn validate_transaction_update(
+    request: &UpdateTransactionRequest,
+) -> Result<(), Vec<FieldError>> {
+    let mut validator = Validator::default();
+
+    validator.date("date", request.date);
+    validator.amount("amount", request.amount);
+    validator.optional_text("description", request.description.as_deref());
+    validator.optional_text("payee", request.payee.as_deref());
+    validator.optional_text("memo", request.memo.as_deref());
+
+    validator.finish()
+}
+
+/// Checks a transfer before its transactions are added.
+///
+/// Whether both accounts exist and are active is checked against storage when
+/// the transfer is made.
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Every field is valid
+/// - `Err(Vec<FieldError>)` - One entry per invalid field, in field order
+pub fn validate_transfer(request: &CreateTransferRequest) -> Result<(), Vec<FieldError>> {
+    let mut validator = Validator::default();
+
+    if request.to_account_id == request.from_account_id {
+        validator.error("to_account_id", "must be a different account");
+    }
+    validator.date("date", request.date);
+    if request.amount <= 0.0 {
+        validator.error("amount", "must be greater than zero");
+    } else {
+        validator.amount("amount", request.amount);
+    }
+    validator.optional_text("description", request.description.as_deref());
+    validator.optional_text("memo", request.memo.as_deref());
+
+    validator.finish()
+}
@@ -1,15 +1,18 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod database;
-
-#[cfg(test)]
-mod tests;
+use std::path::Path;
+use tally_core::{
+    backup, database, encryption, events, export, ledger, reports, storage, validation,
+};
+use tauri::{Emitter, Manager};
 
 /// Entry point for the Tally personal finance application.
 ///
 /// Initializes the SQLite database connection, creates required tables,
 /// and starts the Tauri desktop application with registered command handlers.
+/// An encrypted database starts locked: no connection pool is managed, so every
+/// command that needs the database fails until `unlock_database` opens it.
 ///
 /// # Returns
 ///
@@ -26,71 +29,182 @@ mod tests;
 /// - Application configuration is invalid
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let pool = initialize_database().await?;
+    let backups = backup::BackupConfig::from_env(backup::BACKUP_DIR)?;
+    let builder = tauri::Builder::default();
+    let builder = if encryption::is_encrypted(Path::new(database::DATABASE_PATH))? {
+        builder
+    } else {
+        builder.manage(initialize_database(None, &backups).await?)
+    };
 
-    tauri::Builder::default()
-        .manage(pool)
+    builder
+        .manage(backups)
         .invoke_handler(tauri::generate_handler![
             get_accounts,
             add_account,
             get_account,
+            get_account_balances,
             update_account,
             archive_account,
+            get_archived_accounts,
+            restore_account,
             get_categories,
             add_category,
             get_category,
             update_category,
             archive_category,
+            get_archived_categories,
+            restore_category,
+            merge_categories,
+            get_category_tree,
+            suggest_categories,
+            move_category,
             add_transaction,
+            add_transfer,
             get_transaction,
             update_transaction,
             delete_transaction,
-            get_transactions
+            get_trash,
+            restore_transaction,
+            purge_trash,
+            bulk_update_transactions,
+            get_transactions,
+            search_transactions,
+            get_tags,
+            add_tag,
+            update_tag,
+            delete_tag,
+            tag_transactions,
+            untag_transactions,
+            get_spending_report,
+            get_spending_split_report,
+            add_attachment,
+            get_attachments,
+            get_attachment_data,
+            delete_attachment,
+            get_change_history,
+            undo,
+            redo,
+            get_undo_stack,
+            create_backup,
+            get_backups,
+            restore_backup,
+            get_database_status,
+            unlock_database,
+            enable_encryption,
+            change_passphrase,
+            disable_encryption,
+            export_data,
+            import_data,
+            export_ledger,
+            import_ledger
         ])
         .run(tauri::generate_context!())?;
 
     Ok(())
 }
 
+/// Number of days a deleted transaction stays in the trash before it is purged.
+const TRASH_RETENTION_DAYS: u32 = 30;
+
 /// Initializes the SQLite database connection and creates all required tables.
 ///
 /// Creates a connection pool to the SQLite database file (`tally.db`) and ensures
-/// all necessary tables exist for the application to function properly.
+/// all necessary tables exist for the application to function properly. An
+/// existing database is backed up first, before any schema changes.
+///
+/// # Arguments
+///
+/// * `passphrase` - Passphrase of an encrypted database, `None` otherwise
+/// * `backups` - Where the startup backup is written and how many are kept
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing:
 /// - `Ok(SqlitePool)` - A connection pool ready for database operations
-/// - `Err(sqlx::Error)` - Database connection or table creation error
+/// - `Err(Box<dyn Error>)` - Database connection, backup, table creation or trash purge error
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// - Database file cannot be created or accessed
 /// - SQLite connection cannot be established
+/// - The passphrase doesn't decrypt the database
+/// - The startup backup cannot be written
 /// - Any required table creation fails (accounts, transactions, categories, transfers)
 ///
 /// # Tables Created
 ///
 /// - `accounts` - User financial accounts
 /// - `transactions` - Financial transactions
+/// - `transactions_fts` - Full-text search index over transaction text
 /// - `categories` - Transaction categories
 /// - `transfers` - Money transfers between accounts
-async fn initialize_database() -> Result<sqlx::SqlitePool, sqlx::Error> {
-    let pool = database::create_connection().await?;
+/// - `tags` / `transaction_tags` - Cross-cutting transaction labels
+/// - `attachments` - Receipts and documents linked to transactions
+/// - `change_log` - Append-only history of changes to financial data
+/// - `operations` - Undo and redo stack over the change log
+async fn initialize_database(
+    passphrase: Option<&str>,
+    backups: &backup::BackupConfig,
+) -> Result<sqlx::SqlitePool, Box<dyn std::error::Error>> {
+    let pool = encryption::unlock(Path::new(database::DATABASE_PATH), passphrase).await?;
+
+    // Back up before migrating, so a failed migration can be rolled back
+    backup::backup_on_startup(&pool, backups).await?;
 
-    database::create_accounts_table(&pool).await?;
-    database::create_transactions_table(&pool).await?;
-    database::create_categories_table(&pool).await?;
-    database::
-    create_transfers_table(&pool).await?;
+    database::create_schema(&pool).await?;
 
-    // Seed default system categories
-    database::seed_default_categories(&pool).await?;
+    // Apply the trash retention policy
+    database::purge_trash(&pool, TRASH_RETENTION_DAYS, database::ChangeSource::Schedule).await?;
 
     Ok(pool)
 }
 
+/// Error returned by commands that save a user-edited form.
+///
+/// `fields` lists every invalid field of the request so the form can highlight
+/// them all at once; it is empty when the request failed for another reason.
+#[derive(Debug, serde::Serialize)]
+struct CommandError {
+    message: String,
+    fields: Vec<validation::FieldError>,
+}
+
+impl CommandError {
+    fn new(context: &str, error: database::DatabaseError) -> Self {
+        let message = format!("{context}: {error}");
+        let fields = match error {
+            database::DatabaseError::Validation(fields) => fields,
+            _ => Vec::new(),
+        };
+        Self { message, fields }
+    }
+}
+
+/// Runs a command's change, then emits a `*-changed` event for every record it
+/// changed so each window can refresh just those records.
+///
+/// Changes are captured with `events::capture` as the command writes them, so
+/// bulk changes, merges, undo and redo and imports are covered as well, and
+/// changes other commands make at the same time are left to those commands.
+/// Events are best effort: the change has already been saved, so a failure to
+/// deliver them doesn't fail the command.
+async fn with_change_events<T, E>(
+    app: &tauri::AppHandle,
+    change: impl std::future::Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let (result, changes) = events::capture(change).await;
+
+    if result.is_ok() {
+        for event in changes {
+            let _ = app.emit(event.name(), &event);
+        }
+    }
+
+    result
+}
+
 /// Retrieves all non-archived accounts from the database.
 ///
 /// This Tauri command fetches all active financial accounts, including their
@@ -134,6 +248,7 @@ async fn get_accounts(
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `request` - Account creation data including name, type, and optional fields
 ///
@@ -141,7 +256,7 @@ async fn get_accounts(
 ///
 /// Returns a `Result` containing:
 /// - `Ok(i64)` - The ID of the newly created account
-/// - `Err(String)` - Formatted error message if database operation fails
+/// - `Err(CommandError)` - Error message, plus every invalid field if validation fails
 ///
 /// # Request Fields
 ///
@@ -169,13 +284,13 @@ async fn get_accounts(
 /// ```
 #[tauri::command]
 async fn add_account(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     request: database::CreateAccountRequest,
-) -> Result<i64, String> {
-    match database::insert_account(&pool, &request).await {
-        Ok(id) => Ok(id),
-        Err(e) => Err(format!("Failed to add account: {e}")),
-    }
+) -> Result<i64, CommandError> {
+    with_change_events(&app, database::insert_account(&pool, &request))
+        .await
+        .map_err(|e| CommandError::new("Failed to add account", e))
 }
 
 /// Retrieves a specific account by its unique ID.
@@ -216,6 +331,32 @@ async fn get_account(
         .map_err(|e| e.to_string())
 }
 
+/// Works out the balance of every active account from its transactions.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<AccountBalance>)` - Total, pending and reconciled balance per account
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const balances = await invoke('get_account_balances');
+/// ```
+#[tauri::command]
+async fn get_account_balances(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+) -> Result<Vec<database::AccountBalance>, String> {
+    storage::account_balances(&storage::SqliteStorage::new(pool.inner().clone()))
+        .await
+        .map_err(|e| format!("Failed to get account balances: {e}"))
+}
+
 /// Updates an existing account with new information.
 ///
 /// This Tauri command modifies an existing account record with the provided data
@@ -223,6 +364,7 @@ async fn get_account(
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `account_id` - The ID of the account to update
 /// * `request` - Account update data including name, type, and optional fields
@@ -231,7 +373,7 @@ async fn get_account(
 ///
 /// Returns a `Result` containing:
 /// - `Ok(())` - Account successfully updated
-/// - `Err(String)` - Formatted error message if database operation fails
+/// - `Err(CommandError)` - Error message, plus every invalid field if validation fails
 ///
 /// # Request Fields
 ///
@@ -254,13 +396,14 @@ async fn get_account(
 /// ```
 #[tauri::command]
 async fn update_account(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     account_id: i64,
     request: database::CreateAccountRequest,
-) -> Result<(), String> {
-    database::update_account(&pool, account_id, &request)
+) -> Result<(), CommandError> {
+    with_change_events(&app, database::update_account(&pool, account_id, &request))
         .await
-        .map_err(|e| format!("Failed to update account: {e}"))
+        .map_err(|e| CommandError::new("Failed to update account", e))
 }
 
 /// Archives (soft deletes) an account by marking it as archived.
@@ -270,6 +413,7 @@ async fn update_account(
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `account_id` - The ID of the account to archive
 ///
@@ -286,14 +430,71 @@ async fn update_account(
 /// ```
 #[tauri::command]
 async fn archive_account(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     account_id: i64,
 ) -> Result<(), String> {
-    database::archive_account(&pool, account_id)
+    with_change_events(&app, database::archive_account(&pool, account_id))
         .await
         .map_err(|e| format!("Failed to archive account: {e}"))
 }
 
+/// Lists archived accounts.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Account>)` - Archived accounts, ordered like the active list
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const archived = await invoke('get_archived_accounts');
+/// ```
+#[tauri::command]
+async fn get_archived_accounts(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+) -> Result<Vec<database::Account>, String> {
+    database::get_archived_accounts(&pool)
+        .await
+        .map_err(|e| format!("Failed to get archived accounts: {e}"))
+}
+
+/// Restores an archived account, making it active again.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `account_id` - The ID of the archived account
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Account restored
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('restore_account', { accountId: 123 });
+/// ```
+#[tauri::command]
+async fn restore_account(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    account_id: i64,
+) -> Result<(), String> {
+    with_change_events(&app, database::restore_account(&pool, account_id))
+        .await
+        .map_err(|e| format!("Failed to restore account: {e}"))
+}
+
 /// Creates a new category in the database.
 ///
 /// This Tauri command accepts a category creation request with all user-settable
@@ -302,6 +503,7 @@ async fn archive_account(
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `request` - Category creation data including name and optional fields
 ///
@@ -309,7 +511,7 @@ async fn archive_account(
 ///
 /// Returns a `Result` containing:
 /// - `Ok(i64)` - The ID of the newly created category
-/// - `Err(String)` - Formatted error message if database operation fails
+/// - `Err(CommandError)` - Error message, plus every invalid field if validation fails
 ///
 /// # Request Fields
 ///
@@ -333,13 +535,13 @@ async fn archive_account(
 /// ```
 #[tauri::command]
 async fn add_category(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     request: database::CreateCategoryRequest,
-) -> Result<i64, String> {
-    match database::insert_category(&pool, &request).await {
-        Ok(id) => Ok(id),
-        Err(e) => Err(format!("Failed to add category: {e}")),
-    }
+) -> Result<i64, CommandError> {
+    with_change_events(&app, database::insert_category(&pool, &request))
+        .await
+        .map_err(|e| CommandError::new("Failed to add category", e))
 }
 
 /// Retrieves all non-archived categories from the database.
@@ -421,6 +623,7 @@ async fn get_category(
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `category_id` - The ID of the category to update
 /// * `request` - Category update data including name and optional fields
@@ -429,7 +632,7 @@ async fn get_category(
 ///
 /// Returns a `Result` containing:
 /// - `Ok(())` - Category successfully updated
-/// - `Err(String)` - Formatted error message if database operation fails
+/// - `Err(CommandError)` - Error message, plus every invalid field if validation fails
 ///
 /// # Request Fields
 ///
@@ -450,13 +653,17 @@ async fn get_category(
 /// ```
 #[tauri::command]
 async fn update_category(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     category_id: i64,
     request: database::CreateCategoryRequest,
-) -> Result<(), String> {
-    database::update_category(&pool, category_id, &request)
-        .await
-        .map_err(|e| format!("Failed to update category: {e}"))
+) -> Result<(), CommandError> {
+    with_change_events(
+        &app,
+        database::update_category(&pool, category_id, &request),
+    )
+    .await
+    .map_err(|e| CommandError::new("Failed to update category", e))
 }
 
 /// Archives (soft deletes) a category by marking it as archived.
@@ -467,6 +674,7 @@ async fn update_category(
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `category_id` - The ID of the category to archive
 ///
@@ -483,14 +691,207 @@ async fn update_category(
 /// ```
 #[tauri::command]
 async fn archive_category(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     category_id: i64,
 ) -> Result<(), String> {
-    database::archive_category(&pool, category_id)
+    with_change_events(&app, database::archive_category(&pool, category_id))
         .await
         .map_err(|e| format!("Failed to archive category: {e}"))
 }
 
+/// Lists archived categories.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Category>)` - Archived categories, ordered like the active list
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const archived = await invoke('get_archived_categories');
+/// ```
+#[tauri::command]
+async fn get_archived_categories(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+) -> Result<Vec<database::Category>, String> {
+    database::get_archived_categories(&pool)
+        .await
+        .map_err(|e| format!("Failed to get archived categories: {e}"))
+}
+
+/// Restores an archived category, making it active again.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `category_id` - The ID of the archived category
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Category restored
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('restore_category', { categoryId: 123 });
+/// ```
+#[tauri::command]
+async fn restore_category(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    category_id: i64,
+) -> Result<(), String> {
+    with_change_events(&app, database::restore_category(&pool, category_id))
+        .await
+        .map_err(|e| format!("Failed to restore category: {e}"))
+}
+
+/// Merges one category into another.
+///
+/// This Tauri command moves every transaction of the source category to the
+/// target, re-parents the source's subcategories under the target and archives
+/// the source. It all happens atomically and is undone as a single step.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `source_id` - The category to merge away
+/// * `target_id` - The category that receives its transactions and subcategories
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions moved
+/// - `Err(String)` - Formatted error message, e.g. when merging a category into
+///   its own subcategory
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const moved = await invoke('merge_categories', { sourceId: 12, targetId: 4 });
+/// ```
+#[tauri::command]
+async fn merge_categories(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    source_id: i64,
+    target_id: i64,
+) -> Result<u64, String> {
+    with_change_events(
+        &app,
+        database::merge_categories(&pool, source_id, target_id),
+    )
+    .await
+    .map_err(|e| format!("Failed to merge categories: {e}"))
+}
+
+/// Retrieves the active categories as a tree.
+///
+/// Categories come back depth first in display order, each with its depth and
+/// full path, ready to render as an indented list or a nested menu.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<CategoryTreeNode>)` - `{ category, depth, path }` for every active category
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const tree = await invoke('get_category_tree');
+/// // tree[1] => { category: {...}, depth: 1, path: "Food > Groceries" }
+/// ```
+#[tauri::command]
+async fn get_category_tree(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+) -> Result<Vec<database::CategoryTreeNode>, String> {
+    database::get_category_tree(&pool)
+        .await
+        .map_err(|e| format!("Failed to get category tree: {e}"))
+}
+
+/// Suggests categories for a transaction being entered.
+///
+/// Ranks the categories the payee was given before first, then the most
+/// recently and most often used ones, so the form can preselect the best match.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `payee` - Payee typed so far, or `null` for recently used categories only
+/// * `limit` - Maximum number of suggestions, defaults to 5
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<CategorySuggestion>)` - `{ category, payee_uses }`, best match first
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const suggestions = await invoke('suggest_categories', { payee: 'Safeway', limit: 5 });
+/// form.categoryId = suggestions[0]?.category.id ?? null;
+/// ```
+#[tauri::command]
+async fn suggest_categories(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    payee: Option<String>,
+    limit: Option<i32>,
+) -> Result<Vec<database::CategorySuggestion>, String> {
+    database::suggest_categories(&pool, payee.as_deref(), limit.unwrap_or(5))
+        .await
+        .map_err(|e| format!("Failed to suggest categories: {e}"))
+}
+
+/// Moves a category and its subcategories under a new parent.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `category_id` - The category to move
+/// * `parent_id` - The new parent, or `null` for the top level
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Category moved
+/// - `Err(String)` - Formatted error message, e.g. when the move would create a cycle
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('move_category', { categoryId: 12, parentId: 4 });
+/// ```
+#[tauri::command]
+async fn move_category(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    category_id: i64,
+    parent_id: Option<i64>,
+) -> Result<(), String> {
+    with_change_events(&app, database::move_category(&pool, category_id, parent_id))
+        .await
+        .map_err(|e| format!("Failed to move category: {e}"))
+}
+
 /// Creates a new transaction in the database.
 ///
 /// This Tauri command accepts a transaction creation request with all user-settable
@@ -499,6 +900,7 @@ async fn archive_category(
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `request` - Transaction creation data including amount, date, and optional fields
 ///
@@ -506,7 +908,7 @@ async fn archive_category(
 ///
 /// Returns a `Result` containing:
 /// - `Ok(i64)` - The ID of the newly created transaction
-/// - `Err(String)` - Formatted error message if database operation fails
+/// - `Err(CommandError)` - Error message, plus every invalid field if validation fails
 ///
 /// # Request Fields
 ///
@@ -519,6 +921,8 @@ async fn archive_category(
 /// - `category_id` - Category ID for categorization (optional)
 /// - `pending` - Whether transaction is pending (required)
 /// - `cleared` - Whether transaction has cleared (required)
+/// - `tag_ids` - Tags to apply to the transaction (optional)
+/// - `spending_kind` - `"fixed"` or `"discretionary"` to override the category; omit to inherit it (optional)
 ///
 /// # Frontend Usage
 ///
@@ -532,19 +936,65 @@ async fn archive_category(
 ///   memo: "Weekly groceries",
 ///   category_id: 5,
 ///   pending: false,
-///   cleared: true
+///   cleared: true,
+///   tag_ids: [3]
 /// };
 /// const transactionId = await invoke('add_transaction', { request });
 /// ```
 #[tauri::command]
 async fn add_transaction(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     request: database::CreateTransactionRequest,
-) -> Result<i64, String> {
-    match database::insert_transaction(&pool, &request).await {
-        Ok(id) => Ok(id),
-        Err(e) => Err(format!("Failed to add transaction: {e}")),
-    }
+) -> Result<i64, CommandError> {
+    with_change_events(&app, database::insert_transaction(&pool, &request))
+        .await
+        .map_err(|e| CommandError::new("Failed to add transaction", e))
+}
+
+/// Moves money between two accounts.
+///
+/// Adds a withdrawal and a matching deposit, each with the other account as its
+/// payee, and links them as a transfer.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `request` - The two accounts, date and positive amount to move
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The ID of the new transfer
+/// - `Err(CommandError)` - Error message, plus every invalid field if validation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const request = {
+///   from_account_id: 1,
+///   to_account_id: 2,
+///   date: "2026-10-01",
+///   amount: 250.00,
+///   description: "Monthly savings",
+///   memo: null,
+///   cleared: true
+/// };
+/// const transferId = await invoke('add_transfer', { request });
+/// ```
+#[tauri::command]
+async fn add_transfer(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    request: database::CreateTransferRequest,
+) -> Result<i64, CommandError> {
+    with_change_events(
+        &app,
+        storage::add_transfer(&storage::SqliteStorage::new(pool.inner().clone()), &request),
+    )
+    .await
+    .map_err(|e| CommandError::new("Failed to add transfer", e))
 }
 
 /// Retrieves a specific transaction by its unique ID.
@@ -593,6 +1043,7 @@ async fn get_transaction(
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `transaction_id` - The ID of the transaction to update
 /// * `request` - Transaction update data including amount, date, and optional fields
@@ -601,7 +1052,7 @@ async fn get_transaction(
 ///
 /// Returns a `Result` containing:
 /// - `Ok(())` - Transaction successfully updated
-/// - `Err(String)` - Formatted error message if database operation fails
+/// - `Err(CommandError)` - Error message, plus every invalid field if validation fails
 ///
 /// # Request Fields
 ///
@@ -614,6 +1065,8 @@ async fn get_transaction(
 /// - `category_id` - Category ID for categorization (optional)
 /// - `pending` - Whether transaction is pending (required)
 /// - `cleared` - Whether transaction has cleared (required)
+/// - `tag_ids` - Replacement set of tags; omit to leave tags unchanged (optional)
+/// - `spending_kind` - `"fixed"` or `"discretionary"` to override the category; omit to inherit it (optional)
 ///
 /// # Frontend Usage
 ///
@@ -632,29 +1085,35 @@ async fn get_transaction(
 /// ```
 #[tauri::command]
 async fn update_transaction(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     transaction_id: i64,
     request: database::UpdateTransactionRequest,
-) -> Result<(), String> {
-    database::update_transaction(&pool, transaction_id, &request)
-        .await
-        .map_err(|e| format!("Failed to update transaction: {e}"))
+) -> Result<(), CommandError> {
+    with_change_events(
+        &app,
+        database::update_transaction(&pool, transaction_id, &request),
+    )
+    .await
+    .map_err(|e| CommandError::new("Failed to update transaction", e))
 }
 
-/// Deletes a transaction from the database.
+/// Moves a transaction to the trash.
 ///
-/// This Tauri command permanently removes a transaction record from the database.
-/// This operation cannot be undone, so use with caution.
+/// This Tauri command hides the transaction from listings, search and reports
+/// while keeping it, its tags and its attachments in the database. Trashed
+/// transactions can be restored until they are purged.
 ///
 /// # Arguments
 ///
+/// * `app` - Handle used to emit change events
 /// * `pool` - Tauri-managed SQLite connection pool state
 /// * `transaction_id` - The ID of the transaction to delete
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing:
-/// - `Ok(())` - Transaction successfully deleted
+/// - `Ok(())` - Transaction moved to the trash
 /// - `Err(String)` - Formatted error message if operation fails
 ///
 /// # Frontend Usage
@@ -664,23 +1123,1260 @@ async fn update_transaction(
 /// ```
 #[tauri::command]
 async fn delete_transaction(
+    app: tauri::AppHandle,
     pool: tauri::State<'_, sqlx::SqlitePool>,
     transaction_id: i64,
 ) -> Result<(), String> {
-    database::delete_transaction(&pool, transaction_id)
+    with_change_events(&app, database::delete_transaction(&pool, transaction_id))
         .await
         .map_err(|e| format!("Failed to delete transaction: {e}"))
 }
 
+/// Lists the transactions in the trash, most recently deleted first.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<TrashedTransaction>)` - Trashed transactions with their `deleted_at` time
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const trash = await invoke('get_trash');
+/// trash.forEach(({ transaction, deleted_at }) => console.log(transaction.description, deleted_at));
+/// ```
 #[tauri::command]
-async fn get_transactions(
+async fn get_trash(
     pool: tauri::State<'_, sqlx::SqlitePool>,
-    account_id: i64,
-    limit: i32,
-    offset: i32,
-) -> Result<Vec<database::Transaction>, String> {
-    match database::get_transactions(&pool, account_id, limit, offset).await {
-        Ok(transactions) => Ok(transactions),
-        Err(e) => Err(format!("Failed to get transactions: {e}")),
-    }
+) -> Result<Vec<database::TrashedTransaction>, String> {
+    database::get_trash(&pool)
+        .await
+        .map_err(|e| format!("Failed to get trash: {e}"))
+}
+
+/// Takes a transaction back out of the trash.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `transaction_id` - The ID of the trashed transaction
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Transaction restored
+/// - `Err(String)` - Formatted error message if operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('restore_transaction', { transactionId: 123 });
+/// ```
+#[tauri::command]
+async fn restore_transaction(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    transaction_id: i64,
+) -> Result<(), String> {
+    with_change_events(&app, database::restore_transaction(&pool, transaction_id))
+        .await
+        .map_err(|e| format!("Failed to restore transaction: {e}"))
+}
+
+/// Permanently removes transactions from the trash.
+///
+/// Trashed transactions are also purged automatically on startup once they are
+/// older than `TRASH_RETENTION_DAYS`. Purging can't be undone.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `backups` - Backup settings; the database is backed up before purging
+/// * `older_than_days` - Only purge transactions trashed at least this many days
+///   ago; omit to empty the trash
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions purged
+/// - `Err(String)` - Formatted error message if operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const purged = await invoke('purge_trash');
+/// ```
+#[tauri::command]
+async fn purge_trash(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    backups: tauri::State<'_, backup::BackupConfig>,
+    older_than_days: Option<u32>,
+) -> Result<u64, String> {
+    backup::create_backup(&pool, &backups, backup::BackupReason::PurgeTrash)
+        .await
+        .map_err(|e| format!("Failed to back up before purging trash: {e}"))?;
+
+    with_change_events(
+        &app,
+        database::purge_trash(
+            &pool,
+            older_than_days.unwrap_or(0),
+            database::ChangeSource::Manual,
+        ),
+    )
+    .await
+    .map_err(|e| format!("Failed to purge trash: {e}"))
+}
+
+/// Applies one change to many transactions at once.
+///
+/// All targeted transactions are changed in a single database transaction, so
+/// the command either fully succeeds or changes nothing, and it is undone as a
+/// single step.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `backups` - Backup settings; the database is backed up before a bulk delete
+/// * `target` - `{ ids: [...] }` or `{ filter: TransactionSearchRequest }`
+/// * `change` - The change to apply, tagged by `type`
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions that changed
+/// - `Err(String)` - Formatted error message if operation fails
+///
+/// # Change Types
+///
+/// - `set_category` with `category_id` (or `null` to uncategorize)
+/// - `set_payee` with `payee`
+/// - `set_cleared` with `cleared`
+/// - `add_tag` / `remove_tag` with `tag_id`
+/// - `move_to_account` with `account_id`
+/// - `delete` to move the transactions to the trash
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const changed = await invoke('bulk_update_transactions', {
+///   target: { ids: selectedIds },
+///   change: { type: 'set_category', category_id: 7 }
+/// });
+/// ```
+#[tauri::command]
+async fn bulk_update_transactions(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    backups: tauri::State<'_, backup::BackupConfig>,
+    target: database::BulkTarget,
+    change: database::BulkChange,
+) -> Result<u64, String> {
+    if matches!(change, database::BulkChange::Delete) {
+        backup::create_backup(&pool, &backups, backup::BackupReason::BulkDelete)
+            .await
+            .map_err(|e| format!("Failed to back up before deleting transactions: {e}"))?;
+    }
+
+    with_change_events(
+        &app,
+        database::bulk_update_transactions(&pool, &target, &change),
+    )
+    .await
+    .map_err(|e| format!("Failed to update transactions: {e}"))
+}
+
+/// Retrieves a page of transactions for an account, newest first.
+///
+/// This Tauri command pages through an account's transactions using an opaque
+/// cursor rather than an offset, so deep pages stay fast and concurrent inserts
+/// don't cause rows to be skipped or repeated between pages.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `account_id` - The account whose transactions to list
+/// * `limit` - Maximum number of transactions to return
+/// * `cursor` - The `next_cursor` from the previous page, or `null` for the first page
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(TransactionPage)` - Transactions plus `next_cursor` (`null` on the last page)
+/// - `Err(String)` - Formatted error message if the cursor is invalid or the query fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const page = await invoke('get_transactions', { accountId: 1, limit: 50, cursor: null });
+/// if (page.next_cursor) {
+///   const more = await invoke('get_transactions', { accountId: 1, limit: 50, cursor: page.next_cursor });
+/// }
+/// ```
+#[tauri::command]
+async fn get_transactions(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    account_id: i64,
+    limit: i32,
+    cursor: Option<String>,
+) -> Result<database::TransactionPage, String> {
+    let cursor = match cursor.as_deref() {
+        Some(encoded) => Some(
+            database::TransactionCursor::decode(encoded)
+                .ok_or_else(|| "Failed to get transactions: invalid cursor".to_string())?,
+        ),
+        None => None,
+    };
+
+    database::get_transactions(&pool, account_id, limit, cursor.as_ref())
+        .await
+        .map_err(|e| format!("Failed to get transactions: {e}"))
+}
+
+/// Searches transactions by description, memo, payee and original description.
+///
+/// This Tauri command runs a ranked full-text search over transaction text and
+/// returns each match with a highlighted snippet for display in search results.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `request` - Search text plus optional account and tag filters and result limit
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<TransactionSearchResult>)` - Matches ordered by relevance, best first
+/// - `Err(String)` - Formatted error message if the search fails
+///
+/// # Request Fields
+///
+/// - `query` - Search text; supports prefixes (`groc*`) and phrases (`"whole foods"`) (required)
+/// - `account_id` - Restrict results to a single account (optional)
+/// - `tag_ids` - Only match transactions carrying all of these tags (optional)
+/// - `limit` - Maximum number of results, defaults to 50 (optional)
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const results = await invoke('search_transactions', {
+///   request: { query: 'groc*', account_id: null, tag_ids: null, limit: 25 }
+/// });
+/// results.forEach(r => console.log(r.snippet, r.transaction.amount));
+/// ```
+#[tauri::command]
+async fn search_transactions(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    request: database::TransactionSearchRequest,
+) -> Result<Vec<database::TransactionSearchResult>, String> {
+    database::search_transactions(&pool, &request)
+        .await
+        .map_err(|e| format!("Failed to search transactions: {e}"))
+}
+
+/// Retrieves all tags, ordered alphabetically by name.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Tag>)` - All tags
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const tags = await invoke('get_tags');
+/// ```
+#[tauri::command]
+async fn get_tags(pool: tauri::State<'_, sqlx::SqlitePool>) -> Result<Vec<database::Tag>, String> {
+    database::get_tags(&pool)
+        .await
+        .map_err(|e| format!("Failed to get tags: {e}"))
+}
+
+/// Creates a new tag.
+///
+/// Tag names are unique regardless of case, so adding "Vacation-2026" when
+/// "vacation-2026" exists returns an error.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `request` - Tag creation data containing the name
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The ID of the newly created tag
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const tagId = await invoke('add_tag', { request: { name: 'tax-deductible' } });
+/// ```
+#[tauri::command]
+async fn add_tag(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    request: database::CreateTagRequest,
+) -> Result<i64, String> {
+    with_change_events(&app, database::insert_tag(&pool, &request))
+        .await
+        .map_err(|e| format!("Failed to add tag: {e}"))
+}
+
+/// Renames an existing tag.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `tag_id` - The ID of the tag to rename
+/// * `request` - Tag data containing the new name
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Tag successfully renamed
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('update_tag', { tagId: 3, request: { name: 'reimbursable' } });
+/// ```
+#[tauri::command]
+async fn update_tag(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    tag_id: i64,
+    request: database::CreateTagRequest,
+) -> Result<(), String> {
+    with_change_events(&app, database::update_tag(&pool, tag_id, &request))
+        .await
+        .map_err(|e| format!("Failed to update tag: {e}"))
+}
+
+/// Deletes a tag and removes it from every transaction.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `tag_id` - The ID of the tag to delete
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Tag successfully deleted
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('delete_tag', { tagId: 3 });
+/// ```
+#[tauri::command]
+async fn delete_tag(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    tag_id: i64,
+) -> Result<(), String> {
+    with_change_events(&app, database::delete_tag(&pool, tag_id))
+        .await
+        .map_err(|e| format!("Failed to delete tag: {e}"))
+}
+
+/// Applies a tag to a list of transactions.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `transaction_ids` - The transactions to tag
+/// * `tag_id` - The tag to apply
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions that newly received the tag
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const count = await invoke('tag_transactions', { transactionIds: [101, 102], tagId: 3 });
+/// ```
+#[tauri::command]
+async fn tag_transactions(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    transaction_ids: Vec<i64>,
+    tag_id: i64,
+) -> Result<u64, String> {
+    with_change_events(
+        &app,
+        database::tag_transactions(&pool, &transaction_ids, tag_id),
+    )
+    .await
+    .map_err(|e| format!("Failed to tag transactions: {e}"))
+}
+
+/// Removes a tag from a list of transactions.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `transaction_ids` - The transactions to untag
+/// * `tag_id` - The tag to remove
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(u64)` - Number of transactions the tag was removed from
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const count = await invoke('untag_transactions', { transactionIds: [101, 102], tagId: 3 });
+/// ```
+#[tauri::command]
+async fn untag_transactions(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    transaction_ids: Vec<i64>,
+    tag_id: i64,
+) -> Result<u64, String> {
+    with_change_events(
+        &app,
+        database::untag_transactions(&pool, &transaction_ids, tag_id),
+    )
+    .await
+    .map_err(|e| format!("Failed to untag transactions: {e}"))
+}
+
+/// Totals spending over a date range, grouped by category or by tag.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `request` - Date range, optional account filter and grouping
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<SpendingReportRow>)` - One row per category or tag, largest total first
+/// - `Err(String)` - Formatted error message if the report query fails
+///
+/// # Request Fields
+///
+/// - `start_date` - First day of the range, YYYY-MM-DD, inclusive (required)
+/// - `end_date` - Last day of the range, YYYY-MM-DD, inclusive (required)
+/// - `account_id` - Restrict the report to a single account (optional)
+/// - `group_by` - `"category"` or `"tag"` (required)
+/// - `include_archived` - Also count transactions of archived accounts (optional, default `false`)
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const rows = await invoke('get_spending_report', {
+///   request: { start_date: '2026-01-01', end_date: '2026-12-31', account_id: null, group_by: 'tag' }
+/// });
+/// ```
+#[tauri::command]
+async fn get_spending_report(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    request: reports::SpendingReportRequest,
+) -> Result<Vec<reports::SpendingReportRow>, String> {
+    reports::get_spending_report(&pool, &request)
+        .await
+        .map_err(|e| format!("Failed to get spending report: {e}"))
+}
+
+/// Splits monthly spending into fixed and discretionary amounts.
+///
+/// Each transaction counts under its own `spending_kind` when set, otherwise
+/// under its category's `default_fixed` or `default_discretionary` flag.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `request` - Date range and optional account filter
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<SpendingSplitRow>)` - `{ month, fixed, discretionary, unclassified, total }` per month, oldest first
+/// - `Err(String)` - Formatted error message if the report query fails
+///
+/// # Request Fields
+///
+/// - `start_date` - First day of the range, YYYY-MM-DD, inclusive (required)
+/// - `end_date` - Last day of the range, YYYY-MM-DD, inclusive (required)
+/// - `account_id` - Restrict the report to a single account (optional)
+/// - `include_archived` - Also count transactions of archived accounts (optional, default `false`)
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const months = await invoke('get_spending_split_report', {
+///   request: { start_date: '2026-01-01', end_date: '2026-12-31', account_id: null }
+/// });
+/// // months[0] => { month: "2026-01-01", fixed: 1850, discretionary: 640.5, unclassified: 0, total: 2490.5 }
+/// ```
+#[tauri::command]
+async fn get_spending_split_report(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    request: reports::SpendingSplitRequest,
+) -> Result<Vec<reports::SpendingSplitRow>, String> {
+    reports::get_spending_split_report(&pool, &request)
+        .await
+        .map_err(|e| format!("Failed to get spending split report: {e}"))
+}
+
+/// Attaches a file from disk, such as a receipt scan, to a transaction.
+///
+/// This Tauri command reads the file at `path` and stores its content in the
+/// database together with its name, size, SHA-256 checksum and a MIME type
+/// guessed from the extension. The original file can be deleted afterwards.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `transaction_id` - The transaction to attach the file to
+/// * `path` - Absolute path of the file to attach
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(i64)` - The ID of the new attachment
+/// - `Err(String)` - Formatted error message if the file can't be read or stored
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const attachmentId = await invoke('add_attachment', {
+///   transactionId: 123,
+///   path: '/home/me/Downloads/receipt.pdf'
+/// });
+/// ```
+#[tauri::command]
+async fn add_attachment(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    transaction_id: i64,
+    path: String,
+) -> Result<i64, String> {
+    let path = std::path::Path::new(&path);
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read attachment: {e}"))?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| "Failed to add attachment: path has no file name".to_string())?;
+
+    let request = database::CreateAttachmentRequest {
+        transaction_id,
+        file_name,
+        mime_type: None,
+        data,
+    };
+
+    with_change_events(&app, database::insert_attachment(&pool, &request))
+        .await
+        .map_err(|e| format!("Failed to add attachment: {e}"))
+}
+
+/// Lists the attachments of a transaction without their content.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `transaction_id` - The transaction whose attachments to list
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Attachment>)` - Name, MIME type, size and checksum of each attachment
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const attachments = await invoke('get_attachments', { transactionId: 123 });
+/// ```
+#[tauri::command]
+async fn get_attachments(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    transaction_id: i64,
+) -> Result<Vec<database::Attachment>, String> {
+    database::get_attachments(&pool, transaction_id)
+        .await
+        .map_err(|e| format!("Failed to get attachments: {e}"))
+}
+
+/// Retrieves the content of an attachment.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `attachment_id` - The ID of the attachment to load
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Some(Vec<u8>))` - The file content as bytes
+/// - `Ok(None)` - No attachment found with the given ID
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const bytes = await invoke('get_attachment_data', { attachmentId: 7 });
+/// const url = URL.createObjectURL(new Blob([new Uint8Array(bytes)], { type: attachment.mime_type }));
+/// ```
+#[tauri::command]
+async fn get_attachment_data(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    attachment_id: i64,
+) -> Result<Option<Vec<u8>>, String> {
+    database::get_attachment_data(&pool, attachment_id)
+        .await
+        .map_err(|e| format!("Failed to get attachment: {e}"))
+}
+
+/// Removes an attachment from its transaction.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `attachment_id` - The ID of the attachment to remove
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - Attachment successfully removed
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('delete_attachment', { attachmentId: 7 });
+/// ```
+#[tauri::command]
+async fn delete_attachment(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    attachment_id: i64,
+) -> Result<(), String> {
+    with_change_events(&app, database::delete_attachment(&pool, attachment_id))
+        .await
+        .map_err(|e| format!("Failed to delete attachment: {e}"))
+}
+
+/// Retrieves the change history of a transaction, account, category or tag.
+///
+/// Every change is recorded with where it came from (manual edit, import, rule or
+/// schedule), when it happened, and the full record before and after as JSON.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `entity_type` - One of `"transaction"`, `"account"`, `"category"` or `"tag"`
+/// * `entity_id` - The ID of the record
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<ChangeLogEntry>)` - Changes ordered from oldest to newest
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const history = await invoke('get_change_history', { entityType: 'transaction', entityId: 123 });
+/// history.forEach(entry => console.log(entry.action, entry.source, entry.before, entry.after));
+/// ```
+#[tauri::command]
+async fn get_change_history(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    entity_type: database::EntityType,
+    entity_id: i64,
+) -> Result<Vec<database::ChangeLogEntry>, String> {
+    database::get_change_history(&pool, entity_type, entity_id)
+        .await
+        .map_err(|e| format!("Failed to get change history: {e}"))
+}
+
+/// Undoes the last `count` data-changing commands.
+///
+/// Covers adding, updating, archiving and deleting accounts, categories,
+/// transactions and tags. All operations are reversed in one database
+/// transaction, so either every one of them is undone or none is. The stack is
+/// stored in the database and survives restarting the app.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `count` - Number of operations to undo (defaults to 1)
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Operation>)` - The undone operations, newest first
+/// - `Err(String)` - Formatted error message if the undo failed; nothing was changed
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const [op] = await invoke('undo');
+/// showToast(`Undid ${op.label}`);
+/// ```
+#[tauri::command]
+async fn undo(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    count: Option<u32>,
+) -> Result<Vec<database::Operation>, String> {
+    with_change_events(&app, database::undo(&pool, count.unwrap_or(1)))
+        .await
+        .map_err(|e| format!("Failed to undo: {e}"))
+}
+
+/// Redoes the last `count` undone commands.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `count` - Number of operations to redo (defaults to 1)
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Operation>)` - The redone operations
+/// - `Err(String)` - Formatted error message if the redo failed; nothing was changed
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('redo', { count: 2 });
+/// ```
+#[tauri::command]
+async fn redo(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    count: Option<u32>,
+) -> Result<Vec<database::Operation>, String> {
+    with_change_events(&app, database::redo(&pool, count.unwrap_or(1)))
+        .await
+        .map_err(|e| format!("Failed to redo: {e}"))
+}
+
+/// Lists the operations available to undo and redo, e.g. for menu labels.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(UndoStack)` - `undo` and `redo` lists, top of stack first
+/// - `Err(String)` - Formatted error message if database operation fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const { undo } = await invoke('get_undo_stack');
+/// undoMenuItem.text = undo.length ? `Undo ${undo[0].label}` : 'Undo';
+/// ```
+#[tauri::command]
+async fn get_undo_stack(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+) -> Result<database::UndoStack, String> {
+    database::get_undo_stack(&pool)
+        .await
+        .map_err(|e| format!("Failed to get undo stack: {e}"))
+}
+
+/// Backs up the database now.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `backups` - Tauri-managed backup settings
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Backup)` - `{ file_name, reason, created_at, size_bytes }` of the new backup
+/// - `Err(String)` - Formatted error message if the backup fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const backup = await invoke('create_backup');
+/// ```
+#[tauri::command]
+async fn create_backup(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    backups: tauri::State<'_, backup::BackupConfig>,
+) -> Result<backup::Backup, String> {
+    backup::create_backup(&pool, &backups, backup::BackupReason::Manual)
+        .await
+        .map_err(|e| format!("Failed to create backup: {e}"))
+}
+
+/// Lists the available backups, newest first.
+///
+/// # Arguments
+///
+/// * `backups` - Tauri-managed backup settings
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(Vec<Backup>)` - Backups with their reason, such as `"startup"` or `"bulk_delete"`
+/// - `Err(String)` - Formatted error message if the backups directory can't be read
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const backups = await invoke('get_backups');
+/// ```
+#[tauri::command]
+async fn get_backups(
+    backups: tauri::State<'_, backup::BackupConfig>,
+) -> Result<Vec<backup::Backup>, String> {
+    backup::list_backups(&backups).map_err(|e| format!("Failed to list backups: {e}"))
+}
+
+/// Restores a backup over the database and restarts the app.
+///
+/// The backup is validated before anything changes, and the current database
+/// is backed up first so the restore can be reverted. On success the app
+/// restarts to open the restored database and this command doesn't return.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to restart the app
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `backups` - Tauri-managed backup settings
+/// * `file_name` - Backup to restore, as listed by `get_backups`
+/// * `passphrase` - Passphrase of an encrypted backup; the restored database is
+///   unlocked with it from then on
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Err(String)` - Formatted error message if the backup is invalid or the
+///   restore fails; an invalid backup leaves the database untouched
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('restore_backup', { fileName: backups[0].file_name });
+///
+/// // Encrypted backups, flagged `encrypted` by `get_backups`, need their passphrase
+/// await invoke('restore_backup', { fileName: backup.file_name, passphrase });
+/// ```
+#[tauri::command]
+async fn restore_backup(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    backups: tauri::State<'_, backup::BackupConfig>,
+    file_name: String,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    backup::restore_backup(
+        &pool,
+        Path::new(database::DATABASE_PATH),
+        &backups,
+        &file_name,
+        passphrase.as_deref(),
+    )
+    .await
+    .map_err(|e| format!("Failed to restore backup: {e}"))?;
+
+    app.restart()
+}
+
+/// Reports whether the database is encrypted and still locked.
+///
+/// The frontend calls this on startup and shows the unlock screen while the
+/// database is locked, since no other command can run until then.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to check whether the connection pool is managed yet
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(DatabaseStatus)` - `{ encrypted, locked }`
+/// - `Err(String)` - Formatted error message if the database file can't be read
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const { encrypted, locked } = await invoke('get_database_status');
+/// ```
+#[tauri::command]
+async fn get_database_status(app: tauri::AppHandle) -> Result<encryption::DatabaseStatus, String> {
+    let encrypted = encryption::is_encrypted(Path::new(database::DATABASE_PATH))
+        .map_err(|e| format!("Failed to read database: {e}"))?;
+
+    Ok(encryption::DatabaseStatus {
+        encrypted,
+        locked: app.try_state::<sqlx::SqlitePool>().is_none(),
+    })
+}
+
+/// Unlocks an encrypted database with its passphrase.
+///
+/// Opens the database, then backs it up, migrates it and purges the trash as
+/// a regular startup would, and finally hands the connection pool to Tauri so
+/// the other commands can run. Unlocking an unlocked database does nothing.
+///
+/// # Arguments
+///
+/// * `app` - Handle the connection pool is managed on
+/// * `backups` - Tauri-managed backup settings, for the startup backup
+/// * `passphrase` - The database passphrase
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(())` - The database is unlocked
+/// - `Err(String)` - `"Failed to unlock database: wrong passphrase"`, or
+///   another formatted error message if the database can't be opened
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('unlock_database', { passphrase });
+/// ```
+#[tauri::command]
+async fn unlock_database(
+    app: tauri::AppHandle,
+    backups: tauri::State<'_, backup::BackupConfig>,
+    passphrase: String,
+) -> Result<(), String> {
+    if app.try_state::<sqlx::SqlitePool>().is_some() {
+        return Ok(());
+    }
+
+    let pool = initialize_database(Some(&passphrase), &backups)
+        .await
+        .map_err(|e| format!("Failed to unlock database: {e}"))?;
+    app.manage(pool);
+
+    Ok(())
+}
+
+/// Encrypts the database with a passphrase and restarts the app.
+///
+/// The database is copied into an encrypted file that replaces it. The app
+/// then restarts locked, and the passphrase is needed every time it starts.
+/// Existing backups are not encrypted.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to restart the app
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `passphrase` - Passphrase to encrypt with; can't be empty
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Err(String)` - Formatted error message if the database is already
+///   encrypted, the passphrase is empty or the encrypted copy fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('enable_encryption', { passphrase });
+/// ```
+#[tauri::command]
+async fn enable_encryption(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    passphrase: String,
+) -> Result<(), String> {
+    encryption::enable_encryption(&pool, Path::new(database::DATABASE_PATH), &passphrase)
+        .await
+        .map_err(|e| format!("Failed to encrypt database: {e}"))?;
+
+    app.restart()
+}
+
+/// Re-encrypts the database with a new passphrase and restarts the app.
+///
+/// Backups taken before the change still need the old passphrase.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to restart the app
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `current_passphrase` - The passphrase the database is encrypted with now
+/// * `new_passphrase` - Passphrase to encrypt with from now on; can't be empty
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Err(String)` - Formatted error message if the current passphrase is
+///   wrong, the new one is empty or the re-encrypted copy fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('change_passphrase', {
+///   currentPassphrase: 'old passphrase',
+///   newPassphrase: 'new passphrase',
+/// });
+/// ```
+#[tauri::command]
+async fn change_passphrase(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    current_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), String> {
+    encryption::change_passphrase(
+        &pool,
+        Path::new(database::DATABASE_PATH),
+        &current_passphrase,
+        &new_passphrase,
+    )
+    .await
+    .map_err(|e| format!("Failed to change passphrase: {e}"))?;
+
+    app.restart()
+}
+
+/// Decrypts the database and restarts the app.
+///
+/// After the restart the database opens without a passphrase. Backups taken
+/// while it was encrypted stay encrypted.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to restart the app
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `passphrase` - The current passphrase, confirming the request
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Err(String)` - Formatted error message if the database isn't encrypted,
+///   the passphrase is wrong or the decrypted copy fails
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// await invoke('disable_encryption', { passphrase });
+/// ```
+#[tauri::command]
+async fn disable_encryption(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    passphrase: String,
+) -> Result<(), String> {
+    encryption::disable_encryption(&pool, Path::new(database::DATABASE_PATH), &passphrase)
+        .await
+        .map_err(|e| format!("Failed to decrypt database: {e}"))?;
+
+    app.restart()
+}
+
+/// Exports all accounts, categories, tags, transactions and transfers to a file.
+///
+/// JSON exports are a single versioned document with a checksum that
+/// `import_data` can restore from. CSV exports are a zip with one file per kind
+/// of record, with amounts in dollars and category paths spelled out, meant
+/// for spreadsheets.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `path` - Absolute path of the file to write; an existing file is overwritten
+/// * `format` - `"json"` or `"csv_zip"`
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(DataCounts)` - `{ accounts, categories, tags, transactions, transfers }` exported
+/// - `Err(String)` - Formatted error message if the data can't be read or the file written
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const counts = await invoke('export_data', {
+///   path: '/home/me/Documents/tally-2026-10-18.json',
+///   format: 'json'
+/// });
+/// ```
+#[tauri::command]
+async fn export_data(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    path: String,
+    format: export::ExportFormat,
+) -> Result<export::DataCounts, String> {
+    export::export_to_file(&pool, Path::new(&path), format)
+        .await
+        .map_err(|e| format!("Failed to export data: {e}"))
+}
+
+/// Imports a JSON export into an empty database.
+///
+/// The database is backed up first. The export's format version and checksum
+/// are verified and every reference between its records must resolve, or
+/// nothing is imported. Only a database without accounts, transactions, tags
+/// or user categories can be imported into.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `backups` - Tauri-managed backup settings
+/// * `path` - Absolute path of a JSON file written by `export_data`
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(DataCounts)` - `{ accounts, categories, tags, transactions, transfers }` imported
+/// - `Err(String)` - Formatted error message if the file is invalid, the
+///   database isn't empty or the import fails; nothing is imported then
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const counts = await invoke('import_data', {
+///   path: '/home/me/Documents/tally-2026-10-18.json'
+/// });
+/// ```
+#[tauri::command]
+async fn import_data(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    backups: tauri::State<'_, backup::BackupConfig>,
+    path: String,
+) -> Result<export::DataCounts, String> {
+    backup::create_backup(&pool, &backups, backup::BackupReason::Import)
+        .await
+        .map_err(|e| format!("Failed to back up before importing: {e}"))?;
+
+    with_change_events(&app, export::import_from_file(&pool, Path::new(&path)))
+        .await
+        .map_err(|e| format!("Failed to import data: {e}"))
+}
+
+/// Exports all transactions to a Ledger or Beancount journal.
+///
+/// Accounts become `Assets:` or `Liabilities:` accounts, categories become
+/// `Expenses:Parent:Child` accounts, and transfers become one entry between
+/// the two accounts. Cleared status is kept as the entry's marker, and the
+/// memo and reconciled flag as metadata. hledger reads the `"ledger"` format.
+///
+/// # Arguments
+///
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `path` - Absolute path of the file to write; an existing file is overwritten
+/// * `format` - `"ledger"` or `"beancount"`
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(usize)` - Number of entries in the journal
+/// - `Err(String)` - Formatted error message if the data can't be read or the file written
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const entries = await invoke('export_ledger', {
+///   path: '/home/me/Documents/tally.beancount',
+///   format: 'beancount'
+/// });
+/// ```
+#[tauri::command]
+async fn export_ledger(
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    path: String,
+    format: ledger::LedgerFormat,
+) -> Result<usize, String> {
+    ledger::export_ledger(&pool, Path::new(&path), format)
+        .await
+        .map_err(|e| format!("Failed to export journal: {e}"))
+}
+
+/// Imports a Ledger, hledger or Beancount journal.
+///
+/// The database is backed up first. Asset and liability accounts become
+/// accounts, expense and income accounts become categories, and entries
+/// between two accounts become transfers. Entries Tally can't represent, such
+/// as other commodities or more than two postings, are skipped and reported
+/// with their line.
+///
+/// # Arguments
+///
+/// * `app` - Handle used to emit change events
+/// * `pool` - Tauri-managed SQLite connection pool state
+/// * `backups` - Tauri-managed backup settings
+/// * `path` - Absolute path of the journal file
+/// * `format` - `"ledger"` for Ledger and hledger journals, or `"beancount"`
+///
+/// # Returns
+///
+/// Returns a `Result` containing:
+/// - `Ok(JournalImport)` - `{ accounts, categories, tags, transactions, transfers }`
+///   created and the `skipped` lines as `{ line, reason }`
+/// - `Err(String)` - Formatted error message if the file can't be read or the
+///   import fails; nothing is imported then
+///
+/// # Frontend Usage
+///
+/// ```javascript
+/// const result = await invoke('import_ledger', {
+///   path: '/home/me/Documents/2025.journal',
+///   format: 'ledger'
+/// });
+/// result.skipped.forEach(({ line, reason }) => console.warn(`line ${line}: ${reason}`));
+/// ```
+#[tauri::command]
+async fn import_ledger(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, sqlx::SqlitePool>,
+    backups: tauri::State<'_, backup::BackupConfig>,
+    path: String,
+    format: ledger::LedgerFormat,
+) -> Result<ledger::JournalImport, String> {
+    backup::create_backup(&pool, &backups, backup::BackupReason::Import)
+        .await
+        .map_err(|e| format!("Failed to back up before importing: {e}"))?;
+
+    with_change_events(&app, ledger::import_ledger(&pool, Path::new(&path), format))
+        .await
+        .map_err(|e| format!("Failed to import journal: {e}"))
 }
@@ -1 +0,0 @@
-mod database_tests;